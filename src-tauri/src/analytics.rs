@@ -0,0 +1,309 @@
+//! 执行历史统计
+//!
+//! 统计某个配置文件最近若干次执行中每个步骤和测试用例的通过率、耗时变化，
+//! 并标记不稳定（flaky）的步骤和用例：配置文件内容没有变化，结果却在通过和失败之间翻转。
+
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::config::ExecutionStatus;
+use crate::error::Result;
+use crate::history::{History, RunFilter, RunRecord};
+
+/// 未指定时统计的执行次数
+pub const DEFAULT_RUNS: usize = 20;
+
+/// 一个配置文件的执行统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryStats {
+    pub file: String,
+    /// 参与统计的执行次数
+    pub runs: usize,
+    /// 按首次出现的顺序
+    pub steps: Vec<TestStats>,
+    pub cases: Vec<TestStats>,
+}
+
+impl HistoryStats {
+    pub fn step(&self, id: &str) -> Option<&TestStats> {
+        self.steps.iter().find(|s| s.id == id)
+    }
+
+    pub fn case(&self, id: &str) -> Option<&TestStats> {
+        self.cases.iter().find(|c| c.id == id)
+    }
+
+    pub fn is_flaky_step(&self, id: &str) -> bool {
+        self.step(id).is_some_and(|s| s.flaky)
+    }
+
+    pub fn is_flaky_case(&self, id: &str) -> bool {
+        self.case(id).is_some_and(|c| c.flaky)
+    }
+}
+
+/// 单个步骤或测试用例的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestStats {
+    pub id: String,
+    pub name: String,
+    /// 执行次数（不含被取消的）
+    pub runs: usize,
+    pub passed: usize,
+    pub failed: usize,
+    /// 通过率，0 ~ 1
+    pub pass_rate: f64,
+    pub avg_duration_ms: u64,
+    pub max_duration_ms: u64,
+    /// 每次执行的耗时，按时间先后
+    pub latency: Vec<LatencyPoint>,
+    /// 配置文件未修改的相邻两次执行之间结果翻转的次数
+    pub flips: usize,
+    pub flaky: bool,
+}
+
+/// 一次执行的耗时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyPoint {
+    pub run_id: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    pub passed: bool,
+}
+
+/// 一次执行中的结果，用于累计
+struct Outcome<'a> {
+    run: &'a RunRecord,
+    passed: bool,
+    duration_ms: u64,
+}
+
+/// 读取配置文件最近 `last_runs` 次执行并统计
+pub fn file_stats(history: &History, file: &str, profile: Option<String>, last_runs: usize) -> Result<HistoryStats> {
+    let filter = RunFilter { file: Some(file.to_string()), profile, limit: Some(last_runs), ..Default::default() };
+    let mut runs = Vec::new();
+    for summary in history.list(&filter)? {
+        // 索引中有但记录文件已丢失的跳过
+        if let Ok(run) = history.load(&summary.id) {
+            runs.push(run);
+        }
+    }
+    let mut stats = analyze(&runs);
+    stats.file = file.to_string();
+    Ok(stats)
+}
+
+/// 统计一组执行记录，顺序不限
+pub fn analyze(runs: &[RunRecord]) -> HistoryStats {
+    let mut runs: Vec<&RunRecord> = runs.iter().collect();
+    runs.sort_by_key(|run| run.summary.started_at);
+
+    let mut steps: IndexMap<String, (String, Vec<Outcome>)> = IndexMap::new();
+    let mut cases: IndexMap<String, (String, Vec<Outcome>)> = IndexMap::new();
+
+    for run in &runs {
+        for case in run.cases.iter().filter(|c| c.status != ExecutionStatus::Cancelled) {
+            let entry = cases.entry(case.case_id.clone()).or_insert_with(|| (case.name.clone(), Vec::new()));
+            entry.1.push(Outcome { run, passed: case.success, duration_ms: case.duration_ms });
+        }
+
+        let results = run.steps.iter().chain(run.cases.iter().flat_map(|c| c.steps.iter()));
+        for step in results.filter(|s| s.status != ExecutionStatus::Cancelled) {
+            let entry = steps.entry(step.step_name.clone()).or_insert_with(|| (step.step_name.clone(), Vec::new()));
+            entry.1.push(Outcome { run, passed: step.success, duration_ms: step.duration_ms });
+        }
+    }
+
+    let collect = |map: IndexMap<String, (String, Vec<Outcome>)>| -> Vec<TestStats> {
+        map.into_iter().map(|(id, (name, outcomes))| test_stats(id, name, &outcomes)).collect()
+    };
+
+    HistoryStats {
+        file: runs.first().map(|r| r.summary.file.clone()).unwrap_or_default(),
+        runs: runs.len(),
+        steps: collect(steps),
+        cases: collect(cases),
+    }
+}
+
+fn test_stats(id: String, name: String, outcomes: &[Outcome]) -> TestStats {
+    let runs = outcomes.len();
+    let passed = outcomes.iter().filter(|o| o.passed).count();
+    let total_ms: u64 = outcomes.iter().map(|o| o.duration_ms).sum();
+
+    // 同一次执行中的多个结果（如步骤被多个用例使用）合并为一个，全部通过才算通过；
+    // 同一次执行的结果在 outcomes 中相邻
+    let mut per_run: Vec<(&RunRecord, bool)> = Vec::new();
+    for outcome in outcomes {
+        match per_run.last_mut() {
+            Some((run, passed)) if run.summary.id == outcome.run.summary.id => *passed &= outcome.passed,
+            _ => per_run.push((outcome.run, outcome.passed)),
+        }
+    }
+
+    // 只比较配置文件内容相同（哈希都已知且相等）的相邻两次执行
+    let flips = per_run
+        .windows(2)
+        .filter(|pair| {
+            let ((a, a_passed), (b, b_passed)) = (pair[0], pair[1]);
+            let same_config = a.summary.config_hash.is_some() && a.summary.config_hash == b.summary.config_hash;
+            same_config && a_passed != b_passed
+        })
+        .count();
+
+    TestStats {
+        id,
+        name,
+        runs,
+        passed,
+        failed: runs - passed,
+        pass_rate: if runs > 0 { passed as f64 / runs as f64 } else { 0.0 },
+        avg_duration_ms: total_ms.checked_div(runs as u64).unwrap_or(0),
+        max_duration_ms: outcomes.iter().map(|o| o.duration_ms).max().unwrap_or(0),
+        latency: outcomes
+            .iter()
+            .map(|o| LatencyPoint {
+                run_id: o.run.summary.id.clone(),
+                started_at: o.run.summary.started_at,
+                duration_ms: o.duration_ms,
+                passed: o.passed,
+            })
+            .collect(),
+        flips,
+        flaky: flips > 0,
+    }
+}
+
+// ============ Tauri 命令 ============
+
+/// 统计配置文件最近 `last_runs` 次执行（默认 20 次）中各步骤和用例的通过率、耗时及是否不稳定
+#[tauri::command]
+pub fn get_history_stats(file_path: String, profile: Option<String>, last_runs: Option<usize>) -> Result<HistoryStats> {
+    file_stats(&History::open()?, &file_path, profile, last_runs.unwrap_or(DEFAULT_RUNS))
+}
+
+/// 同 `get_history_stats`，只保留不稳定的步骤和用例
+#[tauri::command]
+pub fn list_flaky_tests(file_path: String, profile: Option<String>, last_runs: Option<usize>) -> Result<HistoryStats> {
+    let mut stats = file_stats(&History::open()?, &file_path, profile, last_runs.unwrap_or(DEFAULT_RUNS))?;
+    stats.steps.retain(|s| s.flaky);
+    stats.cases.retain(|c| c.flaky);
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ExecutionResult, TestCaseResult};
+    use crate::history::{RunKind, RunSummary};
+
+    fn step(name: &str, passed: bool) -> ExecutionResult {
+        ExecutionResult {
+            success: passed,
+            status: if passed { ExecutionStatus::Passed } else { ExecutionStatus::Failed },
+            step_name: name.to_string(),
+            request_url: String::new(),
+            request_method: "GET".to_string(),
+            request_headers: IndexMap::new(),
+            request_body: None,
+            response_status: if passed { 200 } else { 500 },
+            response_headers: IndexMap::new(),
+            response_body: None,
+            validations: vec![],
+            duration_ms: 10,
+            error: None,
+            error_kind: None,
+            attempts: vec![],
+        }
+    }
+
+    fn case(id: &str, steps: Vec<ExecutionResult>) -> TestCaseResult {
+        let success = steps.iter().all(|s| s.success);
+        TestCaseResult {
+            case_id: id.to_string(),
+            name: id.to_string(),
+            success,
+            status: if success { ExecutionStatus::Passed } else { ExecutionStatus::Failed },
+            steps,
+            duration_ms: 20,
+            error: None,
+        }
+    }
+
+    /// 第 `index` 次执行，`config_hash` 相同表示配置没有修改
+    fn run(index: i64, config_hash: &str, cases: Vec<TestCaseResult>) -> RunRecord {
+        RunRecord {
+            summary: RunSummary {
+                id: format!("run-{}", index),
+                kind: RunKind::Suite,
+                target: String::new(),
+                workspace: None,
+                file: "/tmp/api.yml".to_string(),
+                config_hash: Some(config_hash.to_string()),
+                profile: "dev".to_string(),
+                started_at: DateTime::<Utc>::from_timestamp(1_700_000_000 + index, 0).unwrap(),
+                duration_ms: 0,
+                total: 0,
+                passed: 0,
+                failed: 0,
+                success: false,
+            },
+            steps: vec![],
+            cases,
+        }
+    }
+
+    #[test]
+    fn flip_between_runs_is_flaky() {
+        let stats = analyze(&[
+            run(1, "a", vec![case("order", vec![step("login", true)])]),
+            run(2, "a", vec![case("order", vec![step("login", false)])]),
+            run(3, "a", vec![case("order", vec![step("login", true)])]),
+        ]);
+
+        let login = stats.step("login").unwrap();
+        assert_eq!((login.runs, login.passed, login.flips), (3, 2, 2));
+        assert!(login.flaky);
+        assert!(stats.is_flaky_case("order"));
+    }
+
+    #[test]
+    fn different_results_within_one_run_are_not_a_flip() {
+        // login 同时被两个用例使用，同一次执行中一次通过一次失败
+        let runs: Vec<RunRecord> = (1..=3)
+            .map(|i| {
+                run(i, "a", vec![case("order", vec![step("login", true)]), case("refund", vec![step("login", false)])])
+            })
+            .collect();
+        let stats = analyze(&runs);
+
+        let login = stats.step("login").unwrap();
+        assert_eq!((login.runs, login.passed, login.flips), (6, 3, 0));
+        assert!(!login.flaky);
+    }
+
+    #[test]
+    fn result_change_after_config_change_is_not_a_flip() {
+        let stats = analyze(&[
+            run(1, "a", vec![case("order", vec![step("login", false)])]),
+            run(2, "b", vec![case("order", vec![step("login", true)])]),
+        ]);
+
+        assert!(!stats.is_flaky_step("login"));
+    }
+
+    #[test]
+    fn cancelled_results_are_ignored() {
+        let mut cancelled = step("login", false);
+        cancelled.status = ExecutionStatus::Cancelled;
+        let stats = analyze(&[
+            run(1, "a", vec![case("order", vec![step("login", true)])]),
+            run(2, "a", vec![case("order", vec![cancelled])]),
+            run(3, "a", vec![case("order", vec![step("login", true)])]),
+        ]);
+
+        let login = stats.step("login").unwrap();
+        assert_eq!((login.runs, login.flips), (2, 0));
+    }
+}
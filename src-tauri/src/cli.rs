@@ -0,0 +1,201 @@
+//! 命令行模式
+//!
+//! 以 `run` 子命令启动时不打开窗口，直接执行测试套件并以退出码表示结果，便于在 CI 中使用：
+//!
+//! ```text
+//! yuml-ddt run <配置文件> [--tags <标签表达式>] [--workers <并发数>] [--reporter junit|html|allure] [--output <报告路径>]
+//!               [--vault <密钥库文件>] [--key-file <密钥文件>]
+//! ```
+//!
+//! 配置中引用了 `{secret:名称}` 时，用 `--key-file` 或环境变量 `YUML_DDT_VAULT_PASSWORD`（主密码）解锁密钥库，
+//! 密钥库默认为应用数据目录下的 `secrets.vault`。
+//!
+//! 退出码：0 全部通过，1 存在失败的用例，2 参数或配置错误。
+//!
+//! Windows 发布版是图形界面程序，没有自己的控制台，执行时输出到启动它的命令行窗口。
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::analytics::{self, HistoryStats};
+use crate::config::ExecutionStatus;
+use crate::error::{Error, Result};
+use crate::history::History;
+use crate::loader;
+use crate::report::{self, ReportFormat};
+use crate::runner::SuiteRunner;
+use crate::secrets::{Secrets, Vault, VaultKey};
+use crate::tags::TagFilter;
+
+const USAGE: &str = "用法: yuml-ddt run <配置文件> [--tags <标签表达式>] [--workers <并发数>] [--reporter junit|html|allure] [--output <报告路径>] [--vault <密钥库文件>] [--key-file <密钥文件>]";
+
+/// 密钥库主密码的环境变量
+const VAULT_PASSWORD_ENV: &str = "YUML_DDT_VAULT_PASSWORD";
+
+/// 命令行参数
+#[derive(Debug, Default)]
+struct RunArgs {
+    file: String,
+    tags: String,
+    workers: Option<usize>,
+    reporter: Option<ReportFormat>,
+    output: Option<PathBuf>,
+    vault: Option<PathBuf>,
+    key_file: Option<PathBuf>,
+}
+
+impl RunArgs {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            let mut value = |name: &str| {
+                iter.next()
+                    .cloned()
+                    .ok_or_else(|| Error::Validation(format!("参数 {} 缺少值", name)))
+            };
+            match arg.as_str() {
+                "--tags" => parsed.tags = value(arg)?,
+                "--workers" => {
+                    let workers = value(arg)?;
+                    let workers = workers
+                        .parse()
+                        .map_err(|_| Error::Validation(format!("无效的并发数: {}", workers)))?;
+                    parsed.workers = Some(workers);
+                }
+                "--reporter" => parsed.reporter = Some(ReportFormat::parse(&value(arg)?)?),
+                "--output" => parsed.output = Some(PathBuf::from(value(arg)?)),
+                "--vault" => parsed.vault = Some(PathBuf::from(value(arg)?)),
+                "--key-file" => parsed.key_file = Some(PathBuf::from(value(arg)?)),
+                other if other.starts_with("--") => {
+                    return Err(Error::Validation(format!("未知参数: {}", other)));
+                }
+                other if parsed.file.is_empty() => parsed.file = other.to_string(),
+                other => return Err(Error::Validation(format!("多余的参数: {}", other))),
+            }
+        }
+
+        if parsed.file.is_empty() {
+            return Err(Error::Validation("缺少配置文件路径".to_string()));
+        }
+        Ok(parsed)
+    }
+}
+
+/// 连接到父进程的控制台，使 Windows 发布版（图形界面程序）的输出能显示在命令行中
+#[cfg(windows)]
+fn attach_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    // SAFETY: 只传入常量参数；父进程没有控制台或已连接时调用失败，忽略即可
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+/// 处理命令行参数：以 `run` 子命令启动时执行并返回退出码，否则返回 `None`（启动图形界面）
+pub fn run_from_args(args: &[String]) -> Option<i32> {
+    if args.first().map(String::as_str) != Some("run") {
+        return None;
+    }
+    attach_console();
+
+    let args = match RunArgs::parse(&args[1..]) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return Some(2);
+        }
+    };
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("无法启动异步运行时: {}", e);
+            return Some(2);
+        }
+    };
+
+    match runtime.block_on(run(&args)) {
+        Ok(success) => Some(if success { 0 } else { 1 }),
+        Err(e) => {
+            eprintln!("{}", e);
+            Some(2)
+        }
+    }
+}
+
+async fn run(args: &RunArgs) -> Result<bool> {
+    let filter = TagFilter::parse(&args.tags)?;
+    let config = loader::load_config_file(Path::new(&args.file))?;
+    let mut runner = SuiteRunner::new(config.clone());
+    if let Some(workers) = args.workers {
+        runner = runner.max_workers(workers);
+    }
+    if let Some(secrets) = vault_secrets(args)? {
+        runner = runner.secrets(secrets);
+    }
+
+    let result = runner.run(&filter, None).await?;
+
+    for case in &result.cases {
+        let mark = match case.status {
+            ExecutionStatus::Passed => "✅",
+            ExecutionStatus::Cancelled => "⏹",
+            _ => "❌",
+        };
+        println!("{} {} ({}ms)", mark, case.name, case.duration_ms);
+        for step in case.steps.iter().filter(|s| !s.success) {
+            let reason = step.error.clone().unwrap_or_else(|| format!("HTTP {}", step.response_status));
+            println!("   - {}: {}", step.step_name, reason);
+        }
+    }
+    println!(
+        "共 {} 个用例，通过 {}，失败 {}，耗时 {}ms",
+        result.total, result.passed, result.failed, result.duration_ms
+    );
+
+    if let Some(format) = args.reporter {
+        let output = args.output.clone().unwrap_or_else(|| PathBuf::from(format.default_output()));
+        let name = Path::new(&args.file)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let stats = history_stats(&args.file);
+        report::write_report(&result, format, &config, &name, stats.as_ref(), &output)?;
+        println!("报告已写入 {}", output.display());
+    }
+
+    Ok(result.success)
+}
+
+/// 提供了密钥文件或主密码时解锁密钥库，否则不解析密钥引用
+fn vault_secrets(args: &RunArgs) -> Result<Option<Arc<Secrets>>> {
+    let key = match (&args.key_file, std::env::var(VAULT_PASSWORD_ENV)) {
+        (Some(path), _) => VaultKey::KeyFile(path.clone()),
+        (None, Ok(password)) if !password.is_empty() => VaultKey::Password(password),
+        _ => return Ok(None),
+    };
+    let path = match &args.vault {
+        Some(path) => path.clone(),
+        None => Vault::default_path()?,
+    };
+    if !path.exists() {
+        return Err(Error::Vault(format!("密钥库文件不存在: {}", path.display())));
+    }
+    Ok(Some(Vault::unlock(&path, &key)?.secrets()))
+}
+
+/// 本机有该配置文件的执行历史时用于在报告中标出不稳定的步骤；命令行执行本身不写入历史
+fn history_stats(file: &str) -> Option<HistoryStats> {
+    let stats = analytics::file_stats(&History::open().ok()?, file, None, analytics::DEFAULT_RUNS).ok()?;
+    (stats.runs > 0).then_some(stats)
+}
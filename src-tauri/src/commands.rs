@@ -3,26 +3,45 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use indexmap::IndexMap;
 use serde_json::Value as JsonValue;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
 
-use crate::config::{ExecutionResult, StepInfo};
+use crate::analytics;
+use crate::config::{ExecutionResult, ExecutionStatus, RenderedRequest, StepInfo, SuiteResult, TestCaseInfo, TestCaseResult};
+use crate::convert::har::{self, HarEntry, HarImport};
+use crate::convert::openapi::{self, OpenApiOptions};
+use crate::convert::{self, curl, postman, ExportSummary, ImportSummary};
 use crate::engine::TestEngine;
+use crate::error::{Error, Result};
+use crate::events::{ExecutionEvent, ExecutionObserver, TauriObserver};
+use crate::execution::ExecutionRegistry;
+use crate::history::{self, History, RunKind, RunRecord};
+use crate::lint::{self, Diagnostic};
+use crate::mask::Masker;
+use crate::load::{self, LoadOptions, LoadReport};
+use crate::loader;
+use crate::report::{self, ReportFormat};
+use crate::runner::SuiteRunner;
+use crate::secrets::VaultState;
+use crate::tags::TagFilter;
 
 /// 列出目录下的 YAML 文件
 #[tauri::command]
-pub fn list_yaml_files(directory: String) -> Result<Vec<String>, String> {
+pub fn list_yaml_files(directory: String) -> Result<Vec<String>> {
     let path = Path::new(&directory);
     
     if !path.exists() {
-        return Err(format!("目录不存在: {}", directory));
+        return Err(Error::io("目录不存在", path, std::io::ErrorKind::NotFound.into()));
     }
     
     let mut files = Vec::new();
     
-    fn scan_dir(dir: &Path, files: &mut Vec<String>) -> Result<(), String> {
+    fn scan_dir(dir: &Path, files: &mut Vec<String>) -> Result<()> {
         if dir.is_dir() {
-            for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
-                let entry = entry.map_err(|e| e.to_string())?;
+            for entry in fs::read_dir(dir).map_err(|e| Error::io("读取目录失败", dir, e))? {
+                let entry = entry.map_err(|e| Error::io("读取目录失败", dir, e))?;
                 let path = entry.path();
                 
                 if path.is_dir() {
@@ -47,39 +66,404 @@ pub fn list_yaml_files(directory: String) -> Result<Vec<String>, String> {
 
 /// 读取 YAML 文件内容
 #[tauri::command]
-pub fn read_yaml_file(file_path: String) -> Result<String, String> {
+pub fn read_yaml_file(file_path: String) -> Result<String> {
     fs::read_to_string(&file_path)
-        .map_err(|e| format!("读取文件失败: {}", e))
+        .map_err(|e| Error::io("读取文件失败", &file_path, e))
 }
 
 /// 保存 YAML 文件
 #[tauri::command]
-pub fn save_yaml_file(file_path: String, content: String) -> Result<(), String> {
+pub fn save_yaml_file(file_path: String, content: String) -> Result<()> {
     fs::write(&file_path, content)
-        .map_err(|e| format!("保存文件失败: {}", e))
+        .map_err(|e| Error::io("保存文件失败", &file_path, e))
 }
 
 /// 解析 YAML 文件中的 steps
+///
+/// 传入 `file_path` 时，imports 相对该文件解析
 #[tauri::command]
-pub fn parse_yaml_steps(content: String) -> Result<Vec<StepInfo>, String> {
-    let engine = TestEngine::from_yaml(&content)?;
+pub fn parse_yaml_steps(content: String, file_path: Option<String>) -> Result<Vec<StepInfo>> {
+    let engine = match file_path {
+        Some(path) => TestEngine::from_yaml_at(&content, &path)?,
+        None => TestEngine::from_yaml(&content)?,
+    };
     Ok(engine.get_step_list())
 }
 
+/// 静态检查 YAML 内容，返回诊断列表；传入 `file_path` 时，imports 相对该文件解析
+#[tauri::command]
+pub fn lint_yaml(content: String, file_path: Option<String>) -> Vec<Diagnostic> {
+    lint::lint_str(&content, file_path.as_deref().map(Path::new))
+}
+
 /// 获取配置文件的 step 列表
 #[tauri::command]
-pub fn get_step_list(file_path: String) -> Result<Vec<StepInfo>, String> {
+pub fn get_step_list(file_path: String) -> Result<Vec<StepInfo>> {
     let engine = TestEngine::from_file(&file_path)?;
     Ok(engine.get_step_list())
 }
 
-/// 执行单个 step
+/// 执行单个 step，`execution_id` 可用于 `cancel_execution`
 #[tauri::command]
 pub async fn execute_step(
     file_path: String,
     step_name: String,
     variables: Option<HashMap<String, JsonValue>>,
-) -> Result<ExecutionResult, String> {
+    execution_id: Option<String>,
+    executions: State<'_, ExecutionRegistry>,
+    vault: State<'_, VaultState>,
+    app: AppHandle,
+) -> Result<ExecutionResult> {
+    let mut engine = TestEngine::from_file(&file_path)?;
+    let (id, token) = executions.start(execution_id)?;
+    let run = RunRecord::start(RunKind::Step, &step_name, &file_path, engine.config());
+    let observer = start_run(app, &id, &run);
+    engine.set_cancellation_token(token);
+    engine.set_secrets(vault.secrets());
+    engine.set_observer(observer.clone());
+
+    let result = engine.execute_step(&step_name, variables).await;
+    executions.finish(&id);
+    finish_run(&observer, &run, [result.status]);
+    history::save(&run.finish_steps(vec![result.clone()]));
+    Ok(result)
+}
+
+/// 预览 step 最终发出的请求，不发送 HTTP 请求；密钥值显示为 `******`
+#[tauri::command]
+pub fn preview_step(
+    file_path: String,
+    step_name: String,
+    variables: Option<HashMap<String, JsonValue>>,
+    vault: State<'_, VaultState>,
+) -> Result<RenderedRequest> {
+    let mut engine = TestEngine::from_file(&file_path)?;
+    engine.set_secrets(vault.secrets());
+    engine.render_step(&step_name, variables)
+}
+
+/// 将 step 导出为 curl 命令，`mask_auth` 为 true 时隐藏认证信息
+///
+/// 配置了认证时先获取 token，导出的命令与实际发出的请求一致
+#[tauri::command]
+pub async fn export_curl(
+    file_path: String,
+    step_name: String,
+    variables: Option<HashMap<String, JsonValue>>,
+    mask_auth: bool,
+    vault: State<'_, VaultState>,
+) -> Result<String> {
+    let mut engine = TestEngine::from_file(&file_path)?;
+    engine.set_secrets(vault.secrets());
+    engine.fetch_token().await?;
+    let request = engine.render_step(&step_name, variables)?;
+    let masker = mask_auth.then(|| Masker::new(engine.auth_key().as_slice()));
+    Ok(curl::to_curl(&request, masker.as_ref()))
+}
+
+/// 将 curl 命令转换为 step 的 YAML 片段，URL 按配置文件当前环境还原为 path
+#[tauri::command]
+pub fn import_curl(file_path: String, command: String, step_name: Option<String>) -> Result<String> {
+    let config = loader::load_config_file(Path::new(&file_path))?;
+    let env = TestEngine::resolve_env_config(&config)?;
+
+    let request = curl::parse_curl(&command)?;
+    let step = curl::to_step(&request, Some(&env))?;
+    let id = step_name.unwrap_or_else(|| convert::step_id(&step.method, &step.path, &config.steps));
+
+    convert::steps_to_yaml(&IndexMap::from([(id, step)]))
+}
+
+/// 由 OpenAPI / Swagger 文档生成测试配置文件，写入 `output_dir`
+#[tauri::command]
+pub fn import_openapi(spec_path: String, output_dir: String, group_by_tag: bool) -> Result<ImportSummary> {
+    let options = OpenApiOptions { group_by_tag };
+    let import = openapi::import_openapi_file(Path::new(&spec_path), &options)?;
+    let files = convert::write_files(Path::new(&output_dir), &import.files)?;
+
+    Ok(ImportSummary { files, steps: import.steps, skipped: import.skipped })
+}
+
+/// 由 Postman 集合（及可选的环境文件）生成测试配置文件，写入 `output_dir`
+#[tauri::command]
+pub fn import_postman(
+    collection_path: String,
+    environment_path: Option<String>,
+    output_dir: String,
+) -> Result<ImportSummary> {
+    let environment = environment_path.as_deref().map(Path::new);
+    let import = postman::import_postman_files(Path::new(&collection_path), environment)?;
+    let files = convert::write_files(Path::new(&output_dir), &import.files)?;
+
+    Ok(ImportSummary { files, steps: import.steps, skipped: import.skipped })
+}
+
+/// 列出 HAR 文件中的请求
+#[tauri::command]
+pub fn list_har_entries(har_path: String) -> Result<Vec<HarEntry>> {
+    let har = har::read_har(Path::new(&har_path))?;
+    Ok(har::list_entries(&har))
+}
+
+/// 将 HAR 中选中的请求转换为 step 的 YAML 片段，`entries` 为空时导入默认选中的请求
+#[tauri::command]
+pub fn import_har(file_path: String, har_path: String, entries: Option<Vec<usize>>) -> Result<HarImport> {
+    let config = loader::load_config_file(Path::new(&file_path))?;
+    let env = TestEngine::resolve_env_config(&config)?;
+    let har = har::read_har(Path::new(&har_path))?;
+
+    har::import_har(&har, entries.as_deref(), Some(&env), &config.steps)
+}
+
+/// 将配置文件的步骤和当前环境导出为 Postman v2.1 集合，写入 `output_path`
+#[tauri::command]
+pub fn export_postman(file_path: String, output_path: String) -> Result<ExportSummary> {
+    let config = loader::load_config_file(Path::new(&file_path))?;
+    let env = TestEngine::resolve_env_config(&config)?;
+    let name = file_stem(&file_path);
+
+    let (collection, skipped) = postman::export_postman(&config, &env, &name);
+    let content = serde_json::to_string_pretty(&collection)
+        .map_err(|e| Error::Internal(format!("序列化集合失败: {}", e)))?;
+    write_export(&output_path, &content)?;
+
+    Ok(ExportSummary { file: output_path, skipped })
+}
+
+/// 由配置文件的步骤生成 OpenAPI 文档骨架，写入 `output_path`
+///
+/// `results` 为步骤的执行结果，用于生成响应示例；路径以 `.json` 结尾时输出 JSON，否则输出 YAML
+#[tauri::command]
+pub fn export_openapi(
+    file_path: String,
+    output_path: String,
+    results: Option<Vec<ExecutionResult>>,
+) -> Result<ExportSummary> {
+    let config = loader::load_config_file(Path::new(&file_path))?;
+    let env = TestEngine::resolve_env_config(&config)?;
+    let title = file_stem(&file_path);
+
+    let (document, skipped) = openapi::export_openapi(&config, &env, &title, results.as_deref().unwrap_or_default());
+    let content = if output_path.to_lowercase().ends_with(".json") {
+        serde_json::to_string_pretty(&document).map_err(|e| Error::Internal(format!("序列化文档失败: {}", e)))?
+    } else {
+        serde_yaml::to_string(&document).map_err(|e| Error::Internal(format!("序列化文档失败: {}", e)))?
+    };
+    write_export(&output_path, &content)?;
+
+    Ok(ExportSummary { file: output_path, skipped })
+}
+
+fn file_stem(file_path: &str) -> String {
+    Path::new(file_path)
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "tests".to_string())
+}
+
+fn write_export(output_path: &str, content: &str) -> Result<()> {
+    let path = Path::new(output_path);
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| Error::io("无法创建目录", dir, e))?;
+    }
+    fs::write(path, content).map_err(|e| Error::io("无法写入文件", path, e))
+}
+
+/// 获取配置文件的测试用例列表
+#[tauri::command]
+pub fn get_test_case_list(file_path: String) -> Result<Vec<TestCaseInfo>> {
+    let engine = TestEngine::from_file(&file_path)?;
+    Ok(engine.get_test_case_list())
+}
+
+/// 执行单个测试用例
+#[tauri::command]
+pub async fn run_test_case(
+    file_path: String,
+    case_name: String,
+    variables: Option<HashMap<String, JsonValue>>,
+    execution_id: Option<String>,
+    executions: State<'_, ExecutionRegistry>,
+    vault: State<'_, VaultState>,
+    app: AppHandle,
+) -> Result<TestCaseResult> {
     let mut engine = TestEngine::from_file(&file_path)?;
-    Ok(engine.execute_step(&step_name, variables).await)
+    let (id, token) = executions.start(execution_id)?;
+    let run = RunRecord::start(RunKind::TestCase, &case_name, &file_path, engine.config());
+    let observer = start_run(app, &id, &run);
+    engine.set_cancellation_token(token);
+    engine.set_secrets(vault.secrets());
+    engine.set_observer(observer.clone());
+
+    let result = engine.run_test_case(&case_name, variables).await;
+    executions.finish(&id);
+    finish_run(&observer, &run, [result.status]);
+    history::save(&run.finish_cases(vec![result.clone()]));
+    Ok(result)
+}
+
+/// 执行匹配标签表达式的测试用例，如 `smoke and not slow`；不传表达式时执行全部
+#[tauri::command]
+pub async fn run_test_cases(
+    file_path: String,
+    tags: Option<String>,
+    variables: Option<HashMap<String, JsonValue>>,
+    execution_id: Option<String>,
+    executions: State<'_, ExecutionRegistry>,
+    vault: State<'_, VaultState>,
+    app: AppHandle,
+) -> Result<Vec<TestCaseResult>> {
+    let filter = TagFilter::parse(tags.as_deref().unwrap_or_default())?;
+    let mut engine = TestEngine::from_file(&file_path)?;
+    let (id, token) = executions.start(execution_id)?;
+    let run = RunRecord::start(RunKind::TestCases, tags.as_deref().unwrap_or_default(), &file_path, engine.config());
+    let observer = start_run(app, &id, &run);
+    engine.set_cancellation_token(token);
+    engine.set_secrets(vault.secrets());
+    engine.set_observer(observer.clone());
+
+    let results = engine.run_test_cases(&filter, variables).await;
+    executions.finish(&id);
+    finish_run(&observer, &run, results.iter().map(|r| r.status));
+    history::save(&run.finish_cases(results.clone()));
+    Ok(results)
+}
+
+/// 并行执行测试套件，`workers` 覆盖配置中的 `global.workers`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn run_suite(
+    file_path: String,
+    tags: Option<String>,
+    workers: Option<usize>,
+    variables: Option<HashMap<String, JsonValue>>,
+    execution_id: Option<String>,
+    executions: State<'_, ExecutionRegistry>,
+    vault: State<'_, VaultState>,
+    app: AppHandle,
+) -> Result<SuiteResult> {
+    let filter = TagFilter::parse(tags.as_deref().unwrap_or_default())?;
+    let config = loader::load_config_file(Path::new(&file_path))?;
+    let run = RunRecord::start(RunKind::Suite, tags.as_deref().unwrap_or_default(), &file_path, &config);
+    let mut runner = SuiteRunner::new(config);
+    if let Some(workers) = workers {
+        runner = runner.max_workers(workers);
+    }
+    let (id, token) = executions.start(execution_id)?;
+    let observer = start_run(app, &id, &run);
+
+    let result = runner
+        .cancellation_token(token)
+        .secrets(vault.secrets())
+        .observer(observer.clone())
+        .run(&filter, variables)
+        .await;
+    executions.finish(&id);
+    match &result {
+        Ok(result) => finish_run(&observer, &run, result.cases.iter().map(|c| c.status)),
+        Err(_) => finish_run(&observer, &run, [ExecutionStatus::Error]),
+    }
+    if let Ok(result) = &result {
+        history::save(&run.finish_cases(result.cases.clone()));
+    }
+    result
+}
+
+/// 把套件执行结果保存为报告
+///
+/// 报告中的套件名称取配置文件名，认证配置中放置 token 的请求头 / 查询参数会被脱敏，
+/// 并根据该文件的执行历史标出不稳定的用例和步骤；
+/// Allure 格式的 `output_path` 为结果目录
+#[tauri::command]
+pub fn export_report(
+    file_path: String,
+    result: SuiteResult,
+    format: ReportFormat,
+    output_path: String,
+) -> Result<()> {
+    let config = loader::load_config_file(Path::new(&file_path))?;
+    let stats = History::open()
+        .and_then(|history| analytics::file_stats(&history, &file_path, None, analytics::DEFAULT_RUNS))
+        .ok();
+    report::write_report(&result, format, &config, &file_stem(&file_path), stats.as_ref(), Path::new(&output_path))
+}
+
+/// 压测：用多个虚拟用户反复执行某个 step 或测试用例
+#[tauri::command]
+pub async fn run_load_test(
+    file_path: String,
+    options: LoadOptions,
+    execution_id: Option<String>,
+    executions: State<'_, ExecutionRegistry>,
+    vault: State<'_, VaultState>,
+) -> Result<LoadReport> {
+    let (id, token) = executions.start(execution_id)?;
+
+    let result = load::run_load_test_file(&file_path, options, token, vault.secrets()).await;
+    executions.finish(&id);
+    result
+}
+
+/// 执行匹配标签表达式的所有步骤
+#[tauri::command]
+pub async fn run_tagged_steps(
+    file_path: String,
+    tags: String,
+    variables: Option<HashMap<String, JsonValue>>,
+    execution_id: Option<String>,
+    executions: State<'_, ExecutionRegistry>,
+    vault: State<'_, VaultState>,
+    app: AppHandle,
+) -> Result<Vec<ExecutionResult>> {
+    let filter = TagFilter::parse(&tags)?;
+    let mut engine = TestEngine::from_file(&file_path)?;
+    let (id, token) = executions.start(execution_id)?;
+    let run = RunRecord::start(RunKind::TaggedSteps, &tags, &file_path, engine.config());
+    let observer = start_run(app, &id, &run);
+    engine.set_cancellation_token(token.clone());
+    engine.set_secrets(vault.secrets());
+    engine.set_observer(observer.clone());
+
+    let mut results = Vec::new();
+    for step_id in engine.select_steps(&filter) {
+        if token.is_cancelled() {
+            break;
+        }
+        results.push(engine.execute_step(&step_id, variables.clone()).await);
+    }
+    executions.finish(&id);
+    finish_run(&observer, &run, results.iter().map(|r| r.status));
+    history::save(&run.finish_steps(results.clone()));
+    Ok(results)
+}
+
+/// 发出执行开始事件，返回的观察者交给引擎或套件执行器
+fn start_run(app: AppHandle, execution_id: &str, run: &RunRecord) -> Arc<TauriObserver> {
+    let observer = Arc::new(TauriObserver::new(app, execution_id.to_string()));
+    observer.on_event(&ExecutionEvent::RunStarted {
+        kind: run.summary.kind,
+        target: run.summary.target.clone(),
+    });
+    observer
+}
+
+/// 发出执行结束事件，状态取最差的一个：已取消 > 出错 > 失败 > 通过
+fn finish_run(observer: &TauriObserver, run: &RunRecord, statuses: impl IntoIterator<Item = ExecutionStatus>) {
+    let status = statuses
+        .into_iter()
+        .max_by_key(|status| match status {
+            ExecutionStatus::Passed => 0,
+            ExecutionStatus::Failed => 1,
+            ExecutionStatus::Error => 2,
+            ExecutionStatus::Cancelled => 3,
+        })
+        .unwrap_or(ExecutionStatus::Passed);
+    let duration_ms = (chrono::Utc::now() - run.summary.started_at).num_milliseconds().max(0) as u64;
+    observer.on_event(&ExecutionEvent::RunFinished { kind: run.summary.kind, status, duration_ms });
+}
+
+/// 取消正在进行的执行，执行已结束或 ID 不存在时返回 false
+#[tauri::command]
+pub fn cancel_execution(execution_id: String, executions: State<'_, ExecutionRegistry>) -> bool {
+    executions.cancel(&execution_id)
 }
@@ -1,36 +1,122 @@
 //! 配置文件数据结构定义
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::random;
 
 /// 完整的测试配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TestConfig {
+    #[serde(default)]
+    pub imports: Vec<ImportEntry>,
+    #[serde(default)]
     pub global: GlobalConfig,
     #[serde(default)]
-    pub variables: HashMap<String, serde_yaml::Value>,
+    pub variables: IndexMap<String, serde_yaml::Value>,
     #[serde(default)]
-    pub steps: HashMap<String, Step>,
+    pub steps: IndexMap<String, Step>,
     #[serde(default)]
-    pub test_cases: HashMap<String, TestCase>,
+    pub test_cases: IndexMap<String, TestCase>,
+    /// 每个 step 的来源位置（由加载器填充，不参与序列化）
+    #[serde(skip)]
+    pub step_sources: IndexMap<String, StepSource>,
+    /// 递归导入的全部文件，按加载顺序（由加载器填充，不参与序列化）
+    #[serde(skip)]
+    pub imported_files: Vec<PathBuf>,
 }
 
-/// 全局配置
+/// step 定义所在的文件与行号
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StepSource {
+    /// 来源文件，从字符串加载时为空
+    pub file: Option<String>,
+    /// step 键所在行（从 1 开始）
+    pub line: Option<usize>,
+}
+
+/// 导入条目：路径字符串，或 `{ path, as }` 形式指定命名空间
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ImportEntry {
+    Path(String),
+    Detailed {
+        path: String,
+        #[serde(default, rename = "as")]
+        alias: Option<String>,
+    },
+}
+
+impl ImportEntry {
+    /// 导入文件路径（相对于导入方文件）
+    pub fn path(&self) -> &str {
+        match self {
+            ImportEntry::Path(path) => path,
+            ImportEntry::Detailed { path, .. } => path,
+        }
+    }
+
+    /// 命名空间：优先使用 `as`，否则取文件名（不含扩展名）；空字符串表示不加前缀
+    pub fn namespace(&self) -> String {
+        if let ImportEntry::Detailed { alias: Some(alias), .. } = self {
+            return alias.clone();
+        }
+        std::path::Path::new(self.path())
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+/// 全局配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GlobalConfig {
     #[serde(default)]
     pub debug: bool,
+    /// 并行执行测试用例的最大并发数，0 表示按 CPU 核数
+    #[serde(default)]
+    pub workers: usize,
+    #[serde(default)]
+    pub timeout: TimeoutConfig,
     #[serde(default)]
     pub profile: ProfileConfig,
 }
 
+/// 超时配置（毫秒），未设置的项沿用上一级配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TimeoutConfig {
+    /// 建立连接的超时
+    #[serde(default)]
+    pub connect_ms: Option<u64>,
+    /// 整个请求（含读取响应体）的超时，默认 30 秒
+    #[serde(default)]
+    pub total_ms: Option<u64>,
+    /// 两次读取之间的超时
+    #[serde(default)]
+    pub read_ms: Option<u64>,
+}
+
+impl TimeoutConfig {
+    /// 用 `other` 中已设置的值覆盖当前配置
+    pub fn merge(&self, other: &TimeoutConfig) -> TimeoutConfig {
+        TimeoutConfig {
+            connect_ms: other.connect_ms.or(self.connect_ms),
+            total_ms: other.total_ms.or(self.total_ms),
+            read_ms: other.read_ms.or(self.read_ms),
+        }
+    }
+}
+
 /// Profile 配置
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ProfileConfig {
     #[serde(default)]
     pub active: String,
     #[serde(flatten)]
-    pub environments: HashMap<String, EnvironmentConfig>,
+    pub environments: IndexMap<String, EnvironmentConfig>,
 }
 
 /// 环境配置
@@ -43,9 +129,98 @@ pub struct EnvironmentConfig {
     #[serde(default)]
     pub brand: String,
     #[serde(default)]
-    pub path_mapping: HashMap<String, String>,
+    pub path_mapping: IndexMap<String, String>,
     #[serde(default)]
     pub auth: Option<AuthConfig>,
+    #[serde(default)]
+    pub timeout: TimeoutConfig,
+    #[serde(default)]
+    pub retry: RetryConfig,
+}
+
+/// 重试策略，未设置的项沿用上一级配置
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// 最多尝试次数（含首次），默认 1 即不重试
+    #[serde(default)]
+    pub max_attempts: Option<u32>,
+    /// 首次重试前的等待时间，默认 200ms
+    #[serde(default)]
+    pub initial_backoff_ms: Option<u64>,
+    /// 等待时间上限，默认 10 秒
+    #[serde(default)]
+    pub max_backoff_ms: Option<u64>,
+    /// 每次重试等待时间的倍数，默认 2
+    #[serde(default)]
+    pub multiplier: Option<f64>,
+    /// 是否给等待时间加随机抖动，默认开启
+    #[serde(default)]
+    pub jitter: Option<bool>,
+    /// 需要重试的响应状态码，默认 502/503/504
+    #[serde(default)]
+    pub retry_on_status: Option<Vec<u16>>,
+    /// 需要重试的错误类型，默认 timeout/connection/network
+    #[serde(default)]
+    pub retry_on_errors: Option<Vec<ErrorKind>>,
+    /// 是否允许非幂等方法（POST）重试，默认不允许
+    #[serde(default)]
+    pub retry_non_idempotent: Option<bool>,
+}
+
+impl RetryConfig {
+    /// 用 `other` 中已设置的值覆盖当前配置
+    pub fn merge(&self, other: &RetryConfig) -> RetryConfig {
+        RetryConfig {
+            max_attempts: other.max_attempts.or(self.max_attempts),
+            initial_backoff_ms: other.initial_backoff_ms.or(self.initial_backoff_ms),
+            max_backoff_ms: other.max_backoff_ms.or(self.max_backoff_ms),
+            multiplier: other.multiplier.or(self.multiplier),
+            jitter: other.jitter.or(self.jitter),
+            retry_on_status: other.retry_on_status.clone().or_else(|| self.retry_on_status.clone()),
+            retry_on_errors: other.retry_on_errors.clone().or_else(|| self.retry_on_errors.clone()),
+            retry_non_idempotent: other.retry_non_idempotent.or(self.retry_non_idempotent),
+        }
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts.unwrap_or(1).max(1)
+    }
+
+    /// 该请求方法是否允许重试
+    pub fn allows_method(&self, method: &str) -> bool {
+        self.retry_non_idempotent.unwrap_or(false)
+            || matches!(method, "GET" | "HEAD" | "OPTIONS" | "PUT" | "DELETE")
+    }
+
+    /// 响应状态码是否需要重试
+    pub fn retries_status(&self, status: u16) -> bool {
+        match &self.retry_on_status {
+            Some(codes) => codes.contains(&status),
+            None => matches!(status, 502..=504),
+        }
+    }
+
+    /// 错误类型是否需要重试
+    pub fn retries_error(&self, kind: ErrorKind) -> bool {
+        match &self.retry_on_errors {
+            Some(kinds) => kinds.contains(&kind),
+            None => matches!(kind, ErrorKind::Timeout | ErrorKind::Connection | ErrorKind::Network),
+        }
+    }
+
+    /// 第 `attempt` 次尝试失败后的等待时间（指数退避，抖动范围为 50% ~ 100%）
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let initial = self.initial_backoff_ms.unwrap_or(200) as f64;
+        let max = self.max_backoff_ms.unwrap_or(10_000) as f64;
+        let multiplier = self.multiplier.unwrap_or(2.0).max(1.0);
+
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let mut delay = (initial * multiplier.powi(exponent)).min(max);
+        if self.jitter.unwrap_or(true) {
+            delay *= 0.5 + random::next_f64() * 0.5;
+        }
+        Duration::from_millis(delay as u64)
+    }
 }
 
 /// 认证配置
@@ -88,26 +263,37 @@ impl Default for AuthConfig {
 }
 
 /// 测试步骤
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Step {
     #[serde(default)]
     pub name: String,
     #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
     pub method: String,
     #[serde(default)]
     pub path: String,
     #[serde(default)]
-    pub params: HashMap<String, serde_yaml::Value>,
+    pub params: IndexMap<String, serde_yaml::Value>,
     #[serde(default)]
     pub body: Option<serde_yaml::Value>,
     #[serde(default)]
-    pub headers: HashMap<String, String>,
+    pub headers: IndexMap<String, String>,
     #[serde(default)]
-    pub variables: HashMap<String, serde_yaml::Value>,
+    pub variables: IndexMap<String, serde_yaml::Value>,
     #[serde(default)]
     pub validate: Vec<Validation>,
+    /// 表达式断言，如 `total == items | length * price`
+    #[serde(default, rename = "assert")]
+    pub asserts: Vec<String>,
     #[serde(default)]
     pub save_response: Vec<SaveRule>,
+    #[serde(default)]
+    pub timeout: TimeoutConfig,
+    #[serde(default)]
+    pub retry: RetryConfig,
 }
 
 /// 验证规则
@@ -118,6 +304,12 @@ pub struct Validation {
     pub expected: serde_yaml::Value,
 }
 
+/// 支持的验证操作符
+pub const VALIDATION_OPERATORS: &[&str] = &["equals", "exists", "not_empty", "contains"];
+
+/// 支持的 HTTP 方法
+pub const HTTP_METHODS: &[&str] = &["GET", "POST", "PUT", "DELETE"];
+
 /// 保存响应规则
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SaveRule {
@@ -126,16 +318,35 @@ pub struct SaveRule {
 }
 
 /// 测试用例
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TestCase {
     #[serde(default)]
     pub name: String,
     #[serde(default)]
     pub description: String,
     #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
     pub steps: Vec<String>,
     #[serde(default)]
-    pub variables: HashMap<String, serde_yaml::Value>,
+    pub variables: IndexMap<String, serde_yaml::Value>,
+    /// 并行执行时独占运行，不与其他用例重叠
+    #[serde(default)]
+    pub serial: bool,
+    /// 占用的资源名，持有相同资源的用例不会同时执行
+    #[serde(default)]
+    pub locks: Vec<String>,
+}
+
+/// 测试用例信息（用于前端展示）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCaseInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// 用例自身及其步骤的标签
+    pub tags: Vec<String>,
+    pub steps: Vec<String>,
 }
 
 /// Step 信息（用于前端展示）
@@ -145,23 +356,132 @@ pub struct StepInfo {
     pub name: String,
     pub method: String,
     pub path: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    /// 定义该 step 的文件（通过 imports 引入时为被导入文件）
+    pub source: Option<String>,
+    /// step 在来源文件中的行号（从 1 开始）
+    pub line: Option<usize>,
+    /// 引用了该 step 的测试用例
+    pub test_cases: Vec<String>,
 }
 
 /// 执行结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     pub success: bool,
+    pub status: ExecutionStatus,
     pub step_name: String,
     pub request_url: String,
     pub request_method: String,
-    pub request_headers: HashMap<String, String>,
+    pub request_headers: IndexMap<String, String>,
     pub request_body: Option<serde_json::Value>,
     pub response_status: u16,
-    pub response_headers: HashMap<String, String>,
+    pub response_headers: IndexMap<String, String>,
     pub response_body: Option<serde_json::Value>,
     pub validations: Vec<ValidationResult>,
     pub duration_ms: u64,
     pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+    /// 每一次请求尝试（含重试）
+    pub attempts: Vec<AttemptRecord>,
+}
+
+/// 预览的请求（未发送）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedRequest {
+    pub step_name: String,
+    pub method: String,
+    /// 完整 URL，不含查询参数
+    pub url: String,
+    /// 查询参数，认证方式为 query 时包含 token
+    pub query: IndexMap<String, String>,
+    /// 请求头，包含认证头和 Content-Type
+    pub headers: IndexMap<String, String>,
+    pub body: Option<serde_json::Value>,
+    /// 未能替换的占位符，如 `{userId}`
+    pub unresolved: Vec<String>,
+}
+
+/// 单次请求尝试的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttemptRecord {
+    /// 第几次尝试（从 1 开始）
+    pub attempt: u32,
+    /// 响应状态码，请求失败时为 0
+    pub status: u16,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+}
+
+/// 执行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStatus {
+    /// 请求成功且全部验证通过
+    Passed,
+    /// 收到响应，但状态码或验证未通过
+    Failed,
+    /// 请求未完成（超时、连接失败等）
+    Error,
+    /// 执行被取消
+    Cancelled,
+}
+
+/// 执行错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// 连接、读取或总超时
+    Timeout,
+    /// 无法建立连接
+    Connection,
+    /// 其他网络错误
+    Network,
+    /// 获取 token 失败
+    Auth,
+    /// 请求本身有误（如不支持的 HTTP 方法）
+    InvalidRequest,
+    /// 步骤不存在
+    MissingStep,
+}
+
+impl ErrorKind {
+    /// 错误代码，与序列化的名称相同
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Connection => "connection",
+            ErrorKind::Network => "network",
+            ErrorKind::Auth => "auth",
+            ErrorKind::InvalidRequest => "invalid_request",
+            ErrorKind::MissingStep => "missing_step",
+        }
+    }
+}
+
+/// 测试用例执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestCaseResult {
+    pub case_id: String,
+    pub name: String,
+    pub success: bool,
+    pub status: ExecutionStatus,
+    pub steps: Vec<ExecutionResult>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// 测试套件执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuiteResult {
+    pub success: bool,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub cases: Vec<TestCaseResult>,
+    pub duration_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,3 +492,90 @@ pub struct ValidationResult {
     pub actual: String,
     pub passed: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_jitter() -> RetryConfig {
+        RetryConfig { jitter: Some(false), ..Default::default() }
+    }
+
+    fn backoff_ms(retry: &RetryConfig, attempts: std::ops::RangeInclusive<u32>) -> Vec<u128> {
+        attempts.map(|attempt| retry.backoff(attempt).as_millis()).collect()
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_max() {
+        assert_eq!(backoff_ms(&no_jitter(), 1..=4), [200, 400, 800, 1600]);
+        assert_eq!(no_jitter().backoff(10).as_millis(), 10_000);
+        assert_eq!(no_jitter().backoff(u32::MAX).as_millis(), 10_000);
+
+        let retry = RetryConfig {
+            initial_backoff_ms: Some(100),
+            max_backoff_ms: Some(1000),
+            multiplier: Some(3.0),
+            ..no_jitter()
+        };
+        assert_eq!(backoff_ms(&retry, 1..=4), [100, 300, 900, 1000]);
+    }
+
+    #[test]
+    fn backoff_multiplier_is_at_least_one() {
+        let retry = RetryConfig { multiplier: Some(0.5), ..no_jitter() };
+        assert_eq!(backoff_ms(&retry, 1..=3), [200, 200, 200]);
+    }
+
+    #[test]
+    fn backoff_jitter_stays_within_half_to_full_delay() {
+        let retry = RetryConfig { initial_backoff_ms: Some(1000), ..Default::default() };
+        for _ in 0..100 {
+            let delay = retry.backoff(1).as_millis();
+            assert!((500..=1000).contains(&delay), "{}", delay);
+        }
+    }
+
+    #[test]
+    fn retried_statuses_and_errors() {
+        let retry = RetryConfig::default();
+        assert!([502, 503, 504].iter().all(|s| retry.retries_status(*s)));
+        assert!(![200, 429, 500, 501, 505].iter().any(|s| retry.retries_status(*s)));
+        assert!(retry.retries_error(ErrorKind::Timeout));
+        assert!(!retry.retries_error(ErrorKind::InvalidRequest));
+
+        let retry = RetryConfig {
+            retry_on_status: Some(vec![429]),
+            retry_on_errors: Some(vec![ErrorKind::Connection]),
+            ..Default::default()
+        };
+        assert!(retry.retries_status(429));
+        assert!(!retry.retries_status(503));
+        assert!(retry.retries_error(ErrorKind::Connection));
+        assert!(!retry.retries_error(ErrorKind::Timeout));
+        assert!(!RetryConfig { retry_on_status: Some(Vec::new()), ..Default::default() }.retries_status(503));
+    }
+
+    #[test]
+    fn only_idempotent_methods_are_retried_by_default() {
+        let retry = RetryConfig::default();
+        for method in ["GET", "HEAD", "OPTIONS", "PUT", "DELETE"] {
+            assert!(retry.allows_method(method), "{}", method);
+        }
+        assert!(!retry.allows_method("POST"));
+        assert!(!retry.allows_method("PATCH"));
+        assert!(RetryConfig { retry_non_idempotent: Some(true), ..Default::default() }.allows_method("POST"));
+    }
+
+    #[test]
+    fn merge_keeps_unset_values() {
+        let environment = RetryConfig { max_attempts: Some(3), retry_on_status: Some(vec![500]), ..Default::default() };
+        let step = RetryConfig { max_attempts: Some(0), jitter: Some(false), ..Default::default() };
+        let merged = environment.merge(&step);
+
+        assert_eq!(merged.max_attempts, Some(0));
+        assert_eq!(merged.max_attempts(), 1);
+        assert_eq!(merged.retry_on_status, Some(vec![500]));
+        assert_eq!(merged.jitter, Some(false));
+        assert_eq!(RetryConfig::default().max_attempts(), 1);
+    }
+}
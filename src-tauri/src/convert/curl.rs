@@ -0,0 +1,511 @@
+//! curl 命令行导出与导入
+
+use indexmap::IndexMap;
+use reqwest::Url;
+use serde_yaml::Value as YamlValue;
+
+use crate::config::{EnvironmentConfig, RenderedRequest, Step, HTTP_METHODS};
+use crate::error::{Error, Result};
+use crate::mask::Masker;
+
+/// 把预览出的请求转换为 curl 命令行
+///
+/// 传入 `masker` 时按其规则隐藏凭据请求头和查询参数，为 `None` 时不脱敏。
+pub fn to_curl(request: &RenderedRequest, masker: Option<&Masker>) -> String {
+    let query = request
+        .query
+        .iter()
+        .map(|(key, value)| (key, masker.map_or_else(|| value.clone(), |m| m.param(key, value))));
+    let url = match Url::parse(&request.url) {
+        Ok(mut url) if !request.query.is_empty() => {
+            url.query_pairs_mut().extend_pairs(query);
+            url.to_string()
+        }
+        Ok(url) => url.to_string(),
+        // 含未解析变量等无法解析的 URL 原样保留，查询参数自行编码后拼接
+        Err(_) => {
+            let mut url = request.url.clone();
+            for (key, value) in query {
+                url.push(if url.contains('?') { '&' } else { '?' });
+                url.push_str(&encode_query_component(key));
+                url.push('=');
+                url.push_str(&encode_query_component(&value));
+            }
+            url
+        }
+    };
+
+    let mut parts = vec![if request.method == "GET" {
+        format!("curl {}", quote(&url))
+    } else {
+        format!("curl -X {} {}", request.method, quote(&url))
+    }];
+
+    for (key, value) in &request.headers {
+        let value = masker.map_or_else(|| value.clone(), |m| m.header(key, value));
+        parts.push(format!("-H {}", quote(&format!("{}: {}", key, value))));
+    }
+
+    if let Some(body) = &request.body {
+        parts.push(format!("--data-raw {}", quote(&body.to_string())));
+    }
+
+    parts.join(" \\\n  ")
+}
+
+/// 按 `application/x-www-form-urlencoded` 规则编码，与 [`Url::query_pairs_mut`] 的结果一致
+fn encode_query_component(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'*' | b'-' | b'.' | b'_' => encoded.push(byte as char),
+            b' ' => encoded.push('+'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// 按 shell 规则用单引号包裹
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// 解析出的 curl 请求
+#[derive(Debug, Clone, Default)]
+pub struct CurlRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: IndexMap<String, String>,
+    pub data: Option<String>,
+    /// `--max-time`，毫秒
+    pub max_time_ms: Option<u64>,
+    /// `--connect-timeout`，毫秒
+    pub connect_timeout_ms: Option<u64>,
+}
+
+/// 解析 curl 命令行
+pub fn parse_curl(command: &str) -> Result<CurlRequest> {
+    let args = split_args(command)?;
+    let mut args = args.into_iter().peekable();
+
+    if args.peek().map(String::as_str) != Some("curl") {
+        return Err(Error::Validation("不是 curl 命令".to_string()));
+    }
+    args.next();
+
+    let mut request = CurlRequest::default();
+    let mut method = None;
+    let mut data: Vec<String> = Vec::new();
+    let mut get = false;
+
+    // 合写的短选项中尚未处理的部分，如 -sSo 处理完 -s 后剩下 -So
+    let mut bundled: Option<String> = None;
+
+    while let Some(arg) = bundled.take().or_else(|| args.next()) {
+        if !arg.starts_with('-') || arg == "-" {
+            request.url = arg;
+            continue;
+        }
+
+        // 短选项的值可以紧跟在后面，如 -XPOST；不带值的短选项可以合写，如 -sSL
+        let (name, inline) = if arg.starts_with("--") {
+            match arg.split_once('=') {
+                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                None => (arg.clone(), None),
+            }
+        } else {
+            match arg.char_indices().nth(2) {
+                Some((index, _)) => {
+                    let (name, rest) = arg.split_at(index);
+                    if is_short_flag(name) {
+                        bundled = Some(format!("-{}", rest));
+                        (name.to_string(), None)
+                    } else {
+                        (name.to_string(), Some(rest.to_string()))
+                    }
+                }
+                None => (arg.clone(), None),
+            }
+        };
+
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| Error::Validation(format!("curl 选项 {} 缺少参数", name)))
+        };
+
+        match name.as_str() {
+            "-X" | "--request" => method = Some(value()?.to_uppercase()),
+            "-H" | "--header" => {
+                let header = value()?;
+                if let Some((key, val)) = header.split_once(':') {
+                    request.headers.insert(key.trim().to_string(), val.trim().to_string());
+                }
+            }
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii" | "--data-urlencode" => {
+                data.push(value()?)
+            }
+            "--json" => {
+                data.push(value()?);
+                request.headers.insert("Content-Type".to_string(), "application/json".to_string());
+            }
+            "-A" | "--user-agent" => {
+                request.headers.insert("User-Agent".to_string(), value()?);
+            }
+            "-e" | "--referer" => {
+                request.headers.insert("Referer".to_string(), value()?);
+            }
+            "-b" | "--cookie" => {
+                request.headers.insert("Cookie".to_string(), value()?);
+            }
+            "--url" => request.url = value()?,
+            "-m" | "--max-time" => request.max_time_ms = Some(seconds_to_ms(&value()?)?),
+            "--connect-timeout" => request.connect_timeout_ms = Some(seconds_to_ms(&value()?)?),
+            "-G" | "--get" => get = true,
+            "-u" | "--user" => {
+                return Err(Error::Validation(
+                    "不支持 -u/--user，请改用认证配置或 Authorization 请求头".to_string(),
+                ))
+            }
+            "-o" | "--output" | "-w" | "--write-out" | "--retry" => {
+                value()?;
+            }
+            "-s" | "--silent" | "-S" | "--show-error" | "-k" | "--insecure" | "-L" | "--location"
+            | "-i" | "--include" | "-v" | "--verbose" | "-f" | "--fail" | "--compressed" => {}
+            _ => return Err(Error::Validation(format!("不支持的 curl 选项: {}", name))),
+        }
+    }
+
+    if request.url.is_empty() {
+        return Err(Error::Validation("curl 命令中没有 URL".to_string()));
+    }
+
+    let data = (!data.is_empty()).then(|| data.join("&"));
+    if get {
+        // -G 把数据作为查询参数发送
+        if let Some(data) = data {
+            let separator = if request.url.contains('?') { '&' } else { '?' };
+            request.url = format!("{}{}{}", request.url, separator, data);
+        }
+        request.method = method.unwrap_or_else(|| "GET".to_string());
+    } else {
+        request.method = method.unwrap_or_else(|| if data.is_some() { "POST" } else { "GET" }.to_string());
+        request.data = data;
+    }
+
+    Ok(request)
+}
+
+/// 不带值的短选项
+fn is_short_flag(name: &str) -> bool {
+    matches!(name, "-s" | "-S" | "-k" | "-L" | "-i" | "-v" | "-f" | "-G")
+}
+
+fn seconds_to_ms(value: &str) -> Result<u64> {
+    match value.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => Ok((secs * 1000.0) as u64),
+        _ => Err(Error::Validation(format!("无效的超时时间: {}", value))),
+    }
+}
+
+/// 按 shell 规则拆分参数，支持单引号、双引号、`$'...'` 和反斜杠续行
+fn split_args(command: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(next) => current.get_or_insert_with(String::new).push(next),
+                None => {}
+            },
+            '\'' => {
+                let s = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => s.push(ch),
+                        None => return Err(Error::Validation("curl 命令中的引号不匹配".to_string())),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                let s = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            Some('t') => s.push('\t'),
+                            Some('r') => s.push('\r'),
+                            Some(ch) => s.push(ch),
+                            None => break,
+                        },
+                        Some(ch) => s.push(ch),
+                        None => return Err(Error::Validation("curl 命令中的引号不匹配".to_string())),
+                    }
+                }
+            }
+            '"' => {
+                let s = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(ch @ ('"' | '\\' | '$' | '`')) => s.push(ch),
+                            Some('\n') => {}
+                            Some(ch) => {
+                                s.push('\\');
+                                s.push(ch);
+                            }
+                            None => break,
+                        },
+                        Some(ch) => s.push(ch),
+                        None => return Err(Error::Validation("curl 命令中的引号不匹配".to_string())),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    args.extend(current);
+    Ok(args)
+}
+
+/// 把 curl 请求转换为步骤
+///
+/// 主机映射到当前环境的 `base_url`，路径按 `path_mapping` 反向还原；
+/// 由认证配置自动添加的 token 以及引擎自动设置的请求头不会写入步骤。
+pub fn to_step(request: &CurlRequest, env: Option<&EnvironmentConfig>) -> Result<Step> {
+    if !HTTP_METHODS.contains(&request.method.as_str()) {
+        return Err(Error::Validation(format!("不支持的 HTTP 方法: {}", request.method)));
+    }
+
+    let (path, mut params) = super::step_path(&request.url, env);
+
+    let auth = super::auth_target(env);
+    let auth_key = auth.as_ref().map(|(key, _)| key.as_str());
+    let auth_in_query = auth.as_ref().is_some_and(|(_, in_query)| *in_query);
+
+    if let (Some(key), true) = (auth_key, auth_in_query) {
+        params.shift_remove(key);
+    }
+
+    let headers = request
+        .headers
+        .iter()
+        .filter(|(key, value)| {
+            let key = key.to_ascii_lowercase();
+            let is_auth = !auth_in_query && auth_key.is_some_and(|k| k.eq_ignore_ascii_case(&key));
+            let is_json = key == "content-type" && value.starts_with("application/json");
+            !is_auth && !is_json && key != "content-length" && key != "host"
+        })
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    // JSON 请求体转为 YAML 结构，其他内容保留原文
+    let body = request.data.as_ref().map(|data| {
+        serde_json::from_str::<serde_json::Value>(data)
+            .ok()
+            .and_then(|json| serde_yaml::to_value(json).ok())
+            .unwrap_or_else(|| YamlValue::String(data.clone()))
+    });
+
+    let mut step = Step {
+        name: format!("{} {}", request.method, path),
+        method: request.method.clone(),
+        path,
+        params,
+        body,
+        headers,
+        ..Default::default()
+    };
+    step.timeout.total_ms = request.max_time_ms;
+    step.timeout.connect_ms = request.connect_timeout_ms;
+
+    Ok(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(command: &str) -> CurlRequest {
+        parse_curl(command).unwrap_or_else(|e| panic!("{}: {}", command, e))
+    }
+
+    fn parse_err(command: &str) -> String {
+        parse_curl(command).unwrap_err().to_string()
+    }
+
+    fn request(url: &str) -> RenderedRequest {
+        RenderedRequest {
+            step_name: "step".to_string(),
+            method: "GET".to_string(),
+            url: url.to_string(),
+            query: IndexMap::new(),
+            headers: IndexMap::new(),
+            body: None,
+            unresolved: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn splits_shell_arguments() {
+        let args = split_args("curl 'a b' \"c \\\"d\\\" \\$e\" $'f\\ng' h\\ i \\\n  'j'\"k\"").unwrap();
+        assert_eq!(args, ["curl", "a b", "c \"d\" $e", "f\ng", "h i", "jk"]);
+        assert_eq!(split_args("curl \\\r\n  -s").unwrap(), ["curl", "-s"]);
+        // 双引号中其他反斜杠原样保留
+        assert_eq!(split_args(r#""a\nb""#).unwrap(), [r"a\nb"]);
+        assert_eq!(split_args("''").unwrap(), [""]);
+        for command in ["curl 'a", "curl \"a", "curl $'a"] {
+            assert!(split_args(command).unwrap_err().to_string().contains("引号不匹配"), "{}", command);
+        }
+    }
+
+    #[test]
+    fn parses_bundled_short_flags() {
+        let request = parse("curl -sSL -XPOST -HAccept:text/plain -sXPUT https://api.test/a");
+        assert_eq!(request.method, "PUT");
+        assert_eq!(request.headers["Accept"], "text/plain");
+        assert_eq!(request.url, "https://api.test/a");
+
+        // 不带值的短选项之后的选项取后面的参数作为值
+        let request = parse("curl -sd 'a=1' -kH 'X-Id: 7' https://api.test/a");
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.data.as_deref(), Some("a=1"));
+        assert_eq!(request.headers["X-Id"], "7");
+    }
+
+    #[test]
+    fn parses_copied_browser_command() {
+        let command = r#"curl 'https://api.test/v1/users?page=2' \
+  -H 'authorization: Bearer abc' \
+  -H "content-type: application/json" \
+  -b 'sid=1' -A 'Mozilla/5.0' \
+  --data-raw '{"name":"Alice","age":30}' \
+  --compressed --max-time 2.5 --connect-timeout=1"#;
+        let request = parse(command);
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.url, "https://api.test/v1/users?page=2");
+        let headers: Vec<_> = request.headers.keys().map(String::as_str).collect();
+        assert_eq!(headers, ["authorization", "content-type", "Cookie", "User-Agent"]);
+        assert_eq!(request.data.as_deref(), Some(r#"{"name":"Alice","age":30}"#));
+        assert_eq!(request.max_time_ms, Some(2500));
+        assert_eq!(request.connect_timeout_ms, Some(1000));
+    }
+
+    #[test]
+    fn data_with_get_becomes_query() {
+        let request = parse("curl -G https://api.test/search?x=1 -d q=rust --data-urlencode page=2");
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.url, "https://api.test/search?x=1&q=rust&page=2");
+        assert_eq!(request.data, None);
+
+        let request = parse("curl --json '{}' --url https://api.test/a");
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.headers["Content-Type"], "application/json");
+    }
+
+    #[test]
+    fn rejects_unsupported_commands() {
+        assert!(parse_err("wget https://api.test").contains("不是 curl 命令"));
+        assert!(parse_err("curl -s").contains("没有 URL"));
+        assert!(parse_err("curl https://api.test -H").contains("缺少参数"));
+        assert!(parse_err("curl -u a:b https://api.test").contains("不支持 -u/--user"));
+        assert!(parse_err("curl -F a=1 https://api.test").contains("不支持的 curl 选项: -F"));
+        for timeout in ["-1", "inf", "NaN", "abc"] {
+            let message = parse_err(&format!("curl -m {} https://api.test", timeout));
+            assert!(message.contains("无效的超时时间"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn imports_step_relative_to_environment() {
+        let env: EnvironmentConfig = serde_yaml::from_str(
+            "base_url: https://api.test/gateway\n\
+             path_mapping:\n  /users: /user-service/v1/users\n\
+             auth:\n  token_url: /oauth/token\n",
+        )
+        .unwrap();
+        let request = parse(
+            "curl -X POST 'https://api.test/gateway/user-service/v1/users/7?verbose=1' \
+             -H 'Authorization: Bearer abc' -H 'Content-Type: application/json' -H 'X-Trace: 1' \
+             -d '{\"name\":\"Alice\"}' -m 3",
+        );
+        let step = to_step(&request, Some(&env)).unwrap();
+
+        assert_eq!(step.name, "POST /users/7");
+        assert_eq!(step.path, "/users/7");
+        assert_eq!(step.params["verbose"], "1");
+        assert_eq!(step.headers, IndexMap::from([("X-Trace".to_string(), "1".to_string())]));
+        assert_eq!(step.body, Some(serde_yaml::to_value(json!({ "name": "Alice" })).unwrap()));
+        assert_eq!(step.timeout.total_ms, Some(3000));
+
+        let request = parse("curl -X PATCH https://api.test/a");
+        assert!(to_step(&request, None).unwrap_err().to_string().contains("不支持的 HTTP 方法: PATCH"));
+        let request = parse("curl https://api.test/a -d 'plain text'");
+        assert_eq!(to_step(&request, None).unwrap().body, Some(YamlValue::from("plain text")));
+    }
+
+    #[test]
+    fn exports_request() {
+        let mut request = request("https://api.test/users");
+        request.method = "POST".to_string();
+        request.query = IndexMap::from([("q".to_string(), "a b".to_string()), ("token".to_string(), "t".to_string())]);
+        request.headers = IndexMap::from([
+            ("Authorization".to_string(), "Bearer abc".to_string()),
+            ("X-Note".to_string(), "it's".to_string()),
+        ]);
+        request.body = Some(json!({ "name": "Alice" }));
+
+        assert_eq!(
+            to_curl(&request, None),
+            "curl -X POST 'https://api.test/users?q=a+b&token=t' \\\n  \
+             -H 'Authorization: Bearer abc' \\\n  \
+             -H 'X-Note: it'\\''s' \\\n  \
+             --data-raw '{\"name\":\"Alice\"}'"
+        );
+        let masked = to_curl(&request, Some(&Masker::default()));
+        assert!(masked.contains("?q=a+b&token=******'"), "{}", masked);
+        assert!(masked.contains("Authorization: Bearer ******"), "{}", masked);
+    }
+
+    #[test]
+    fn exported_command_parses_back() {
+        let mut exported = request("https://api.test/search");
+        exported.query = IndexMap::from([("q".to_string(), "a&b=c".to_string())]);
+        exported.headers = IndexMap::from([("X-Id".to_string(), "7".to_string())]);
+        let parsed = parse(&to_curl(&exported, None));
+
+        assert_eq!(parsed.method, "GET");
+        assert_eq!(parsed.url, "https://api.test/search?q=a%26b%3Dc");
+        assert_eq!(parsed.headers, exported.headers);
+    }
+
+    #[test]
+    fn exports_query_of_unparsable_url() {
+        let mut request = request("{host}/users");
+        request.query = IndexMap::from([("q".to_string(), "a b&c".to_string()), ("page".to_string(), "{page}".to_string())]);
+        assert_eq!(to_curl(&request, None), "curl '{host}/users?q=a+b%26c&page=%7Bpage%7D'");
+
+        request.url = "{host}/users?x=1".to_string();
+        assert!(to_curl(&request, None).starts_with("curl '{host}/users?x=1&q="));
+    }
+}
@@ -0,0 +1,511 @@
+//! 从 HAR 抓包生成步骤
+//!
+//! 选中的请求按顺序转换为步骤：主机映射到 `base_url`，认证头交给环境的认证配置，
+//! 浏览器自动添加的请求头被去掉。前面响应中出现过、后面请求又用到的值
+//! （如创建后返回的 id）改写为占位符，并在产生它的步骤上添加 `save_response`。
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::{EnvironmentConfig, SaveRule, Step, HTTP_METHODS};
+use crate::error::{Error, Result};
+
+use super::items;
+
+/// 浏览器或 HTTP 客户端自动添加的请求头，不写入步骤
+const BROWSER_HEADERS: &[&str] = &[
+    "host", "connection", "content-length", "accept-encoding", "accept-language", "user-agent",
+    "origin", "referer", "cache-control", "pragma", "priority", "dnt", "upgrade-insecure-requests",
+    "cookie",
+];
+
+/// 参与值传递识别的最短字符串长度，过短的值容易误匹配
+const MIN_FLOW_LENGTH: usize = 6;
+
+/// HAR 中的一条请求（供前端选择）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HarEntry {
+    pub index: usize,
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    /// 响应类型，如 `application/json`
+    pub mime_type: String,
+    /// 默认是否选中：支持的方法发出的 XHR / fetch 请求或 JSON 响应
+    pub selected: bool,
+}
+
+/// HAR 导入结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HarImport {
+    /// 可粘贴到 `steps:` 下的 YAML 片段
+    pub content: String,
+    /// 按抓包顺序排列的步骤 id，依次执行才能传递响应中的值
+    pub steps: Vec<String>,
+    /// 未能导入的请求或内容及原因
+    pub skipped: Vec<String>,
+}
+
+pub fn read_har(path: &Path) -> Result<JsonValue> {
+    let content = fs::read_to_string(path).map_err(|e| Error::io("无法读取 HAR 文件", path, e))?;
+    let har: JsonValue = serde_json::from_str(&content)
+        .map_err(|e| Error::config_parse(format!("HAR 文件解析错误: {}", e), Some(path)))?;
+    if !har.pointer("/log/entries").is_some_and(JsonValue::is_array) {
+        return Err(Error::Validation("不是 HAR 文件：缺少 log.entries".to_string()));
+    }
+    Ok(har)
+}
+
+/// 列出 HAR 中的请求
+pub fn list_entries(har: &JsonValue) -> Vec<HarEntry> {
+    entries(har)
+        .enumerate()
+        .map(|(index, entry)| {
+            let mime_type = str_at(entry, "/response/content/mimeType").to_string();
+            let method = str_at(entry, "/request/method").to_uppercase();
+            let resource_type = str_at(entry, "/_resourceType");
+            let selected = HTTP_METHODS.contains(&method.as_str())
+                && if resource_type.is_empty() {
+                    mime_type.contains("json")
+                } else {
+                    matches!(resource_type, "xhr" | "fetch")
+                };
+
+            HarEntry {
+                index,
+                method,
+                url: str_at(entry, "/request/url").to_string(),
+                status: entry.pointer("/response/status").and_then(JsonValue::as_u64).unwrap_or(0) as u16,
+                mime_type,
+                selected,
+            }
+        })
+        .collect()
+}
+
+/// 把选中的请求转换为步骤，`selection` 为空时使用默认选中的请求
+///
+/// `existing` 为目标配置中已有的步骤，生成的 id 不与之重复。
+pub fn import_har(
+    har: &JsonValue,
+    selection: Option<&[usize]>,
+    env: Option<&EnvironmentConfig>,
+    existing: &IndexMap<String, Step>,
+) -> Result<HarImport> {
+    let selected: Vec<usize> = match selection {
+        Some(indexes) => indexes.to_vec(),
+        None => list_entries(har).iter().filter(|e| e.selected).map(|e| e.index).collect(),
+    };
+    let all: Vec<&JsonValue> = entries(har).collect();
+
+    let mut importer = Importer {
+        env,
+        auth: super::auth_target(env),
+        steps: IndexMap::new(),
+        sources: HashMap::new(),
+        flows: HashMap::new(),
+        foreign_hosts: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    for index in selected {
+        let Some(entry) = all.get(index) else {
+            importer.skipped.push(format!("第 {} 条请求不存在", index));
+            continue;
+        };
+        importer.entry(index, entry, existing);
+    }
+
+    let content = super::steps_to_yaml(&importer.steps)?;
+    Ok(HarImport {
+        content,
+        steps: importer.steps.keys().cloned().collect(),
+        skipped: importer.skipped,
+    })
+}
+
+struct Importer<'a> {
+    env: Option<&'a EnvironmentConfig>,
+    auth: Option<(String, bool)>,
+    steps: IndexMap<String, Step>,
+    /// 响应中出现过的值 -> (步骤 id, 字段路径)，后出现的覆盖先出现的
+    sources: HashMap<String, (String, String)>,
+    /// (步骤 id, 字段路径) -> 保存到的变量名
+    flows: HashMap<(String, String), String>,
+    /// 已报告过的与 base_url 不同的主机
+    foreign_hosts: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl Importer<'_> {
+    fn entry(&mut self, index: usize, entry: &JsonValue, existing: &IndexMap<String, Step>) {
+        let method = str_at(entry, "/request/method").to_uppercase();
+        let url = str_at(entry, "/request/url");
+        let label = format!("第 {} 条请求 {} {}", index, method, url);
+
+        if !HTTP_METHODS.contains(&method.as_str()) {
+            self.skipped.push(format!("{}: 不支持的 HTTP 方法", label));
+            return;
+        }
+
+        let (path, params) = super::step_path(url, self.env);
+        if let Some(base) = self.env.and_then(|e| reqwest::Url::parse(&e.base_url).ok()) {
+            let host = reqwest::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string));
+            if let Some(host) = host.filter(|h| Some(h.as_str()) != base.host_str()) {
+                if !self.foreign_hosts.contains(&host) {
+                    self.skipped.push(format!("主机 {} 与 base_url 不同，已映射到 base_url", host));
+                    self.foreign_hosts.push(host);
+                }
+            }
+        }
+
+        let mut step = Step {
+            method: method.clone(),
+            path,
+            params,
+            ..Default::default()
+        };
+
+        let mut dropped_auth = false;
+        for header in items(entry.pointer("/request/headers")) {
+            let name = str_at(header, "/name");
+            let value = str_at(header, "/value");
+            let lower = name.to_ascii_lowercase();
+
+            if name.starts_with(':') || lower.starts_with("sec-") || BROWSER_HEADERS.contains(&lower.as_str()) {
+                continue;
+            }
+            if lower == "content-type" && value.contains("json") {
+                continue;
+            }
+            if self.is_auth(name, false) || (self.auth.is_none() && lower == "authorization") {
+                dropped_auth = true;
+                continue;
+            }
+            step.headers.insert(name.to_string(), value.to_string());
+        }
+        if let Some((key, true)) = &self.auth {
+            dropped_auth |= step.params.shift_remove(key).is_some();
+        }
+        if dropped_auth && self.auth.is_none() {
+            self.skipped.push(format!("{}: 已去掉认证信息，请在环境中配置 auth", label));
+        }
+
+        if let Some(text) = entry.pointer("/request/postData/text").and_then(JsonValue::as_str) {
+            let mime_type = str_at(entry, "/request/postData/mimeType");
+            if serde_json::from_str::<JsonValue>(text).is_ok() {
+                // 用 serde_yaml 解析以保留字段顺序
+                step.body = serde_yaml::from_str(text).ok();
+            } else if mime_type.contains("x-www-form-urlencoded") || mime_type.contains("multipart") {
+                self.skipped.push(format!("{}: 不支持 {} 请求体，已忽略", label, mime_type));
+            } else if !text.is_empty() {
+                step.body = Some(YamlValue::String(text.to_string()));
+            }
+        }
+
+        let status = entry.pointer("/response/status").and_then(JsonValue::as_u64).unwrap_or(0);
+        if status > 0 {
+            step.asserts.push(format!("status == {}", status));
+        }
+
+        // 先改写占位符，id 和名称不包含抓包时的具体值
+        self.link(&mut step);
+        step.name = format!("{} {}", method, step.path);
+
+        let mut all_ids = existing.clone();
+        all_ids.extend(self.steps.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let id = super::step_id(&method, &step.path, &all_ids);
+        self.steps.insert(id.clone(), step);
+        self.collect_sources(&id, entry);
+    }
+
+    fn is_auth(&self, key: &str, in_query: bool) -> bool {
+        self.auth
+            .as_ref()
+            .is_some_and(|(auth_key, query)| *query == in_query && auth_key.eq_ignore_ascii_case(key))
+    }
+
+    /// 记录响应体中可能被后续请求用到的值
+    fn collect_sources(&mut self, id: &str, entry: &JsonValue) {
+        let text = str_at(entry, "/response/content/text");
+        if str_at(entry, "/response/content/encoding") == "base64" || text.is_empty() {
+            return;
+        }
+        let Ok(response) = serde_json::from_str::<JsonValue>(text) else { return };
+
+        let mut values = Vec::new();
+        collect_values(&response, String::new(), &mut values);
+        for (field, value) in values {
+            self.sources.insert(value, (id.to_string(), field));
+        }
+    }
+
+    /// 把请求中来自前面响应的值改写为占位符
+    fn link(&mut self, step: &mut Step) {
+        if self.sources.is_empty() {
+            return;
+        }
+
+        let segments: Vec<String> = step.path.split('/').map(|s| self.replace_exact(s)).collect();
+        step.path = segments.join("/");
+
+        let mut params = std::mem::take(&mut step.params);
+        for value in params.values_mut() {
+            if let YamlValue::String(s) = value {
+                *s = self.replace_exact(s);
+            }
+        }
+        step.params = params;
+
+        let mut headers = std::mem::take(&mut step.headers);
+        for value in headers.values_mut() {
+            *value = self.replace_within(value);
+        }
+        step.headers = headers;
+
+        if let Some(body) = step.body.take() {
+            step.body = Some(self.link_body(body));
+        }
+    }
+
+    fn link_body(&mut self, value: YamlValue) -> YamlValue {
+        match value {
+            YamlValue::String(s) => YamlValue::String(self.replace_within(&s)),
+            YamlValue::Sequence(items) => YamlValue::Sequence(items.into_iter().map(|v| self.link_body(v)).collect()),
+            YamlValue::Mapping(map) => {
+                YamlValue::Mapping(map.into_iter().map(|(k, v)| (k, self.link_body(v))).collect())
+            }
+            // 数字替换为占位符后会变成字符串，保持原样
+            other => other,
+        }
+    }
+
+    /// 整个值等于某个响应值时替换
+    fn replace_exact(&mut self, text: &str) -> String {
+        match self.sources.get(text).cloned() {
+            Some(source) => format!("{{{}}}", self.variable(source)),
+            None => text.to_string(),
+        }
+    }
+
+    /// 替换值中出现的响应字符串，如 `Bearer <token>` 中的 token
+    fn replace_within(&mut self, text: &str) -> String {
+        let exact = self.replace_exact(text);
+        if exact != text {
+            return exact;
+        }
+
+        let mut matches: Vec<(String, (String, String))> = self
+            .sources
+            .iter()
+            .filter(|(value, _)| value.len() >= MIN_FLOW_LENGTH && value.parse::<f64>().is_err() && text.contains(value.as_str()))
+            .map(|(value, source)| (value.clone(), source.clone()))
+            .collect();
+        // 长的值优先，避免被其中包含的短值截断
+        matches.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+        let mut result = text.to_string();
+        for (value, source) in matches {
+            if result.contains(&value) {
+                let placeholder = format!("{{{}}}", self.variable(source));
+                result = result.replace(&value, &placeholder);
+            }
+        }
+        result
+    }
+
+    /// 为响应字段分配变量名，并在产生它的步骤上添加保存规则
+    fn variable(&mut self, source: (String, String)) -> String {
+        if let Some(name) = self.flows.get(&source) {
+            return name.clone();
+        }
+
+        // 优先使用字段名，如 `data.orderId` -> `order_id`，重名时加上步骤 id
+        let (id, field) = source.clone();
+        let last = super::snake_case(field.rsplit('.').next().unwrap_or(&field));
+        let taken = |name: &str| self.flows.values().any(|v| v == name);
+        let mut name = last.clone();
+        if taken(&name) {
+            let base = format!("{}_{}", id, last);
+            name = base.clone();
+            let mut n = 2;
+            while taken(&name) {
+                name = format!("{}_{}", base, n);
+                n += 1;
+            }
+        }
+
+        if let Some(step) = self.steps.get_mut(&id) {
+            step.save_response.push(SaveRule { field, to: name.clone() });
+        }
+        self.flows.insert(source, name.clone());
+        name
+    }
+}
+
+/// 收集响应中的字符串和整数值及其字段路径
+///
+/// 保存规则的字段路径不支持数组下标，数组中的值不参与识别。
+fn collect_values(value: &JsonValue, path: String, out: &mut Vec<(String, String)>) {
+    match value {
+        JsonValue::Object(fields) => {
+            for (key, child) in fields {
+                let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                collect_values(child, child_path, out);
+            }
+        }
+        JsonValue::String(s) if s.len() >= MIN_FLOW_LENGTH && !path.is_empty() => out.push((path, s.clone())),
+        JsonValue::Number(n) if n.as_u64().is_some_and(|n| n >= 1000) && !path.is_empty() => {
+            out.push((path, n.to_string()))
+        }
+        _ => {}
+    }
+}
+
+fn entries(har: &JsonValue) -> impl Iterator<Item = &JsonValue> {
+    items(har.pointer("/log/entries"))
+}
+
+fn str_at<'a>(value: &'a JsonValue, pointer: &str) -> &'a str {
+    value.pointer(pointer).and_then(JsonValue::as_str).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(method: &str, url: &str, resource_type: &str, headers: JsonValue, response: JsonValue) -> JsonValue {
+        json!({
+            "_resourceType": resource_type,
+            "request": { "method": method, "url": url, "headers": headers },
+            "response": response,
+        })
+    }
+
+    fn json_response(status: u16, body: JsonValue) -> JsonValue {
+        json!({ "status": status, "content": { "mimeType": "application/json", "text": body.to_string() } })
+    }
+
+    fn har() -> JsonValue {
+        let mut create = entry(
+            "POST",
+            "https://api.test/v1/orders",
+            "xhr",
+            json!([
+                { "name": ":authority", "value": "api.test" },
+                { "name": "User-Agent", "value": "Mozilla/5.0" },
+                { "name": "sec-ch-ua", "value": "Chromium" },
+                { "name": "Content-Type", "value": "application/json" },
+                { "name": "Authorization", "value": "Bearer abcdef123456" },
+                { "name": "X-Client", "value": "web" }
+            ]),
+            json_response(201, json!({ "data": { "orderId": "ord_98765", "total": 1500, "items": ["ord_98765"] } })),
+        );
+        create["request"]["postData"] = json!({ "mimeType": "application/json", "text": "{\"sku\":\"A1\",\"qty\":2}" });
+
+        let mut login = entry("POST", "https://api.test/v1/login", "xhr", json!([]), json!({ "status": 302 }));
+        login["request"]["postData"] = json!({ "mimeType": "application/x-www-form-urlencoded", "text": "user=a" });
+
+        json!({ "log": { "entries": [
+            create,
+            entry(
+                "GET",
+                "https://api.test/v1/orders/ord_98765?ref=ord_98765&page=1",
+                "fetch",
+                json!([{ "name": "X-Order", "value": "order ord_98765" }]),
+                json_response(200, json!({ "status": "paid", "amount": 1500 })),
+            ),
+            entry("GET", "https://cdn.test/app.js", "script", json!([]), json!({ "status": 200, "content": { "mimeType": "application/javascript" } })),
+            entry("OPTIONS", "https://api.test/v1/orders", "preflight", json!([]), json!({ "status": 204 })),
+            login,
+            // 没有资源类型时按响应类型选择
+            json!({
+                "request": { "method": "PUT", "url": "https://other.test/v1/orders/1500", "headers": [] },
+                "response": json_response(200, json!({})),
+            }),
+        ] } })
+    }
+
+    fn env() -> EnvironmentConfig {
+        serde_yaml::from_str("base_url: https://api.test/v1\nauth:\n  token_url: /oauth/token\n").unwrap()
+    }
+
+    #[test]
+    fn lists_entries_with_default_selection() {
+        let entries = list_entries(&har());
+        let summary: Vec<_> = entries.iter().map(|e| (e.method.as_str(), e.status, e.selected)).collect();
+        assert_eq!(
+            summary,
+            [("POST", 201, true), ("GET", 200, true), ("GET", 200, false), ("OPTIONS", 204, false), ("POST", 302, true), ("PUT", 200, true)]
+        );
+        assert_eq!(entries[1].mime_type, "application/json");
+        assert_eq!(entries[2].url, "https://cdn.test/app.js");
+    }
+
+    #[test]
+    fn imports_steps_and_links_response_values() {
+        let env = env();
+        let import = import_har(&har(), None, Some(&env), &IndexMap::new()).unwrap();
+        let steps: IndexMap<String, Step> = serde_yaml::from_str(&import.content).unwrap();
+
+        assert_eq!(import.steps, ["post_orders", "get_orders", "post_login", "put_orders"]);
+        assert_eq!(steps.keys().collect::<Vec<_>>(), import.steps.iter().collect::<Vec<_>>());
+
+        let create = &steps["post_orders"];
+        assert_eq!(create.name, "POST /orders");
+        assert_eq!(create.headers, IndexMap::from([("X-Client".to_string(), "web".to_string())]));
+        assert_eq!(create.body, Some(serde_yaml::from_str("{sku: A1, qty: 2}").unwrap()));
+        assert_eq!(create.asserts, ["status == 201"]);
+        let saved: Vec<_> = create.save_response.iter().map(|r| (r.field.as_str(), r.to.as_str())).collect();
+        assert_eq!(saved, [("data.orderId", "order_id")]);
+
+        let get = &steps["get_orders"];
+        assert_eq!(get.name, "GET /orders/{order_id}");
+        assert_eq!(get.path, "/orders/{order_id}");
+        assert_eq!(get.params["ref"], "{order_id}");
+        assert_eq!(get.params["page"], "1");
+        assert_eq!(get.headers["X-Order"], "order {order_id}");
+
+        // 两个响应中都有 1500，取后出现的
+        assert_eq!(get.save_response[0].field, "amount");
+        assert_eq!(steps["put_orders"].path, "/orders/{amount}");
+
+        assert_eq!(
+            import.skipped,
+            [
+                "第 4 条请求 POST https://api.test/v1/login: 不支持 application/x-www-form-urlencoded 请求体，已忽略",
+                "主机 other.test 与 base_url 不同，已映射到 base_url",
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_authorization_without_auth_config() {
+        let import = import_har(&har(), Some(&[0, 3, 9]), None, &IndexMap::new()).unwrap();
+        let steps: IndexMap<String, Step> = serde_yaml::from_str(&import.content).unwrap();
+
+        assert_eq!(steps["post_orders"].path, "/v1/orders");
+        assert!(!steps["post_orders"].headers.contains_key("Authorization"));
+        assert_eq!(
+            import.skipped,
+            [
+                "第 0 条请求 POST https://api.test/v1/orders: 已去掉认证信息，请在环境中配置 auth",
+                "第 3 条请求 OPTIONS https://api.test/v1/orders: 不支持的 HTTP 方法",
+                "第 9 条请求不存在",
+            ]
+        );
+    }
+
+    #[test]
+    fn generated_ids_do_not_clash_with_existing_steps() {
+        let existing = IndexMap::from([("post_orders".to_string(), Step::default())]);
+        let import = import_har(&har(), Some(&[0, 0]), Some(&env()), &existing).unwrap();
+        assert_eq!(import.steps, ["post_orders_2", "post_orders_3"]);
+    }
+}
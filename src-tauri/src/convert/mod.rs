@@ -0,0 +1,317 @@
+//! 与其他格式互相转换
+//!
+//! 导入时把外部请求的 URL 还原成 step 的 `path`：去掉当前环境的 `base_url`，
+//! 按 `path_mapping` 反向映射，再去掉 `context` 前缀，与执行时拼接 URL 的顺序相反。
+
+pub mod curl;
+pub mod har;
+pub mod openapi;
+pub mod postman;
+
+use indexmap::IndexMap;
+use regex::Regex;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::{EnvironmentConfig, Step, TestConfig};
+use crate::error::{Error, Result};
+
+/// 用户数据字段，其中的空值有意义，序列化时保持原样
+const DATA_KEYS: &[&str] = &["body", "params", "headers", "variables", "path_mapping"];
+
+/// 导入生成的文件
+#[derive(Debug, Clone)]
+pub struct GeneratedFile {
+    /// 文件名（不含目录）
+    pub name: String,
+    pub content: String,
+}
+
+/// 转换结果：生成的文件及无法转换的内容
+#[derive(Debug, Clone, Default)]
+pub struct ImportOutput {
+    pub files: Vec<GeneratedFile>,
+    /// 生成的步骤数
+    pub steps: usize,
+    /// 未能转换的条目及原因
+    pub skipped: Vec<String>,
+}
+
+/// 导入结果（写入文件后返回给前端）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// 写入的文件路径
+    pub files: Vec<String>,
+    /// 生成的步骤数
+    pub steps: usize,
+    /// 未能导入的条目及原因，如 `PATCH /pets: 不支持的 HTTP 方法`
+    pub skipped: Vec<String>,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSummary {
+    /// 写入的文件路径
+    pub file: String,
+    /// 未能导出的内容及原因
+    pub skipped: Vec<String>,
+}
+
+/// 把步骤序列化为可直接粘贴到 `steps:` 下的 YAML 片段，省略未设置的字段
+pub fn steps_to_yaml(steps: &IndexMap<String, Step>) -> Result<String> {
+    let mut mapping = serde_yaml::Mapping::new();
+    for (id, step) in steps {
+        mapping.insert(YamlValue::String(id.clone()), to_compact_value(step)?);
+    }
+
+    serde_yaml::to_string(&mapping).map_err(|e| Error::Internal(format!("步骤序列化失败: {}", e)))
+}
+
+/// 把完整配置序列化为 YAML，省略未设置的字段
+pub fn config_to_yaml(config: &TestConfig) -> Result<String> {
+    serde_yaml::to_string(&to_compact_value(config)?)
+        .map_err(|e| Error::Internal(format!("配置序列化失败: {}", e)))
+}
+
+fn to_compact_value<T: Serialize>(value: &T) -> Result<YamlValue> {
+    serde_yaml::to_value(value)
+        .map(compact)
+        .map_err(|e| Error::Internal(format!("配置序列化失败: {}", e)))
+}
+
+/// 去掉映射中取默认值的字段（null、false、0、空字符串、空列表、空映射），不进入用户数据字段
+///
+/// 用户数据字段本身只在为 null 或空集合时省略，`body: false`、`body: 0` 等取值保持原样。
+fn compact(value: YamlValue) -> YamlValue {
+    let YamlValue::Mapping(map) = value else { return value };
+
+    let map = map
+        .into_iter()
+        .filter_map(|(k, v)| {
+            if k.as_str().is_some_and(|k| DATA_KEYS.contains(&k)) {
+                return (!is_empty(&v)).then_some((k, v));
+            }
+            let v = compact(v);
+            let is_default = match &v {
+                YamlValue::Bool(b) => !b,
+                YamlValue::Number(n) => n.as_f64() == Some(0.0),
+                YamlValue::String(s) => s.is_empty(),
+                v => is_empty(v),
+            };
+            (!is_default).then_some((k, v))
+        })
+        .collect();
+    YamlValue::Mapping(map)
+}
+
+/// null 或空集合
+fn is_empty(value: &YamlValue) -> bool {
+    match value {
+        YamlValue::Null => true,
+        YamlValue::Sequence(items) => items.is_empty(),
+        YamlValue::Mapping(m) => m.is_empty(),
+        _ => false,
+    }
+}
+
+/// 把生成的文件写入目录，任一文件已存在时不写入任何文件
+pub fn write_files(dir: &Path, files: &[GeneratedFile]) -> Result<Vec<String>> {
+    let paths: Vec<PathBuf> = files.iter().map(|f| dir.join(&f.name)).collect();
+    if let Some(existing) = paths.iter().find(|p| p.exists()) {
+        return Err(Error::io("文件已存在", existing, std::io::ErrorKind::AlreadyExists.into()));
+    }
+
+    fs::create_dir_all(dir).map_err(|e| Error::io("无法创建目录", dir, e))?;
+    for (path, file) in paths.iter().zip(files) {
+        fs::write(path, &file.content).map_err(|e| Error::io("无法写入文件", path, e))?;
+    }
+
+    Ok(paths.iter().map(|p| p.display().to_string()).collect())
+}
+
+/// 把名称转换为可用作 id 或文件名的形式，如 `getPetById` -> `get_pet_by_id`
+pub fn snake_case(name: &str) -> String {
+    let mut result = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && prev_lower {
+                result.push('_');
+            }
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+            result.push(c.to_ascii_lowercase());
+        } else {
+            if !result.ends_with('_') {
+                result.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    result.trim_matches('_').to_string()
+}
+
+/// 与已有 id 冲突时追加序号
+pub fn unique_id(base: &str, existing: &IndexMap<String, Step>) -> String {
+    let mut id = base.to_string();
+    let mut n = 2;
+    while existing.contains_key(&id) {
+        id = format!("{}_{}", base, n);
+        n += 1;
+    }
+    id
+}
+
+/// 首次使用时编译正则表达式，之后复用
+fn lazy_regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("无效的正则表达式"))
+}
+
+/// 数组字段的元素，字段不存在时为空
+fn items(value: Option<&JsonValue>) -> impl Iterator<Item = &JsonValue> {
+    value.and_then(JsonValue::as_array).into_iter().flatten()
+}
+
+/// 对 YAML 值中的每个字符串应用 `convert`，保持结构不变
+fn map_strings(value: YamlValue, convert: &mut impl FnMut(&str) -> String) -> YamlValue {
+    match value {
+        YamlValue::String(s) => YamlValue::String(convert(&s)),
+        YamlValue::Sequence(items) => {
+            YamlValue::Sequence(items.into_iter().map(|v| map_strings(v, convert)).collect())
+        }
+        YamlValue::Mapping(map) => {
+            YamlValue::Mapping(map.into_iter().map(|(k, v)| (k, map_strings(v, convert))).collect())
+        }
+        other => other,
+    }
+}
+
+/// 把请求 URL 还原为 step 的 `path` 和查询参数
+///
+/// 主机部分总是映射到当前环境的 `base_url`；URL 无法解析时按原样作为路径。
+pub fn step_path(url: &str, env: Option<&EnvironmentConfig>) -> (String, IndexMap<String, YamlValue>) {
+    let parsed = Url::parse(url).or_else(|_| Url::parse(&format!("http://{}", url)));
+    let Ok(parsed) = parsed else {
+        return (url.to_string(), IndexMap::new());
+    };
+
+    let params = parsed
+        .query_pairs()
+        .map(|(k, v)| (k.into_owned(), YamlValue::String(v.into_owned())))
+        .collect();
+
+    let mut path = parsed.path().to_string();
+    let Some(env) = env else { return (path, params) };
+
+    // base_url 可能带有路径前缀，如 http://host/gateway
+    let base_path = Url::parse(&env.base_url)
+        .map(|u| u.path().trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    if let Some(rest) = strip_path_prefix(&path, &base_path) {
+        path = rest.to_string();
+    }
+
+    // 执行时取第一个匹配的映射，这里同样取第一个匹配的目标
+    for (source, target) in &env.path_mapping {
+        if let Some(rest) = strip_path_prefix(&path, target) {
+            path = format!("{}{}", source, rest);
+            break;
+        }
+    }
+
+    if let Some(rest) = strip_path_prefix(&path, &env.context) {
+        path = rest.to_string();
+    }
+
+    (path, params)
+}
+
+/// 步骤路径加上 `context` 并应用 `path_mapping`，与执行时拼接 URL 的顺序一致（不含 base_url）
+pub fn request_path(path: &str, env: &EnvironmentConfig) -> String {
+    let full_path = format!("{}{}", env.context, path);
+    for (source, target) in &env.path_mapping {
+        if full_path.starts_with(source.as_str()) {
+            return full_path.replacen(source.as_str(), target, 1);
+        }
+    }
+    full_path
+}
+
+/// 认证配置放置 token 的键及是否在查询参数中，未配置认证时返回 None
+///
+/// 导入时去掉这个键，执行时由认证配置自动添加。
+pub fn auth_target(env: Option<&EnvironmentConfig>) -> Option<(String, bool)> {
+    let auth = env?.auth.as_ref().filter(|a| !a.token_url.is_empty())?;
+    let in_query = auth.auth_position == "query";
+    let key = match (in_query, auth.auth_key.is_empty()) {
+        (true, true) => "access_token",
+        (false, true) => "Authorization",
+        (_, false) => auth.auth_key.as_str(),
+    };
+    Some((key.to_string(), in_query))
+}
+
+/// 去掉路径前缀，前缀必须在 `/` 边界上结束
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    if prefix.is_empty() {
+        return None;
+    }
+    let rest = path.strip_prefix(prefix)?;
+    (rest.is_empty() || rest.starts_with('/') || prefix.ends_with('/')).then_some(rest)
+}
+
+/// 由方法和路径生成步骤 id，如 `GET /v1/users/{id}` -> `get_users`，与已有 id 冲突时追加序号
+pub fn step_id(method: &str, path: &str, existing: &IndexMap<String, Step>) -> String {
+    let segment = path
+        .split('/')
+        .rev()
+        .find(|s| !s.is_empty() && !s.starts_with('{') && !s.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or("root");
+    let base = format!("{}_{}", method.to_lowercase(), snake_case(segment));
+
+    unique_id(&base, existing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step_yaml(step: Step) -> String {
+        steps_to_yaml(&IndexMap::from([("step".to_string(), step)])).unwrap()
+    }
+
+    #[test]
+    fn compact_drops_default_fields() {
+        let yaml = step_yaml(Step { method: "GET".to_string(), path: "/users".to_string(), ..Default::default() });
+        assert_eq!(yaml, "step:\n  method: GET\n  path: /users\n");
+    }
+
+    #[test]
+    fn compact_keeps_falsy_data_values() {
+        for (body, expected) in [("false", "false"), ("0", "0"), ("''", "''")] {
+            let step = Step { body: Some(serde_yaml::from_str(body).unwrap()), ..Default::default() };
+            assert_eq!(step_yaml(step), format!("step:\n  body: {}\n", expected));
+        }
+
+        let step = Step {
+            params: IndexMap::from([("page".to_string(), YamlValue::from(0)), ("q".to_string(), YamlValue::Null)]),
+            body: Some(YamlValue::Mapping(Default::default())),
+            ..Default::default()
+        };
+        assert_eq!(step_yaml(step), "step:\n  params:\n    page: 0\n    q: null\n");
+    }
+
+    #[test]
+    fn step_ids_are_unique() {
+        let mut steps = IndexMap::new();
+        let id = step_id("GET", "/v1/users/{id}", &steps);
+        assert_eq!(id, "get_users");
+        steps.insert(id, Step::default());
+        assert_eq!(step_id("GET", "/users/42", &steps), "get_users_2");
+        assert_eq!(step_id("POST", "/", &steps), "post_root");
+    }
+}
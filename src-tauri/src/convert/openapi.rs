@@ -0,0 +1,933 @@
+//! 从 OpenAPI 3 / Swagger 2 文档生成测试配置
+//!
+//! 每个操作生成一个 step：路径中的 `{param}` 原样保留，请求体和参数取文档中的示例，
+//! 没有示例时按 schema 生成；并附带一条检查成功状态码的断言。
+//!
+//! 也支持反向导出：由步骤生成 OpenAPI 3 文档骨架。
+
+use indexmap::IndexMap;
+use regex::Regex;
+use serde_yaml::{Mapping, Value as YamlValue};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::config::{
+    EnvironmentConfig, ExecutionResult, GlobalConfig, ImportEntry, ProfileConfig, Step, TestConfig, HTTP_METHODS,
+};
+use crate::error::{Error, Result};
+
+use super::{lazy_regex, snake_case, unique_id, GeneratedFile, ImportOutput};
+
+/// 生成的配置中环境的名称
+const ENVIRONMENT: &str = "default";
+
+/// schema 展开的最大深度，超过后以 null 代替
+const MAX_DEPTH: usize = 8;
+
+/// 没有标签的操作归入的分组
+const UNTAGGED: &str = "default";
+
+/// 导入选项
+#[derive(Debug, Clone, Default)]
+pub struct OpenApiOptions {
+    /// 按操作的第一个标签拆分为多个文件，环境配置单独放在 `<name>_global.yml` 中由各文件导入
+    pub group_by_tag: bool,
+}
+
+/// 读取 OpenAPI 文档（JSON 或 YAML）并生成测试配置，生成的文件名以文档文件名为前缀
+pub fn import_openapi_file(path: &Path, options: &OpenApiOptions) -> Result<ImportOutput> {
+    let content = fs::read_to_string(path).map_err(|e| Error::io("无法读取 OpenAPI 文档", path, e))?;
+    let spec: YamlValue = serde_yaml::from_str(&content)
+        .map_err(|e| Error::config_parse(format!("OpenAPI 文档解析错误: {}", e), Some(path)))?;
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(snake_case)
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "openapi".to_string());
+
+    import_openapi(&spec, &name, options)
+}
+
+/// 由解析后的 OpenAPI 文档生成测试配置
+pub fn import_openapi(spec: &YamlValue, name: &str, options: &OpenApiOptions) -> Result<ImportOutput> {
+    if spec.get("openapi").is_none() && spec.get("swagger").is_none() {
+        return Err(Error::Validation("不是 OpenAPI / Swagger 文档：缺少 openapi 或 swagger 字段".to_string()));
+    }
+
+    let document = Document { root: spec };
+    let mut steps: IndexMap<String, Step> = IndexMap::new();
+    let mut skipped = Vec::new();
+
+    if let Some(paths) = spec.get("paths").and_then(YamlValue::as_mapping) {
+        for (path, item) in paths {
+            let Some(path) = path.as_str() else { continue };
+            let item = document.resolve(item);
+            let shared = item.get("parameters").and_then(YamlValue::as_sequence).cloned().unwrap_or_default();
+
+            for (method, operation) in item.as_mapping().into_iter().flatten() {
+                let Some(method) = method.as_str() else { continue };
+                if matches!(method, "parameters" | "summary" | "description" | "servers") || method.starts_with("x-") {
+                    continue;
+                }
+                let method = method.to_uppercase();
+                if !HTTP_METHODS.contains(&method.as_str()) {
+                    skipped.push(format!("{} {}: 不支持的 HTTP 方法", method, path));
+                    continue;
+                }
+
+                let step = document.step(&method, path, operation, &shared);
+                let base = operation
+                    .get("operationId")
+                    .and_then(YamlValue::as_str)
+                    .map(snake_case)
+                    .filter(|s| !s.is_empty());
+                let id = match base {
+                    Some(base) => unique_id(&base, &steps),
+                    None => super::step_id(&method, path, &steps),
+                };
+                steps.insert(id, step);
+            }
+        }
+    }
+
+    let global = GlobalConfig {
+        profile: ProfileConfig {
+            active: ENVIRONMENT.to_string(),
+            environments: IndexMap::from([(
+                ENVIRONMENT.to_string(),
+                EnvironmentConfig {
+                    base_url: document.base_url(),
+                    context: String::new(),
+                    brand: String::new(),
+                    path_mapping: IndexMap::new(),
+                    auth: None,
+                    timeout: Default::default(),
+                    retry: Default::default(),
+                },
+            )]),
+        },
+        ..Default::default()
+    };
+
+    let title = spec
+        .get("info")
+        .and_then(|i| i.get("title"))
+        .and_then(YamlValue::as_str)
+        .unwrap_or(name);
+    let header = format!("# 由 OpenAPI 文档生成: {}\n", title);
+    let step_count = steps.len();
+
+    let files = if options.group_by_tag {
+        let global_name = format!("{}_global.yml", name);
+        let mut groups: IndexMap<String, IndexMap<String, Step>> = IndexMap::new();
+        for (id, step) in steps {
+            let tag = step.tags.first().map(|t| snake_case(t)).filter(|t| !t.is_empty());
+            groups.entry(tag.unwrap_or_else(|| UNTAGGED.to_string())).or_default().insert(id, step);
+        }
+
+        let mut files = vec![GeneratedFile {
+            content: header.clone() + &super::config_to_yaml(&TestConfig { global, ..Default::default() })?,
+            name: global_name.clone(),
+        }];
+        for (tag, steps) in groups {
+            // 避免与环境配置文件同名
+            let tag = if tag == "global" { "global_api".to_string() } else { tag };
+            let config = TestConfig {
+                imports: vec![ImportEntry::Path(global_name.clone())],
+                steps,
+                ..Default::default()
+            };
+            files.push(GeneratedFile {
+                name: format!("{}_{}.yml", name, tag),
+                content: header.clone() + &super::config_to_yaml(&config)?,
+            });
+        }
+        files
+    } else {
+        let config = TestConfig { global, steps, ..Default::default() };
+        vec![GeneratedFile {
+            name: format!("{}_tests.yml", name),
+            content: header + &super::config_to_yaml(&config)?,
+        }]
+    };
+
+    Ok(ImportOutput { files, steps: step_count, skipped })
+}
+
+struct Document<'a> {
+    root: &'a YamlValue,
+}
+
+impl<'a> Document<'a> {
+    /// 展开 `$ref`（只支持文档内引用，如 `#/components/schemas/Pet`）
+    fn resolve(&self, value: &'a YamlValue) -> &'a YamlValue {
+        let mut value = value;
+        for _ in 0..MAX_DEPTH {
+            let Some(target) = value.get("$ref").and_then(YamlValue::as_str).and_then(|r| self.lookup(r)) else {
+                break;
+            };
+            value = target;
+        }
+        value
+    }
+
+    fn lookup(&self, reference: &str) -> Option<&'a YamlValue> {
+        let pointer = reference.strip_prefix("#/")?;
+        pointer.split('/').try_fold(self.root, |node, segment| {
+            node.get(segment.replace("~1", "/").replace("~0", "~"))
+        })
+    }
+
+    /// 基础 URL：OpenAPI 3 取第一个 server（变量取默认值），Swagger 2 由 schemes、host、basePath 拼接
+    fn base_url(&self) -> String {
+        let url = if let Some(server) = self.root.get("servers").and_then(|s| s.get(0)) {
+            let mut url = server.get("url").and_then(YamlValue::as_str).unwrap_or_default().to_string();
+            for (name, variable) in server.get("variables").and_then(YamlValue::as_mapping).into_iter().flatten() {
+                if let (Some(name), Some(default)) = (name.as_str(), variable.get("default")) {
+                    url = url.replace(&format!("{{{}}}", name), &scalar_string(default));
+                }
+            }
+            url
+        } else {
+            let host = self.root.get("host").and_then(YamlValue::as_str).unwrap_or_default();
+            let scheme = self
+                .root
+                .get("schemes")
+                .and_then(|s| s.get(0))
+                .and_then(YamlValue::as_str)
+                .unwrap_or("https");
+            let base_path = self.root.get("basePath").and_then(YamlValue::as_str).unwrap_or_default();
+            if host.is_empty() {
+                base_path.to_string()
+            } else {
+                format!("{}://{}{}", scheme, host, base_path)
+            }
+        };
+
+        // 相对地址无法直接请求，补上本机地址，导入后再修改
+        let url = if url.starts_with("http://") || url.starts_with("https://") {
+            url
+        } else {
+            format!("http://localhost{}", url)
+        };
+        url.trim_end_matches('/').to_string()
+    }
+
+    fn step(&self, method: &str, path: &str, operation: &YamlValue, shared: &[YamlValue]) -> Step {
+        let mut step = Step {
+            name: operation
+                .get("summary")
+                .or_else(|| operation.get("operationId"))
+                .and_then(YamlValue::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{} {}", method, path)),
+            description: operation.get("description").and_then(YamlValue::as_str).unwrap_or_default().to_string(),
+            tags: operation
+                .get("tags")
+                .and_then(YamlValue::as_sequence)
+                .into_iter()
+                .flatten()
+                .filter_map(|t| t.as_str().map(str::to_string))
+                .collect(),
+            method: method.to_string(),
+            path: path.to_string(),
+            ..Default::default()
+        };
+
+        // 操作级参数覆盖路径级同名参数
+        let mut parameters: IndexMap<(String, String), &YamlValue> = IndexMap::new();
+        let own = operation.get("parameters").and_then(YamlValue::as_sequence);
+        for parameter in shared.iter().chain(own.into_iter().flatten()) {
+            let parameter = self.resolve(parameter);
+            let name = parameter.get("name").and_then(YamlValue::as_str).unwrap_or_default();
+            let location = parameter.get("in").and_then(YamlValue::as_str).unwrap_or_default();
+            parameters.insert((name.to_string(), location.to_string()), parameter);
+        }
+
+        for ((name, location), parameter) in parameters {
+            let example = self.parameter_example(parameter);
+            let required = parameter.get("required").and_then(YamlValue::as_bool).unwrap_or(false);
+            // 变量名需是合法标识符，否则占位符会被当作表达式，如 `{X-Request-Id}`
+            let placeholder = || YamlValue::String(format!("{{{}}}", snake_case(&name)));
+
+            match location.as_str() {
+                "path" => {
+                    if let Some(example) = example {
+                        step.variables.insert(name, example);
+                    }
+                }
+                "query" => {
+                    if let Some(value) = example.or_else(|| required.then(placeholder)) {
+                        step.params.insert(name, value);
+                    }
+                }
+                "header" => {
+                    if let Some(value) = example.or_else(|| required.then(placeholder)) {
+                        step.headers.insert(name, scalar_string(&value));
+                    }
+                }
+                "body" => {
+                    let schema = parameter.get("schema").unwrap_or(&YamlValue::Null);
+                    step.body = Some(self.example(schema, 0, &mut Vec::new()));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(body) = operation.get("requestBody").map(|b| self.resolve(b)) {
+            step.body = self.request_body_example(body).or(step.body);
+        }
+
+        step.asserts.push(format!("status == {}", success_status(operation)));
+        step
+    }
+
+    /// 参数的示例值，只使用文档中显式给出的值
+    fn parameter_example(&self, parameter: &YamlValue) -> Option<YamlValue> {
+        explicit_example(parameter)
+            .or_else(|| parameter.get("schema").and_then(|s| explicit_example(self.resolve(s))))
+    }
+
+    /// OpenAPI 3 的 JSON 请求体示例
+    fn request_body_example(&self, body: &YamlValue) -> Option<YamlValue> {
+        let content = body.get("content")?.as_mapping()?;
+        let media = content
+            .iter()
+            .find(|(k, _)| k.as_str().is_some_and(|k| k.contains("json")))
+            .map(|(_, v)| v)?;
+
+        if let Some(example) = media.get("example") {
+            return Some(example.clone());
+        }
+        if let Some((_, example)) = media.get("examples").and_then(YamlValue::as_mapping).and_then(|m| m.iter().next()) {
+            if let Some(value) = self.resolve(example).get("value") {
+                return Some(value.clone());
+            }
+        }
+        media.get("schema").map(|schema| self.example(schema, 0, &mut Vec::new()))
+    }
+
+    /// 按 schema 生成示例值，`seen` 记录正在展开的引用以避免循环
+    fn example(&self, schema: &YamlValue, depth: usize, seen: &mut Vec<String>) -> YamlValue {
+        if depth > MAX_DEPTH {
+            return YamlValue::Null;
+        }
+
+        if let Some(reference) = schema.get("$ref").and_then(YamlValue::as_str) {
+            if seen.iter().any(|r| r == reference) {
+                return YamlValue::Null;
+            }
+            let Some(target) = self.lookup(reference) else { return YamlValue::Null };
+            seen.push(reference.to_string());
+            let value = self.example(target, depth + 1, seen);
+            seen.pop();
+            return value;
+        }
+
+        if let Some(example) = explicit_example(schema) {
+            return example;
+        }
+
+        if let Some(parts) = schema.get("allOf").and_then(YamlValue::as_sequence) {
+            let mut merged = serde_yaml::Mapping::new();
+            for part in parts {
+                if let YamlValue::Mapping(fields) = self.example(part, depth + 1, seen) {
+                    merged.extend(fields);
+                }
+            }
+            return YamlValue::Mapping(merged);
+        }
+
+        for key in ["oneOf", "anyOf"] {
+            if let Some(first) = schema.get(key).and_then(|s| s.get(0)) {
+                return self.example(first, depth + 1, seen);
+            }
+        }
+
+        let schema_type = schema.get("type").and_then(YamlValue::as_str).unwrap_or_else(|| {
+            if schema.get("properties").is_some() { "object" } else { "" }
+        });
+
+        match schema_type {
+            "object" => {
+                let mut fields = serde_yaml::Mapping::new();
+                for (name, property) in schema.get("properties").and_then(YamlValue::as_mapping).into_iter().flatten() {
+                    fields.insert(name.clone(), self.example(property, depth + 1, seen));
+                }
+                YamlValue::Mapping(fields)
+            }
+            "array" => {
+                let items = schema.get("items").unwrap_or(&YamlValue::Null);
+                YamlValue::Sequence(vec![self.example(items, depth + 1, seen)])
+            }
+            "string" => YamlValue::String(
+                match schema.get("format").and_then(YamlValue::as_str) {
+                    Some("date-time") => "2024-01-01T00:00:00Z",
+                    Some("date") => "2024-01-01",
+                    Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                    Some("email") => "user@example.com",
+                    _ => "string",
+                }
+                .to_string(),
+            ),
+            "integer" | "number" => YamlValue::Number(0.into()),
+            "boolean" => YamlValue::Bool(false),
+            _ => YamlValue::Null,
+        }
+    }
+}
+
+/// 文档中显式给出的示例：`example`、`x-example`、`default` 或第一个枚举值
+fn explicit_example(value: &YamlValue) -> Option<YamlValue> {
+    ["example", "x-example", "default"]
+        .iter()
+        .find_map(|key| value.get(key).cloned())
+        .or_else(|| value.get("enum").and_then(|e| e.get(0)).cloned())
+}
+
+/// 第一个 2xx 响应码，没有时为 200
+fn success_status(operation: &YamlValue) -> u16 {
+    operation
+        .get("responses")
+        .and_then(YamlValue::as_mapping)
+        .into_iter()
+        .flatten()
+        .filter_map(|(code, _)| match code {
+            YamlValue::Number(n) => n.as_u64().and_then(|n| u16::try_from(n).ok()),
+            YamlValue::String(s) => s.parse().ok(),
+            _ => None,
+        })
+        .find(|code| (200..300).contains(code))
+        .unwrap_or(200)
+}
+
+fn scalar_string(value: &YamlValue) -> String {
+    match value {
+        YamlValue::String(s) => s.clone(),
+        YamlValue::Null => String::new(),
+        other => serde_yaml::to_string(other).unwrap_or_default().trim_end().to_string(),
+    }
+}
+
+/// 由步骤生成 OpenAPI 3 文档骨架，返回文档及无法导出的内容
+///
+/// 路径、方法、参数和请求体来自步骤定义；`examples` 为执行结果，
+/// 有对应步骤的成功响应时据此生成响应示例和 schema，否则只根据状态码断言生成响应码。
+pub fn export_openapi(
+    config: &TestConfig,
+    env: &EnvironmentConfig,
+    title: &str,
+    examples: &[ExecutionResult],
+) -> (YamlValue, Vec<String>) {
+    let mut report = Vec::new();
+    let mut paths = Mapping::new();
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    let placeholder = lazy_regex(&PLACEHOLDER, r"\{([^{}]+)\}");
+    let auth = super::auth_target(Some(env));
+
+    for (id, step) in &config.steps {
+        let context = format!("步骤 '{}'", id);
+        let path = super::request_path(&step.path, env);
+        let method = step.method.to_lowercase();
+
+        if placeholder.captures_iter(&path).any(|caps| !is_identifier(&caps[1])) {
+            report.push(format!("{}: 路径中包含表达式占位符，未导出", context));
+            continue;
+        }
+
+        let item = paths
+            .entry(YamlValue::String(path.clone()))
+            .or_insert_with(|| YamlValue::Mapping(Mapping::new()));
+        let YamlValue::Mapping(item) = item else { continue };
+        if item.contains_key(method.as_str()) {
+            report.push(format!("{}: {} {} 已由其他步骤导出", context, step.method.to_uppercase(), path));
+            continue;
+        }
+
+        let mut operation = Mapping::new();
+        operation.insert("operationId".into(), id.as_str().into());
+        if !step.name.is_empty() {
+            operation.insert("summary".into(), step.name.as_str().into());
+        }
+        if !step.description.is_empty() {
+            operation.insert("description".into(), step.description.as_str().into());
+        }
+        if !step.tags.is_empty() {
+            operation.insert("tags".into(), string_list(&step.tags));
+        }
+
+        let mut parameters = Vec::new();
+        for caps in placeholder.captures_iter(&path) {
+            let name = &caps[1];
+            let mut parameter = mapping([
+                ("name", name.into()),
+                ("in", "path".into()),
+                ("required", true.into()),
+                ("schema", object([("type", "string".into())])),
+            ]);
+            let example = step.variables.get(name).or_else(|| config.variables.get(name));
+            let example = example.cloned().or_else(|| (name == "brand").then(|| env.brand.as_str().into()));
+            if let Some(example) = example.filter(|e| !has_placeholder(e)) {
+                parameter.insert("example".into(), example);
+            }
+            parameters.push(YamlValue::Mapping(parameter));
+        }
+        for (name, value) in &step.params {
+            parameters.push(parameter(name, "query", value));
+        }
+        for (name, value) in &step.headers {
+            let is_auth = auth.as_ref().is_some_and(|(key, _)| key.eq_ignore_ascii_case(name));
+            if !is_auth && !name.eq_ignore_ascii_case("content-type") {
+                parameters.push(parameter(name, "header", &YamlValue::String(value.clone())));
+            }
+        }
+        if !parameters.is_empty() {
+            operation.insert("parameters".into(), YamlValue::Sequence(parameters));
+        }
+
+        if let Some(body) = step.body.as_ref().filter(|_| matches!(method.as_str(), "post" | "put")) {
+            operation.insert(
+                "requestBody".into(),
+                object([("content", json_content(body))]),
+            );
+        }
+
+        operation.insert("responses".into(), YamlValue::Mapping(responses(id, step, examples)));
+        item.insert(method.as_str().into(), YamlValue::Mapping(operation));
+    }
+
+    let mut document = mapping([
+        ("openapi", "3.0.3".into()),
+        ("info", object([("title", title.into()), ("version", "1.0.0".into())])),
+        ("servers", YamlValue::Sequence(vec![object([("url", env.base_url.as_str().into())])])),
+        ("paths", YamlValue::Mapping(paths)),
+    ]);
+
+    if let Some((key, in_query)) = auth {
+        let prefix = env.auth.as_ref().map(|a| a.auth_prefix.as_str()).unwrap_or_default();
+        let scheme = if !in_query && key == "Authorization" && (prefix.is_empty() || prefix == "Bearer") {
+            object([("type", "http".into()), ("scheme", "bearer".into())])
+        } else {
+            object([
+                ("type", "apiKey".into()),
+                ("in", if in_query { "query" } else { "header" }.into()),
+                ("name", key.as_str().into()),
+            ])
+        };
+        document.insert(
+            "components".into(),
+            object([("securitySchemes", object([("auth", scheme)]))]),
+        );
+        document.insert(
+            "security".into(),
+            YamlValue::Sequence(vec![object([("auth", YamlValue::Sequence(Vec::new()))])]),
+        );
+    }
+
+    (YamlValue::Mapping(document), report)
+}
+
+/// 响应：优先使用执行结果中最近一次的响应，否则取状态码断言，都没有时为 200
+fn responses(id: &str, step: &Step, examples: &[ExecutionResult]) -> Mapping {
+    static STATUS: OnceLock<Regex> = OnceLock::new();
+    let status = lazy_regex(&STATUS, r"^\s*status\s*==\s*(\d+)\s*$");
+    let mut responses = Mapping::new();
+
+    let recorded = examples.iter().rev().find(|r| r.step_name == id && r.response_status > 0);
+    if let Some(result) = recorded {
+        let mut response = mapping([("description", "执行时记录的响应".into())]);
+        if let Some(body) = result.response_body.as_ref().filter(|b| !b.is_null()) {
+            let body = serde_yaml::to_value(body).unwrap_or(YamlValue::Null);
+            response.insert("content".into(), json_content(&body));
+        }
+        responses.insert(result.response_status.to_string().into(), YamlValue::Mapping(response));
+    }
+
+    for caps in step.asserts.iter().filter_map(|a| status.captures(a)) {
+        responses
+            .entry(caps[1].into())
+            .or_insert_with(|| object([("description", "OK".into())]));
+    }
+
+    if responses.is_empty() {
+        responses.insert("200".into(), object([("description", "OK".into())]));
+    }
+    responses
+}
+
+fn parameter(name: &str, location: &str, value: &YamlValue) -> YamlValue {
+    let mut parameter = mapping([
+        ("name", name.into()),
+        ("in", location.into()),
+        ("schema", infer_schema(value)),
+    ]);
+    if !has_placeholder(value) {
+        parameter.insert("example".into(), value.clone());
+    }
+    YamlValue::Mapping(parameter)
+}
+
+fn json_content(example: &YamlValue) -> YamlValue {
+    object([(
+        "application/json",
+        object([("schema", infer_schema(example)), ("example", example.clone())]),
+    )])
+}
+
+/// 由示例值推断 schema
+fn infer_schema(value: &YamlValue) -> YamlValue {
+    match value {
+        YamlValue::Mapping(fields) => {
+            let properties: Mapping = fields.iter().map(|(k, v)| (k.clone(), infer_schema(v))).collect();
+            object([("type", "object".into()), ("properties", YamlValue::Mapping(properties))])
+        }
+        YamlValue::Sequence(items) => {
+            let item_schema = items.first().map(infer_schema).unwrap_or_else(|| object([]));
+            object([("type", "array".into()), ("items", item_schema)])
+        }
+        YamlValue::Number(n) if n.is_i64() || n.is_u64() => object([("type", "integer".into())]),
+        YamlValue::Number(_) => object([("type", "number".into())]),
+        YamlValue::Bool(_) => object([("type", "boolean".into())]),
+        YamlValue::Null => object([("nullable", true.into())]),
+        _ => object([("type", "string".into())]),
+    }
+}
+
+/// 值中是否包含 `{var}` 占位符，这类值不能作为示例
+fn has_placeholder(value: &YamlValue) -> bool {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    match value {
+        YamlValue::String(s) => lazy_regex(&PLACEHOLDER, r"\{[^{}]+\}").is_match(s),
+        YamlValue::Sequence(items) => items.iter().any(has_placeholder),
+        YamlValue::Mapping(map) => map.values().any(has_placeholder),
+        _ => false,
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.split('.').all(|p| !p.is_empty() && p.chars().all(|c| c.is_alphanumeric() || c == '_'))
+}
+
+fn mapping<const N: usize>(pairs: [(&str, YamlValue); N]) -> Mapping {
+    pairs.into_iter().map(|(k, v)| (YamlValue::from(k), v)).collect()
+}
+
+fn string_list(items: &[String]) -> YamlValue {
+    YamlValue::Sequence(items.iter().map(|s| YamlValue::from(s.as_str())).collect())
+}
+
+fn object<const N: usize>(pairs: [(&str, YamlValue); N]) -> YamlValue {
+    YamlValue::Mapping(mapping(pairs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PET_STORE: &str = r##"
+openapi: 3.0.0
+info:
+  title: Pet Store
+servers:
+  - url: https://{region}.pets.test/v1/
+    variables:
+      region:
+        default: eu
+paths:
+  /pets:
+    get:
+      operationId: listPets
+      tags: [pets]
+      parameters:
+        - { name: limit, in: query, schema: { type: integer, default: 20 } }
+        - { name: cursor, in: query, schema: { type: string } }
+        - { name: X-Request-Id, in: header, required: true, schema: { type: string } }
+      responses:
+        "200": { description: ok }
+    post:
+      summary: Create pet
+      tags: [pets]
+      requestBody:
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Pet" }
+      responses:
+        "201": { description: created }
+        "400": { description: invalid }
+    patch:
+      responses: {}
+  /pets/{petId}:
+    parameters:
+      - { name: petId, in: path, required: true, schema: { type: integer, example: 7 } }
+    get:
+      operationId: getPet
+      tags: [pets]
+      responses:
+        default: { description: pet }
+    delete:
+      tags: [global]
+      responses:
+        "204": { description: deleted }
+  /health:
+    get:
+      responses:
+        "200": { description: ok }
+components:
+  schemas:
+    Pet:
+      type: object
+      properties:
+        name: { type: string, example: Rex }
+        born: { type: string, format: date }
+        tags: { type: array, items: { type: string } }
+        owner: { $ref: "#/components/schemas/Owner" }
+        kind: { enum: [dog, cat] }
+    Owner:
+      allOf:
+        - properties: { id: { type: integer } }
+        - properties: { pet: { $ref: "#/components/schemas/Pet" } }
+"##;
+
+    fn import(spec: &str, options: &OpenApiOptions) -> ImportOutput {
+        import_openapi(&serde_yaml::from_str(spec).unwrap(), "petstore", options).unwrap()
+    }
+
+    fn parse(file: &GeneratedFile) -> TestConfig {
+        serde_yaml::from_str(&file.content).unwrap()
+    }
+
+    #[test]
+    fn imports_openapi_3() {
+        let output = import(PET_STORE, &OpenApiOptions::default());
+        assert_eq!(output.files.len(), 1);
+        assert_eq!(output.files[0].name, "petstore_tests.yml");
+        assert!(output.files[0].content.starts_with("# 由 OpenAPI 文档生成: Pet Store\n"));
+        assert_eq!(output.steps, 5);
+        assert_eq!(output.skipped, ["PATCH /pets: 不支持的 HTTP 方法"]);
+
+        let config = parse(&output.files[0]);
+        assert_eq!(config.global.profile.environments["default"].base_url, "https://eu.pets.test/v1");
+        let ids: Vec<_> = config.steps.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["list_pets", "post_pets", "get_pet", "delete_pets", "get_health"]);
+
+        let list = &config.steps["list_pets"];
+        assert_eq!((list.name.as_str(), list.method.as_str(), list.path.as_str()), ("listPets", "GET", "/pets"));
+        assert_eq!(list.tags, ["pets"]);
+        assert_eq!(list.params, IndexMap::from([("limit".to_string(), YamlValue::from(20))]));
+        assert_eq!(list.headers["X-Request-Id"], "{x_request_id}");
+        assert_eq!(list.asserts, ["status == 200"]);
+
+        let create = &config.steps["post_pets"];
+        assert_eq!(create.name, "Create pet");
+        let expected: YamlValue = serde_yaml::from_str(
+            "{ name: Rex, born: '2024-01-01', tags: [string], owner: { id: 0, pet: null }, kind: dog }",
+        )
+        .unwrap();
+        assert_eq!(create.body, Some(expected));
+        assert_eq!(create.asserts, ["status == 201"]);
+
+        let get = &config.steps["get_pet"];
+        assert_eq!(get.path, "/pets/{petId}");
+        assert_eq!(get.variables["petId"], 7);
+        assert_eq!(get.asserts, ["status == 200"]);
+        assert_eq!(config.steps["delete_pets"].asserts, ["status == 204"]);
+    }
+
+    #[test]
+    fn groups_steps_by_tag() {
+        let output = import(PET_STORE, &OpenApiOptions { group_by_tag: true });
+        let names: Vec<_> = output.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["petstore_global.yml", "petstore_pets.yml", "petstore_global_api.yml", "petstore_default.yml"]);
+
+        let global = parse(&output.files[0]);
+        assert!(global.steps.is_empty());
+        assert_eq!(global.global.profile.active, "default");
+
+        let pets = parse(&output.files[1]);
+        assert_eq!(pets.imports.len(), 1);
+        assert_eq!(pets.imports[0].path(), "petstore_global.yml");
+        assert_eq!(pets.steps.keys().collect::<Vec<_>>(), ["list_pets", "post_pets", "get_pet"]);
+        assert!(pets.global.profile.environments.is_empty());
+        assert!(parse(&output.files[3]).steps.contains_key("get_health"));
+    }
+
+    #[test]
+    fn imports_swagger_2() {
+        let spec = r#"
+swagger: "2.0"
+host: api.test
+basePath: /v2
+schemes: [http]
+paths:
+  /users:
+    post:
+      parameters:
+        - name: body
+          in: body
+          schema:
+            type: object
+            properties:
+              email: { type: string, format: email }
+              active: { type: boolean }
+        - { name: dryRun, in: query, x-example: true }
+      responses:
+        201: { description: created }
+"#;
+        let config = parse(&import(spec, &OpenApiOptions::default()).files[0]);
+
+        assert_eq!(config.global.profile.environments["default"].base_url, "http://api.test/v2");
+        let step = &config.steps["post_users"];
+        assert_eq!(step.body, Some(serde_yaml::from_str("{ email: user@example.com, active: false }").unwrap()));
+        assert_eq!(step.params["dryRun"], true);
+        assert_eq!(step.asserts, ["status == 201"]);
+    }
+
+    #[test]
+    fn relative_server_uses_localhost() {
+        let spec = "openapi: 3.1.0\nservers:\n  - url: /api/\npaths: {}\n";
+        let output = import(spec, &OpenApiOptions::default());
+        assert_eq!(parse(&output.files[0]).global.profile.environments["default"].base_url, "http://localhost/api");
+        assert_eq!(output.steps, 0);
+
+        let message = import_openapi(&serde_yaml::from_str("info: {}").unwrap(), "x", &Default::default())
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("缺少 openapi 或 swagger 字段"));
+    }
+
+    const STEPS: &str = r#"
+global:
+  profile:
+    active: dev
+    dev:
+      base_url: https://api.test
+      context: /v1
+      auth:
+        token_url: /oauth/token
+variables:
+  id: 7
+steps:
+  get_pet:
+    name: Get pet
+    tags: [pets]
+    method: GET
+    path: /pets/{id}
+    params:
+      verbose: true
+    headers:
+      Authorization: Bearer abc
+      X-Trace: "{trace}"
+    assert:
+      - status == 200
+  create_pet:
+    method: POST
+    path: /pets
+    body:
+      name: Rex
+      age: 3
+    assert:
+      - status == 201
+      - body.id > 0
+  get_pet_again:
+    method: GET
+    path: /pets/{id}
+  next_pet:
+    method: GET
+    path: /pets/{id + 1}
+"#;
+
+    fn recorded(step_name: &str, status: u16, body: serde_json::Value) -> ExecutionResult {
+        ExecutionResult {
+            success: true,
+            status: crate::config::ExecutionStatus::Passed,
+            step_name: step_name.to_string(),
+            request_url: String::new(),
+            request_method: "GET".to_string(),
+            request_headers: IndexMap::new(),
+            request_body: None,
+            response_status: status,
+            response_headers: IndexMap::new(),
+            response_body: Some(body),
+            validations: Vec::new(),
+            duration_ms: 0,
+            error: None,
+            error_kind: None,
+            attempts: Vec::new(),
+        }
+    }
+
+    fn export(examples: &[ExecutionResult]) -> (YamlValue, Vec<String>) {
+        let config: TestConfig = serde_yaml::from_str(STEPS).unwrap();
+        let env = config.global.profile.environments["dev"].clone();
+        export_openapi(&config, &env, "Pets", examples)
+    }
+
+    fn yaml(text: &str) -> YamlValue {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn exports_openapi_document() {
+        let (document, report) = export(&[recorded("get_pet", 200, serde_json::json!({ "id": 7, "name": "Rex" }))]);
+
+        assert_eq!(document["openapi"], "3.0.3");
+        assert_eq!(document["info"], yaml("{ title: Pets, version: 1.0.0 }"));
+        assert_eq!(document["servers"], yaml("[{ url: 'https://api.test' }]"));
+        assert_eq!(document["components"]["securitySchemes"]["auth"], yaml("{ type: http, scheme: bearer }"));
+        let paths: Vec<_> = document["paths"].as_mapping().unwrap().keys().filter_map(YamlValue::as_str).collect();
+        assert_eq!(paths, ["/v1/pets/{id}", "/v1/pets"]);
+
+        let get = &document["paths"]["/v1/pets/{id}"]["get"];
+        assert_eq!(get["operationId"], "get_pet");
+        assert_eq!(get["summary"], "Get pet");
+        assert_eq!(get["tags"], yaml("[pets]"));
+        assert_eq!(
+            get["parameters"],
+            yaml(
+                "[{ name: id, in: path, required: true, schema: { type: string }, example: 7 },
+                  { name: verbose, in: query, schema: { type: boolean }, example: true },
+                  { name: X-Trace, in: header, schema: { type: string } }]"
+            )
+        );
+        assert_eq!(
+            get["responses"],
+            yaml(
+                "{ '200': { description: 执行时记录的响应, content: { application/json: {
+                    schema: { type: object, properties: { id: { type: integer }, name: { type: string } } },
+                    example: { id: 7, name: Rex } } } } }"
+            )
+        );
+
+        let create = &document["paths"]["/v1/pets"]["post"];
+        assert_eq!(
+            create["requestBody"]["content"]["application/json"]["schema"],
+            yaml("{ type: object, properties: { name: { type: string }, age: { type: integer } } }")
+        );
+        assert_eq!(create["responses"], yaml("'201': { description: OK }"));
+
+        assert_eq!(
+            report,
+            [
+                "步骤 'get_pet_again': GET /v1/pets/{id} 已由其他步骤导出",
+                "步骤 'next_pet': 路径中包含表达式占位符，未导出",
+            ]
+        );
+    }
+
+    #[test]
+    fn exported_document_imports_back() {
+        let (document, _) = export(&[]);
+        let output = import(&serde_yaml::to_string(&document).unwrap(), &OpenApiOptions::default());
+        let config = parse(&output.files[0]);
+
+        assert_eq!(config.global.profile.environments["default"].base_url, "https://api.test");
+        let get = &config.steps["get_pet"];
+        assert_eq!((get.method.as_str(), get.path.as_str()), ("GET", "/v1/pets/{id}"));
+        assert_eq!(get.variables["id"], 7);
+        assert_eq!(get.params["verbose"], true);
+        assert_eq!(get.asserts, ["status == 200"]);
+        assert_eq!(config.steps["create_pet"].body, Some(yaml("{ name: Rex, age: 3 }")));
+    }
+}
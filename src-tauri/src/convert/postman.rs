@@ -0,0 +1,1131 @@
+//! 导入 Postman v2.0 / v2.1 集合与环境
+//!
+//! 文件夹转换为测试用例，请求转换为步骤，集合变量写入 `variables`，
+//! 环境文件转换为 `global.profile` 下的一个环境。`{{var}}` 改写为 `{var}`，
+//! 作为 URL 前缀的变量（如 `{{baseUrl}}`）映射到环境的 `base_url`。
+//! 脚本只识别常见的状态码断言和保存响应字段，其余无法转换的内容记录在报告中。
+//!
+//! 也支持反向导出：步骤和当前环境导出为集合。
+
+use indexmap::IndexMap;
+use regex::{Captures, Regex};
+use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::config::{
+    EnvironmentConfig, GlobalConfig, ProfileConfig, SaveRule, Step, TestCase, TestConfig, HTTP_METHODS,
+};
+use crate::error::{Error, Result};
+
+use super::{items, lazy_regex, map_strings, snake_case, unique_id, GeneratedFile, ImportOutput};
+
+/// 没有环境文件时生成的环境名称
+const DEFAULT_ENVIRONMENT: &str = "default";
+
+/// 读取集合文件（及可选的环境文件）并转换为测试配置
+pub fn import_postman_files(collection: &Path, environment: Option<&Path>) -> Result<ImportOutput> {
+    let collection = read_json(collection, "Postman 集合")?;
+    let environment = environment.map(|path| read_json(path, "Postman 环境")).transpose()?;
+
+    import_postman(&collection, environment.as_ref())
+}
+
+fn read_json(path: &Path, what: &str) -> Result<JsonValue> {
+    let content = fs::read_to_string(path).map_err(|e| Error::io(format!("无法读取{}", what), path, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| Error::config_parse(format!("{}解析错误: {}", what, e), Some(path)))
+}
+
+/// 把解析后的集合（及环境）转换为测试配置
+pub fn import_postman(collection: &JsonValue, environment: Option<&JsonValue>) -> Result<ImportOutput> {
+    let schema = collection.pointer("/info/schema").and_then(JsonValue::as_str).unwrap_or_default();
+    if !schema.contains("collection/v2") {
+        return Err(Error::Validation("只支持 Postman v2.0 / v2.1 集合".to_string()));
+    }
+    if environment.is_some_and(|e| !e.get("values").is_some_and(JsonValue::is_array)) {
+        return Err(Error::Validation("不是 Postman 环境文件：缺少 values".to_string()));
+    }
+
+    let collection_name = collection.pointer("/info/name").and_then(JsonValue::as_str).unwrap_or("postman");
+    let mut converter = Converter::default();
+
+    for variable in items(collection.get("variable")) {
+        if let Some((key, value)) = key_value(variable) {
+            let value = converter.convert_text(&value, "集合变量");
+            converter.variables.insert(key, YamlValue::String(value));
+        }
+    }
+
+    converter.report_events(collection, "集合");
+    let children: Vec<JsonValue> = items(collection.get("item")).cloned().collect();
+    converter.walk(&children, &[], collection.get("auth"));
+
+    let mut environment_config = EnvironmentConfig {
+        base_url: String::new(),
+        context: String::new(),
+        brand: String::new(),
+        path_mapping: IndexMap::new(),
+        auth: None,
+        timeout: Default::default(),
+        retry: Default::default(),
+    };
+    let environment_name = environment
+        .and_then(|e| e.get("name"))
+        .and_then(JsonValue::as_str)
+        .map(snake_case)
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| DEFAULT_ENVIRONMENT.to_string());
+
+    // 环境中的值覆盖集合变量，与 Postman 的优先级一致
+    for value in items(environment.and_then(|e| e.get("values"))) {
+        let enabled = value.get("enabled").and_then(JsonValue::as_bool).unwrap_or(true);
+        let Some((key, text)) = key_value(value).filter(|_| enabled) else { continue };
+        let text = converter.convert_text(&text, "环境变量");
+        if converter.base_variable.as_deref() == Some(key.as_str()) {
+            environment_config.base_url = text;
+        } else {
+            converter.report.push(format!("环境变量 '{}' 无法放入环境配置，已写入 variables", key));
+            converter.variables.insert(key, YamlValue::String(text));
+        }
+    }
+
+    if environment_config.base_url.is_empty() {
+        let from_variable = converter
+            .base_variable
+            .as_ref()
+            .and_then(|name| converter.variables.get(name))
+            .and_then(YamlValue::as_str)
+            .map(str::to_string);
+        let first_host = converter.literal_hosts.first().map(|(_, host)| host.clone());
+        environment_config.base_url = from_variable.or(first_host).unwrap_or_else(|| {
+            converter.report.push("没有找到基础地址，base_url 暂设为 http://localhost".to_string());
+            "http://localhost".to_string()
+        });
+    }
+    converter.map_literal_hosts(&environment_config.base_url);
+    if let Some(name) = &converter.base_variable {
+        converter.variables.shift_remove(name);
+    }
+
+    let steps = converter.steps.len();
+    let config = TestConfig {
+        global: GlobalConfig {
+            profile: ProfileConfig {
+                active: environment_name.clone(),
+                environments: IndexMap::from([(environment_name, environment_config)]),
+            },
+            ..Default::default()
+        },
+        variables: converter.variables,
+        steps: converter.steps,
+        test_cases: converter.test_cases,
+        ..Default::default()
+    };
+
+    let name = Some(snake_case(collection_name)).filter(|s| !s.is_empty()).unwrap_or_else(|| "postman".to_string());
+    let content = format!("# 由 Postman 集合生成: {}\n{}", collection_name, super::config_to_yaml(&config)?);
+
+    Ok(ImportOutput {
+        files: vec![GeneratedFile { name: format!("{}_tests.yml", name), content }],
+        steps,
+        skipped: converter.report,
+    })
+}
+
+#[derive(Default)]
+struct Converter {
+    steps: IndexMap<String, Step>,
+    test_cases: IndexMap<String, TestCase>,
+    variables: IndexMap<String, YamlValue>,
+    /// 作为 URL 前缀的变量名
+    base_variable: Option<String>,
+    /// 使用字面主机地址的步骤及其主机（如 `https://host`），最后按 base_url 去掉路径前缀
+    literal_hosts: Vec<(String, String)>,
+    report: Vec<String>,
+}
+
+impl Converter {
+    /// 转换一层条目，返回其中直接包含的请求对应的步骤 id
+    fn walk(&mut self, children: &[JsonValue], folders: &[String], auth: Option<&JsonValue>) -> Vec<String> {
+        let mut step_ids = Vec::new();
+
+        for child in children {
+            let name = child.get("name").and_then(JsonValue::as_str).unwrap_or_default().to_string();
+            let mut path = folders.to_vec();
+            path.push(name.clone());
+            let label = path.join("/");
+            let auth = child.get("auth").or(auth);
+
+            if let Some(grandchildren) = child.get("item").and_then(JsonValue::as_array) {
+                self.report_events(child, &format!("文件夹 '{}'", label));
+
+                // 先占位，使父文件夹的用例排在子文件夹之前
+                let base = Some(snake_case(&label)).filter(|s| !s.is_empty()).unwrap_or_else(|| "case".to_string());
+                let mut id = base.clone();
+                let mut n = 2;
+                while self.test_cases.contains_key(&id) {
+                    id = format!("{}_{}", base, n);
+                    n += 1;
+                }
+                self.test_cases.insert(id.clone(), TestCase {
+                    name: path.join(" / "),
+                    description: description(child),
+                    ..Default::default()
+                });
+
+                let steps = self.walk(grandchildren, &path, auth);
+                if steps.is_empty() {
+                    self.test_cases.shift_remove(&id);
+                } else if let Some(case) = self.test_cases.get_mut(&id) {
+                    case.steps = steps;
+                }
+            } else if let Some(id) = self.request(child, &name, &label, auth) {
+                step_ids.push(id);
+            }
+        }
+
+        step_ids
+    }
+
+    /// 转换单个请求，不支持的请求记录原因并返回 None
+    fn request(&mut self, item: &JsonValue, name: &str, label: &str, auth: Option<&JsonValue>) -> Option<String> {
+        let context = format!("请求 '{}'", label);
+        let request = item.get("request")?;
+        // 请求可以简写为 URL 字符串
+        let simple = JsonValue::Null;
+        let (request, url) = match request {
+            JsonValue::String(_) => (&simple, Some(request)),
+            _ => (request, request.get("url")),
+        };
+        let auth = request.get("auth").or(auth);
+
+        let method = request.get("method").and_then(JsonValue::as_str).unwrap_or("GET").to_uppercase();
+        if !HTTP_METHODS.contains(&method.as_str()) {
+            self.report.push(format!("{}: 不支持的 HTTP 方法 {}", context, method));
+            return None;
+        }
+
+        let mut step = Step {
+            name: name.to_string(),
+            description: description(request),
+            method: method.clone(),
+            ..Default::default()
+        };
+
+        let host = self.url(url, &mut step, &context);
+
+        for header in items(request.get("header")) {
+            if disabled(header) {
+                continue;
+            }
+            if let Some((key, value)) = key_value(header) {
+                let value = self.convert_text(&value, &context);
+                step.headers.insert(key, value);
+            }
+        }
+
+        self.body(request.get("body"), &mut step, &context);
+        self.auth(auth, &mut step, &context);
+        self.scripts(item, &mut step, &context);
+
+        let base = Some(snake_case(name)).filter(|s| !s.is_empty());
+        let id = match base {
+            Some(base) => unique_id(&base, &self.steps),
+            None => super::step_id(&step.method, &step.path, &self.steps),
+        };
+        self.steps.insert(id.clone(), step);
+        if let Some(host) = host {
+            self.literal_hosts.push((id.clone(), host));
+        }
+        Some(id)
+    }
+
+    /// 拆分 URL：前缀变量或主机映射到 base_url，`:id` 路径变量改写为 `{id}`；返回字面主机地址
+    fn url(&mut self, url: Option<&JsonValue>, step: &mut Step, context: &str) -> Option<String> {
+        let (raw, query, path_variables) = match url {
+            Some(JsonValue::String(raw)) => (raw.clone(), None, None),
+            Some(url) => {
+                let raw = url.get("raw").and_then(JsonValue::as_str).map(str::to_string).unwrap_or_else(|| {
+                    let host = url.get("host").map(join_parts(".")).unwrap_or_default();
+                    let path = url.get("path").map(join_parts("/")).unwrap_or_default();
+                    format!("{}/{}", host, path)
+                });
+                (raw, url.get("query"), url.get("variable"))
+            }
+            None => (String::new(), None, None),
+        };
+
+        let raw = self.convert_text(&raw, context);
+        let (address, query_string) = match raw.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (raw.as_str(), None),
+        };
+
+        static PREFIX_VARIABLE: OnceLock<Regex> = OnceLock::new();
+        let mut host = None;
+        let rest = if let Some((origin, path)) = split_origin(address) {
+            host = Some(origin.to_string());
+            path.to_string()
+        } else if let Some(caps) = lazy_regex(&PREFIX_VARIABLE, r"^\{([^{}]+)\}").captures(address) {
+            let variable = caps[1].to_string();
+            match &self.base_variable {
+                None => self.base_variable = Some(variable),
+                Some(base) if *base != variable => {
+                    self.report.push(format!(
+                        "{}: 基础地址变量 {{{}}} 与 {{{}}} 不同，已映射到 base_url",
+                        context, variable, base
+                    ));
+                }
+                _ => {}
+            }
+            address[caps[0].len()..].to_string()
+        } else {
+            // 没有协议时第一段为主机
+            address.find('/').map(|i| address[i..].to_string()).unwrap_or_default()
+        };
+
+        let segments: Vec<String> = rest
+            .split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(name) if !name.is_empty() => format!("{{{}}}", name),
+                _ => segment.to_string(),
+            })
+            .collect();
+        step.path = segments.join("/");
+        if !step.path.starts_with('/') {
+            step.path.insert(0, '/');
+        }
+
+        for variable in items(path_variables) {
+            if let Some((key, value)) = key_value(variable).filter(|(_, v)| !v.is_empty()) {
+                let value = self.convert_text(&value, context);
+                step.variables.insert(key, YamlValue::String(value));
+            }
+        }
+
+        // 结构化的 query 包含禁用状态，优先使用
+        if let Some(query) = query.and_then(JsonValue::as_array) {
+            for param in query.iter().filter(|p| !disabled(p)) {
+                if let Some((key, value)) = key_value(param) {
+                    let value = self.convert_text(&value, context);
+                    step.params.insert(key, YamlValue::String(value));
+                }
+            }
+        } else if let Some(query_string) = query_string {
+            for pair in query_string.split('&').filter(|p| !p.is_empty()) {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                step.params.insert(key.to_string(), YamlValue::String(value.to_string()));
+            }
+        }
+
+        host
+    }
+
+    fn body(&mut self, body: Option<&JsonValue>, step: &mut Step, context: &str) {
+        let Some(body) = body.filter(|b| !disabled(b)) else { return };
+        let mode = body.get("mode").and_then(JsonValue::as_str).unwrap_or_default();
+
+        let value = match mode {
+            "raw" => {
+                let raw = body.get("raw").and_then(JsonValue::as_str).unwrap_or_default();
+                if raw.trim().is_empty() {
+                    return;
+                }
+                match parse_json_body(raw) {
+                    Some((value, quoted)) => {
+                        if quoted {
+                            self.report.push(format!("{}: 请求体中未加引号的变量已改为字符串", context));
+                        }
+                        self.convert_yaml(value, context)
+                    }
+                    None => {
+                        self.report.push(format!("{}: 请求体不是 JSON，按字符串发送", context));
+                        YamlValue::String(self.convert_text(raw, context))
+                    }
+                }
+            }
+            "graphql" => {
+                let graphql = body.get("graphql").cloned().unwrap_or(JsonValue::Null);
+                let query = graphql.get("query").and_then(JsonValue::as_str).unwrap_or_default();
+                let mut mapping = serde_yaml::Mapping::new();
+                mapping.insert("query".into(), YamlValue::String(self.convert_text(query, context)));
+                let variables = graphql.get("variables").and_then(JsonValue::as_str).and_then(parse_json_body);
+                if let Some((variables, _)) = variables {
+                    mapping.insert("variables".into(), self.convert_yaml(variables, context));
+                }
+                YamlValue::Mapping(mapping)
+            }
+            "" => return,
+            other => {
+                self.report.push(format!("{}: 不支持 {} 请求体，已忽略", context, other));
+                return;
+            }
+        };
+
+        if !matches!(step.method.as_str(), "POST" | "PUT") {
+            self.report.push(format!("{}: {} 请求不会发送请求体", context, step.method));
+        }
+        // 引擎发送 JSON 请求体时会自动设置 Content-Type
+        step.headers.retain(|k, v| !(k.eq_ignore_ascii_case("content-type") && v.contains("json")));
+        step.body = Some(value);
+    }
+
+    /// 转换认证：bearer 和 apikey 改写为请求头或查询参数，其他方式记录在报告中
+    fn auth(&mut self, auth: Option<&JsonValue>, step: &mut Step, context: &str) {
+        let Some(auth) = auth else { return };
+        let kind = auth.get("type").and_then(JsonValue::as_str).unwrap_or("noauth");
+
+        match kind {
+            "noauth" => {}
+            "bearer" => {
+                let token = auth_param(auth, "bearer", "token").unwrap_or_default();
+                let token = self.convert_text(&token, context);
+                step.headers.insert("Authorization".to_string(), format!("Bearer {}", token));
+            }
+            "apikey" => {
+                let key = auth_param(auth, "apikey", "key").unwrap_or_else(|| "X-API-Key".to_string());
+                let value = auth_param(auth, "apikey", "value").unwrap_or_default();
+                let value = self.convert_text(&value, context);
+                if auth_param(auth, "apikey", "in").as_deref() == Some("query") {
+                    step.params.insert(key, YamlValue::String(value));
+                } else {
+                    step.headers.insert(key, value);
+                }
+            }
+            other => self.report.push(format!(
+                "{}: 认证方式 {} 未转换，请改用环境中的 auth 配置",
+                context, other
+            )),
+        }
+    }
+
+    /// 识别测试脚本中的状态码断言和保存响应字段，其他脚本记录在报告中
+    fn scripts(&mut self, item: &JsonValue, step: &mut Step, context: &str) {
+        static STATUS: OnceLock<Regex> = OnceLock::new();
+        static ALIAS: OnceLock<Regex> = OnceLock::new();
+        static SET: OnceLock<Regex> = OnceLock::new();
+        static WRAPPER: OnceLock<Regex> = OnceLock::new();
+        let status = lazy_regex(&STATUS, r"^pm\.response\.to\.have\.status\((\d+)\);?$");
+        let alias = lazy_regex(&ALIAS, r"^(?:var|let|const)\s+(\w+)\s*=\s*pm\.response\.json\(\);?$");
+        let set = lazy_regex(
+            &SET,
+            r#"^pm\.(?:environment|collectionVariables|globals|variables)\.set\(\s*["']([\w.-]+)["']\s*,\s*(pm\.response\.json\(\)|\w+)\.([\w.]+)\s*\);?$"#,
+        );
+        let wrapper = lazy_regex(&WRAPPER, r"^(pm\.test\(.*(function\s*\(\)|=>)\s*\{|\}\);?)$");
+
+        for event in items(item.get("event")) {
+            let listen = event.get("listen").and_then(JsonValue::as_str).unwrap_or_default();
+            let lines = script_lines(event);
+            if lines.is_empty() {
+                continue;
+            }
+            if listen != "test" {
+                self.report.push(format!("{}: 预请求脚本未转换", context));
+                continue;
+            }
+
+            let mut aliases: Vec<String> = Vec::new();
+            let mut untranslated = 0;
+            for line in &lines {
+                if let Some(caps) = status.captures(line) {
+                    step.asserts.push(format!("status == {}", &caps[1]));
+                } else if let Some(caps) = alias.captures(line) {
+                    aliases.push(caps[1].to_string());
+                } else if let Some(caps) = set.captures(line).filter(|c| {
+                    &c[2] == "pm.response.json()" || aliases.iter().any(|a| a == &c[2])
+                }) {
+                    step.save_response.push(SaveRule { field: caps[3].to_string(), to: caps[1].to_string() });
+                } else if !wrapper.is_match(line) {
+                    untranslated += 1;
+                }
+            }
+
+            if untranslated > 0 {
+                self.report.push(format!("{}: 测试脚本有 {} 行未转换", context, untranslated));
+            }
+        }
+    }
+
+    /// 使用字面主机地址的请求：主机映射到 base_url，并去掉 base_url 中的路径前缀
+    fn map_literal_hosts(&mut self, base_url: &str) {
+        let (base_origin, base_path) = split_origin(base_url).unwrap_or(("", base_url));
+        let base_path = base_path.trim_end_matches('/');
+
+        for (id, host) in std::mem::take(&mut self.literal_hosts) {
+            let Some(step) = self.steps.get_mut(&id) else { continue };
+            if let Some(rest) = super::strip_path_prefix(&step.path, base_path) {
+                step.path = if rest.is_empty() { "/".to_string() } else { rest.to_string() };
+            }
+            if host != base_origin {
+                self.report.push(format!("步骤 '{}': 主机 {} 与 base_url 不同，已映射到 base_url", id, host));
+            }
+        }
+    }
+
+    /// 集合或文件夹级别的脚本无法对应到步骤
+    fn report_events(&mut self, item: &JsonValue, label: &str) {
+        if items(item.get("event")).any(|event| !script_lines(event).is_empty()) {
+            self.report.push(format!("{}: 脚本未转换", label));
+        }
+    }
+
+    /// `{{var}}` 改写为 `{var}`，`{{$timestamp}}` 对应内置变量，其他动态变量记录在报告中
+    fn convert_text(&mut self, text: &str, context: &str) -> String {
+        static VARIABLE: OnceLock<Regex> = OnceLock::new();
+        let re = lazy_regex(&VARIABLE, r"\{\{([^{}]+)\}\}");
+        let mut unsupported = Vec::new();
+        let converted = re.replace_all(text, |caps: &Captures| {
+            let name = caps[1].trim();
+            match name.strip_prefix('$') {
+                Some("timestamp") => "{timestamp}".to_string(),
+                Some(_) => {
+                    unsupported.push(name.to_string());
+                    caps[0].to_string()
+                }
+                None => format!("{{{}}}", name),
+            }
+        });
+        let converted = converted.to_string();
+
+        for name in unsupported {
+            self.report.push(format!("{}: 不支持动态变量 {}", context, name));
+        }
+        converted
+    }
+
+    fn convert_yaml(&mut self, value: YamlValue, context: &str) -> YamlValue {
+        map_strings(value, &mut |s| self.convert_text(s, context))
+    }
+}
+
+/// 导出的集合中基础地址变量的名称
+const BASE_URL_VARIABLE: &str = "baseUrl";
+
+/// 导出的集合中 token 变量的名称
+const TOKEN_VARIABLE: &str = "access_token";
+
+const COLLECTION_SCHEMA: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+/// 把配置中的步骤和当前环境导出为 Postman v2.1 集合，返回集合及无法转换的内容
+///
+/// 测试用例导出为文件夹，不属于任何用例的步骤放在集合顶层；
+/// 环境的 `base_url` 写入集合变量 `baseUrl`，状态码断言、验证规则和保存响应转换为测试脚本。
+pub fn export_postman(config: &TestConfig, env: &EnvironmentConfig, name: &str) -> (JsonValue, Vec<String>) {
+    let mut exporter = Exporter { env, report: Vec::new() };
+
+    let mut variables = vec![variable(BASE_URL_VARIABLE, &env.base_url)];
+    if !env.brand.is_empty() {
+        variables.push(variable("brand", &env.brand));
+    }
+    for (key, value) in &config.variables {
+        let value = exporter.convert_yaml(value.clone(), &format!("变量 '{}'", key));
+        let text = match value {
+            YamlValue::String(s) => s,
+            other => serde_json::to_string(&other).unwrap_or_default(),
+        };
+        variables.push(variable(key, &text));
+    }
+
+    let mut collection = serde_json::json!({
+        "info": { "name": name, "schema": COLLECTION_SCHEMA },
+        "item": [],
+    });
+
+    if let Some(auth) = exporter.auth() {
+        variables.push(variable(TOKEN_VARIABLE, ""));
+        collection["auth"] = auth;
+    }
+    collection["variable"] = JsonValue::Array(variables);
+
+    let mut items = Vec::new();
+    for (case_id, case) in &config.test_cases {
+        let requests: Vec<JsonValue> = case
+            .steps
+            .iter()
+            .filter_map(|id| config.steps.get(id).map(|step| exporter.request(id, step)))
+            .collect();
+        let mut folder = serde_json::json!({
+            "name": if case.name.is_empty() { case_id } else { &case.name },
+            "item": requests,
+        });
+        if !case.description.is_empty() {
+            folder["description"] = JsonValue::String(case.description.clone());
+        }
+        items.push(folder);
+    }
+    for (id, step) in &config.steps {
+        if !config.test_cases.values().any(|case| case.steps.contains(id)) {
+            items.push(exporter.request(id, step));
+        }
+    }
+    collection["item"] = JsonValue::Array(items);
+
+    (collection, exporter.report)
+}
+
+struct Exporter<'a> {
+    env: &'a EnvironmentConfig,
+    report: Vec<String>,
+}
+
+impl Exporter<'_> {
+    /// 认证配置转换为集合级认证，token 需要手动填入集合变量
+    fn auth(&mut self) -> Option<JsonValue> {
+        let (key, in_query) = super::auth_target(Some(self.env))?;
+        let auth = self.env.auth.as_ref()?;
+        self.report.push(format!(
+            "认证 token 需要从 {} 获取后填入集合变量 {}",
+            auth.token_url, TOKEN_VARIABLE
+        ));
+
+        let token = format!("{{{{{}}}}}", TOKEN_VARIABLE);
+        let prefix = if auth.auth_prefix.is_empty() { "Bearer" } else { auth.auth_prefix.as_str() };
+        let param = |k: &str, v: &str| serde_json::json!({ "key": k, "value": v, "type": "string" });
+
+        Some(if !in_query && key == "Authorization" && prefix == "Bearer" {
+            serde_json::json!({ "type": "bearer", "bearer": [param("token", &token)] })
+        } else {
+            let value = if in_query { token } else { format!("{} {}", prefix, token) };
+            let location = if in_query { "query" } else { "header" };
+            serde_json::json!({
+                "type": "apikey",
+                "apikey": [param("key", &key), param("value", &value), param("in", location)],
+            })
+        })
+    }
+
+    fn request(&mut self, id: &str, step: &Step) -> JsonValue {
+        let context = format!("步骤 '{}'", id);
+        let path = self.convert_text(&super::request_path(&step.path, self.env), &context);
+
+        let query: Vec<JsonValue> = step
+            .params
+            .iter()
+            .map(|(key, value)| {
+                let value = match self.convert_yaml(value.clone(), &context) {
+                    YamlValue::String(s) => s,
+                    other => serde_json::to_string(&other).unwrap_or_default(),
+                };
+                serde_json::json!({ "key": key, "value": value })
+            })
+            .collect();
+        let query_string: Vec<String> = query
+            .iter()
+            .map(|q| format!("{}={}", q["key"].as_str().unwrap_or_default(), q["value"].as_str().unwrap_or_default()))
+            .collect();
+
+        let mut raw = format!("{{{{{}}}}}{}", BASE_URL_VARIABLE, path);
+        if !query_string.is_empty() {
+            raw = format!("{}?{}", raw, query_string.join("&"));
+        }
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let mut url = serde_json::json!({
+            "raw": raw,
+            "host": [format!("{{{{{}}}}}", BASE_URL_VARIABLE)],
+            "path": segments,
+        });
+        if !query.is_empty() {
+            url["query"] = JsonValue::Array(query);
+        }
+
+        let headers: Vec<JsonValue> = step
+            .headers
+            .iter()
+            .map(|(key, value)| serde_json::json!({ "key": key, "value": self.convert_text(value, &context) }))
+            .collect();
+
+        let mut request = serde_json::json!({
+            "method": step.method.to_uppercase(),
+            "header": headers,
+            "url": url,
+        });
+        if !step.description.is_empty() {
+            request["description"] = JsonValue::String(step.description.clone());
+        }
+
+        if let Some(body) = &step.body {
+            let body = self.convert_yaml(body.clone(), &context);
+            // 直接序列化 YAML 值以保留字段顺序
+            let raw = serde_json::to_string_pretty(&body).unwrap_or_default();
+            request["body"] = serde_json::json!({
+                "mode": "raw",
+                "raw": raw,
+                "options": { "raw": { "language": "json" } },
+            });
+            if let JsonValue::Array(headers) = &mut request["header"] {
+                headers.push(serde_json::json!({ "key": "Content-Type", "value": "application/json" }));
+            }
+        }
+
+        if step.timeout != Default::default() || step.retry != Default::default() {
+            self.report.push(format!("{}: 超时和重试配置未导出", context));
+        }
+
+        let mut item = serde_json::json!({
+            "name": if step.name.is_empty() { id } else { &step.name },
+            "request": request,
+        });
+        let script = self.test_script(step, &context);
+        if !script.is_empty() {
+            item["event"] = serde_json::json!([{
+                "listen": "test",
+                "script": { "type": "text/javascript", "exec": script },
+            }]);
+        }
+        item
+    }
+
+    /// 状态码断言、验证规则、保存响应转换为测试脚本
+    fn test_script(&mut self, step: &Step, context: &str) -> Vec<String> {
+        static STATUS: OnceLock<Regex> = OnceLock::new();
+        let status = lazy_regex(&STATUS, r"^\s*status\s*==\s*(\d+)\s*$");
+        let mut lines = Vec::new();
+
+        for assertion in &step.asserts {
+            match status.captures(assertion) {
+                Some(caps) => lines.extend([
+                    format!("pm.test(\"status == {}\", function () {{", &caps[1]),
+                    format!("    pm.response.to.have.status({});", &caps[1]),
+                    "});".to_string(),
+                ]),
+                None => self.report.push(format!("{}: 断言 '{}' 未导出", context, assertion)),
+            }
+        }
+
+        let mut body_lines = Vec::new();
+        for validation in &step.validate {
+            let actual = js_path(&validation.field);
+            let expected = self.convert_yaml(validation.expected.clone(), context);
+            let expected = match &expected {
+                YamlValue::String(s) if s.contains("{{") => format!("pm.variables.replaceIn({:?})", s),
+                other => serde_json::to_string(other).unwrap_or_default(),
+            };
+            let check = match validation.operator.as_str() {
+                "equals" => format!("pm.expect({}).to.eql({});", actual, expected),
+                "exists" => format!("pm.expect({}).to.not.be.undefined;", actual),
+                "not_empty" => format!("pm.expect({}).to.not.be.empty;", actual),
+                "contains" => format!("pm.expect(String({})).to.include({});", actual, expected),
+                other => {
+                    self.report.push(format!("{}: 验证操作符 {} 未导出", context, other));
+                    continue;
+                }
+            };
+            body_lines.extend([
+                format!("pm.test({:?}, function () {{", format!("{} {}", validation.field, validation.operator)),
+                format!("    {}", check),
+                "});".to_string(),
+            ]);
+        }
+        for rule in &step.save_response {
+            body_lines.push(format!("pm.collectionVariables.set({:?}, {});", rule.to, js_path(&rule.field)));
+        }
+
+        if !body_lines.is_empty() {
+            lines.push("const json = pm.response.json();".to_string());
+            lines.extend(body_lines);
+        }
+        lines
+    }
+
+    /// `{var}` 改写为 `{{var}}`，`{timestamp}` 对应 `{{$timestamp}}`；表达式无法转换，保留原文
+    fn convert_text(&mut self, text: &str, context: &str) -> String {
+        static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+        let re = lazy_regex(&PLACEHOLDER, r"\{([^{}]+)\}");
+        let mut unsupported = Vec::new();
+        let converted = re
+            .replace_all(text, |caps: &Captures| {
+                let name = caps[1].trim();
+                let is_path = name.split('.').all(|p| !p.is_empty() && p.chars().all(|c| c.is_alphanumeric() || c == '_'));
+                match name {
+                    "timestamp" => "{{$timestamp}}".to_string(),
+                    _ if is_path => format!("{{{{{}}}}}", name),
+                    _ => {
+                        unsupported.push(caps[0].to_string());
+                        caps[0].to_string()
+                    }
+                }
+            })
+            .to_string();
+
+        for placeholder in unsupported {
+            self.report.push(format!("{}: 表达式占位符 {} 无法转换", context, placeholder));
+        }
+        converted
+    }
+
+    fn convert_yaml(&mut self, value: YamlValue, context: &str) -> YamlValue {
+        map_strings(value, &mut |s| self.convert_text(s, context))
+    }
+}
+
+fn variable(key: &str, value: &str) -> JsonValue {
+    serde_json::json!({ "key": key, "value": value, "type": "string" })
+}
+
+/// 响应字段路径转换为 JS 表达式，如 `data.id` -> `json.data.id`
+fn js_path(field: &str) -> String {
+    static IDENTIFIER: OnceLock<Regex> = OnceLock::new();
+    let identifier = lazy_regex(&IDENTIFIER, r"^[A-Za-z_$][\w$]*$");
+    field.split('.').fold("json".to_string(), |acc, part| {
+        if identifier.is_match(part) {
+            format!("{}.{}", acc, part)
+        } else {
+            format!("{}[{:?}]", acc, part)
+        }
+    })
+}
+
+/// 解析 JSON 请求体，未加引号的 `{{var}}` 先加上引号；返回值及是否加过引号
+fn parse_json_body(raw: &str) -> Option<(YamlValue, bool)> {
+    static STRINGS_OR_VARIABLES: OnceLock<Regex> = OnceLock::new();
+    let strings_or_variables = lazy_regex(&STRINGS_OR_VARIABLES, r#""(?:[^"\\]|\\.)*"|\{\{[^{}]+\}\}"#);
+    let mut quoted = false;
+    let text = strings_or_variables.replace_all(raw, |caps: &Captures| {
+        if caps[0].starts_with('"') {
+            caps[0].to_string()
+        } else {
+            quoted = true;
+            format!("\"{}\"", &caps[0])
+        }
+    });
+
+    // 先按 JSON 校验，再用 serde_yaml 解析以保留字段顺序
+    serde_json::from_str::<JsonValue>(&text).ok()?;
+    serde_yaml::from_str(&text).ok().map(|value| (value, quoted))
+}
+
+/// 拆分 `scheme://host` 与其后的路径，不是绝对地址时返回 None
+fn split_origin(url: &str) -> Option<(&str, &str)> {
+    let rest = url.strip_prefix("http://").or_else(|| url.strip_prefix("https://"))?;
+    let end = url.len() - rest.len() + rest.find('/').unwrap_or(rest.len());
+    Some(url.split_at(end))
+}
+
+fn key_value(item: &JsonValue) -> Option<(String, String)> {
+    let key = item.get("key").and_then(JsonValue::as_str)?;
+    let value = match item.get("value") {
+        Some(JsonValue::String(s)) => s.clone(),
+        Some(JsonValue::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    };
+    Some((key.to_string(), value))
+}
+
+fn disabled(item: &JsonValue) -> bool {
+    item.get("disabled").and_then(JsonValue::as_bool).unwrap_or(false)
+}
+
+fn description(item: &JsonValue) -> String {
+    match item.get("description") {
+        Some(JsonValue::String(s)) => s.clone(),
+        Some(other) => other.get("content").and_then(JsonValue::as_str).unwrap_or_default().to_string(),
+        None => String::new(),
+    }
+}
+
+/// 认证参数：v2.1 为 `[{ key, value }]` 数组，v2.0 为对象
+fn auth_param(auth: &JsonValue, kind: &str, key: &str) -> Option<String> {
+    let params = auth.get(kind)?;
+    let value = match params {
+        JsonValue::Array(list) => list.iter().find(|p| p.get("key").and_then(JsonValue::as_str) == Some(key))?.get("value")?,
+        other => other.get(key)?,
+    };
+    value.as_str().map(str::to_string)
+}
+
+/// 脚本的非空、非注释行
+fn script_lines(event: &JsonValue) -> Vec<String> {
+    let lines: Vec<String> = match event.pointer("/script/exec") {
+        Some(JsonValue::Array(lines)) => lines.iter().filter_map(JsonValue::as_str).map(str::to_string).collect(),
+        Some(JsonValue::String(script)) => script.lines().map(str::to_string).collect(),
+        _ => Vec::new(),
+    };
+    lines
+        .iter()
+        .flat_map(|line| line.lines())
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 把 host / path 数组拼接为字符串
+fn join_parts(separator: &'static str) -> impl Fn(&JsonValue) -> String {
+    move |value| match value {
+        JsonValue::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p.as_str().or_else(|| p.get("value").and_then(JsonValue::as_str)))
+            .collect::<Vec<_>>()
+            .join(separator),
+        JsonValue::String(s) => s.clone(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn collection() -> JsonValue {
+        json!({
+            "info": {
+                "name": "User API",
+                "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+            },
+            "variable": [{ "key": "baseUrl", "value": "https://api.test/v1" }, { "key": "age", "value": "30" }],
+            "auth": { "type": "bearer", "bearer": [{ "key": "token", "value": "{{token}}" }] },
+            "item": [
+                {
+                    "name": "Users",
+                    "item": [
+                        {
+                            "name": "Create User",
+                            "event": [{
+                                "listen": "test",
+                                "script": { "exec": [
+                                    "pm.test(\"created\", function () {",
+                                    "    pm.response.to.have.status(201);",
+                                    "});",
+                                    "var data = pm.response.json();",
+                                    "pm.environment.set(\"userId\", data.id);",
+                                    "console.log(data);"
+                                ] }
+                            }],
+                            "request": {
+                                "method": "POST",
+                                "header": [
+                                    { "key": "Content-Type", "value": "application/json" },
+                                    { "key": "X-Debug", "value": "1", "disabled": true }
+                                ],
+                                "body": { "mode": "raw", "raw": "{\"name\": \"{{name}}\", \"age\": {{age}}}" },
+                                "url": "{{baseUrl}}/users"
+                            }
+                        },
+                        {
+                            "name": "Get User",
+                            "request": {
+                                "method": "GET",
+                                "auth": { "type": "apikey", "apikey": [
+                                    { "key": "key", "value": "api_key" },
+                                    { "key": "value", "value": "{{apiKey}}" },
+                                    { "key": "in", "value": "query" }
+                                ] },
+                                "url": {
+                                    "raw": "{{baseUrl}}/users/:id?verbose=1&ts={{$timestamp}}",
+                                    "query": [
+                                        { "key": "verbose", "value": "1" },
+                                        { "key": "ts", "value": "{{$timestamp}}" },
+                                        { "key": "skip", "value": "1", "disabled": true }
+                                    ],
+                                    "variable": [{ "key": "id", "value": "{{userId}}" }]
+                                }
+                            }
+                        }
+                    ]
+                },
+                { "name": "Patch User", "request": { "method": "PATCH", "url": "{{baseUrl}}/users/1" } },
+                { "name": "Health", "request": "https://api.test/v1/health?uid={{$guid}}" }
+            ]
+        })
+    }
+
+    fn import(environment: Option<&JsonValue>) -> (TestConfig, ImportOutput) {
+        let output = import_postman(&collection(), environment).unwrap();
+        let config = serde_yaml::from_str(&output.files[0].content).unwrap();
+        (config, output)
+    }
+
+    #[test]
+    fn imports_collection() {
+        let (config, output) = import(None);
+
+        assert_eq!(output.files[0].name, "user_api_tests.yml");
+        assert!(output.files[0].content.starts_with("# 由 Postman 集合生成: User API\n"));
+        assert_eq!(output.steps, 3);
+        let env = &config.global.profile.environments["default"];
+        assert_eq!(config.global.profile.active, "default");
+        assert_eq!(env.base_url, "https://api.test/v1");
+        assert_eq!(config.variables.keys().collect::<Vec<_>>(), ["age"]);
+
+        assert_eq!(config.test_cases["users"].name, "Users");
+        assert_eq!(config.test_cases["users"].steps, ["create_user", "get_user"]);
+
+        let create = &config.steps["create_user"];
+        assert_eq!((create.method.as_str(), create.path.as_str()), ("POST", "/users"));
+        assert_eq!(create.headers, IndexMap::from([("Authorization".to_string(), "Bearer {token}".to_string())]));
+        assert_eq!(create.body, Some(serde_yaml::from_str("{name: '{name}', age: '{age}'}").unwrap()));
+        assert_eq!(create.asserts, ["status == 201"]);
+        assert_eq!(create.save_response[0].field, "id");
+        assert_eq!(create.save_response[0].to, "userId");
+
+        let get = &config.steps["get_user"];
+        assert_eq!(get.path, "/users/{id}");
+        assert_eq!(get.variables["id"], "{userId}");
+        let params: Vec<_> = get.params.iter().map(|(k, v)| (k.as_str(), v.as_str().unwrap())).collect();
+        assert_eq!(params, [("verbose", "1"), ("ts", "{timestamp}"), ("api_key", "{apiKey}")]);
+        assert!(get.headers.is_empty());
+
+        // 字面主机与 base_url 相同，去掉路径前缀
+        assert_eq!(config.steps["health"].path, "/health");
+    }
+
+    #[test]
+    fn reports_untranslated_content() {
+        let (_, output) = import(None);
+        assert_eq!(
+            output.skipped,
+            [
+                "请求 'Users/Create User': 请求体中未加引号的变量已改为字符串",
+                "请求 'Users/Create User': 测试脚本有 1 行未转换",
+                "请求 'Patch User': 不支持的 HTTP 方法 PATCH",
+                "请求 'Health': 不支持动态变量 $guid",
+            ]
+        );
+    }
+
+    #[test]
+    fn environment_overrides_collection_variables() {
+        let environment = json!({
+            "name": "Staging Env",
+            "values": [
+                { "key": "baseUrl", "value": "https://staging.test/v1" },
+                { "key": "age", "value": "40" },
+                { "key": "unused", "value": "x", "enabled": false }
+            ]
+        });
+        let (config, output) = import(Some(&environment));
+
+        assert_eq!(config.global.profile.active, "staging_env");
+        assert_eq!(config.global.profile.environments["staging_env"].base_url, "https://staging.test/v1");
+        assert_eq!(config.variables["age"], "40");
+        assert!(!config.variables.contains_key("unused"));
+        assert!(output.skipped.contains(&"环境变量 'age' 无法放入环境配置，已写入 variables".to_string()));
+        assert!(output.skipped.contains(&"步骤 'health': 主机 https://api.test 与 base_url 不同，已映射到 base_url".to_string()));
+    }
+
+    #[test]
+    fn rejects_unsupported_files() {
+        let v1 = json!({ "info": { "schema": "https://schema.getpostman.com/json/collection/v1.0.0/collection.json" } });
+        assert!(import_postman(&v1, None).unwrap_err().to_string().contains("只支持 Postman v2.0 / v2.1 集合"));
+        let environment = json!({ "name": "dev" });
+        let message = import_postman(&collection(), Some(&environment)).unwrap_err().to_string();
+        assert!(message.contains("缺少 values"));
+    }
+
+    const CONFIG: &str = r#"
+global:
+  profile:
+    active: dev
+    dev:
+      base_url: https://api.test
+      context: /v1
+      auth:
+        token_url: /oauth/token
+variables:
+  limit: 10
+steps:
+  list_users:
+    name: List Users
+    method: GET
+    path: /users
+    params:
+      limit: "{limit}"
+      since: "{timestamp}"
+    assert:
+      - status == 200
+      - body.total > 0
+    validate:
+      - field: data.0.name
+        operator: equals
+        expected: "{name}"
+    save_response:
+      - field: data.0.id
+        to: user_id
+  create_user:
+    method: POST
+    path: /users
+    body:
+      name: "{name | upper}"
+  ping:
+    method: GET
+    path: /ping
+test_cases:
+  users:
+    name: User Flow
+    steps: [list_users, create_user]
+"#;
+
+    fn export() -> (JsonValue, Vec<String>) {
+        let config: TestConfig = serde_yaml::from_str(CONFIG).unwrap();
+        let env = config.global.profile.environments["dev"].clone();
+        export_postman(&config, &env, "Users")
+    }
+
+    #[test]
+    fn exports_collection() {
+        let (collection, _) = export();
+
+        assert_eq!(collection["info"], json!({ "name": "Users", "schema": COLLECTION_SCHEMA }));
+        let variables: Vec<_> = items(collection.get("variable")).map(|v| (v["key"].clone(), v["value"].clone())).collect();
+        assert_eq!(variables, [(json!("baseUrl"), json!("https://api.test")), (json!("limit"), json!("10")), (json!("access_token"), json!(""))]);
+        assert_eq!(collection["auth"]["type"], "bearer");
+        assert_eq!(collection["auth"]["bearer"][0]["value"], "{{access_token}}");
+
+        let folder = &collection["item"][0];
+        assert_eq!(folder["name"], "User Flow");
+        let list = &folder["item"][0];
+        assert_eq!(list["name"], "List Users");
+        assert_eq!(list["request"]["url"]["raw"], "{{baseUrl}}/v1/users?limit={{limit}}&since={{$timestamp}}");
+        assert_eq!(list["request"]["url"]["path"], json!(["v1", "users"]));
+        assert_eq!(
+            list["event"][0]["script"]["exec"],
+            json!([
+                "pm.test(\"status == 200\", function () {",
+                "    pm.response.to.have.status(200);",
+                "});",
+                "const json = pm.response.json();",
+                "pm.test(\"data.0.name equals\", function () {",
+                "    pm.expect(json.data[\"0\"].name).to.eql(pm.variables.replaceIn(\"{{name}}\"));",
+                "});",
+                "pm.collectionVariables.set(\"user_id\", json.data[\"0\"].id);",
+            ])
+        );
+
+        let create = &folder["item"][1]["request"];
+        assert_eq!(create["body"]["raw"], "{\n  \"name\": \"{name | upper}\"\n}");
+        assert_eq!(create["header"], json!([{ "key": "Content-Type", "value": "application/json" }]));
+        // 不属于任何用例的步骤放在顶层
+        assert_eq!(collection["item"][1]["name"], "ping");
+    }
+
+    #[test]
+    fn export_reports_untranslated_content() {
+        let (_, report) = export();
+        assert_eq!(
+            report,
+            [
+                "认证 token 需要从 /oauth/token 获取后填入集合变量 access_token",
+                "步骤 'list_users': 断言 'body.total > 0' 未导出",
+                "步骤 'create_user': 表达式占位符 {name | upper} 无法转换",
+            ]
+        );
+    }
+
+    #[test]
+    fn exported_collection_imports_back() {
+        let (collection, _) = export();
+        let output = import_postman(&collection, None).unwrap();
+        let config: TestConfig = serde_yaml::from_str(&output.files[0].content).unwrap();
+
+        assert_eq!(config.global.profile.environments["default"].base_url, "https://api.test");
+        assert_eq!(config.test_cases["user_flow"].steps, ["list_users", "create_user"]);
+        assert_eq!(config.steps["list_users"].path, "/v1/users");
+        assert_eq!(config.steps["list_users"].asserts, ["status == 200"]);
+        assert_eq!(config.steps["ping"].method, "GET");
+    }
+}
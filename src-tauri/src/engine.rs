@@ -1,14 +1,25 @@
 //! 测试引擎核心实现
 
+use indexmap::IndexMap;
 use regex::Regex;
+use reqwest::Method;
 use serde_json::Value as JsonValue;
 use serde_yaml::Value as YamlValue;
 use std::collections::HashMap;
-use std::fs;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Instant;
+use tokio_util::sync::CancellationToken;
 
+use crate::cache::Cache;
 use crate::config::*;
-use crate::http_client::HttpClient;
+use crate::error::{Error, Result};
+use crate::events::{ExecutionEvent, ExecutionObserver};
+use crate::expr;
+use crate::http_client::{AuthPlacement, HttpClient, HttpError};
+use crate::loader;
+use crate::secrets::Secrets;
+use crate::tags::TagFilter;
 
 pub struct TestEngine {
     config: TestConfig,
@@ -16,28 +27,44 @@ pub struct TestEngine {
     client: HttpClient,
     context: HashMap<String, JsonValue>,
     debug: bool,
-    path_mapping: HashMap<String, String>,
+    path_mapping: IndexMap<String, String>,
+    cancel_token: CancellationToken,
+    observer: Option<Arc<dyn ExecutionObserver>>,
+    /// 正在执行的测试用例，用于标记事件归属
+    current_case: Option<String>,
+    /// 解析 `{secret:名称}` 的密钥，结果和事件中出现的密钥值会被脱敏
+    secrets: Arc<Secrets>,
 }
 
 impl TestEngine {
     /// 从配置文件创建引擎
-    pub fn from_file(config_path: &str) -> Result<Self, String> {
-        let content = fs::read_to_string(config_path)
-            .map_err(|e| format!("无法读取配置文件: {}", e))?;
-        
-        Self::from_yaml(&content)
+    pub fn from_file(config_path: &str) -> Result<Self> {
+        let config = loader::load_config_file(Path::new(config_path))?;
+
+        Self::new(config)
     }
 
-    /// 从 YAML 字符串创建引擎
-    pub fn from_yaml(yaml_content: &str) -> Result<Self, String> {
-        let config: TestConfig = serde_yaml::from_str(yaml_content)
-            .map_err(|e| format!("YAML 解析错误: {}", e))?;
-        
+    /// 从 YAML 字符串创建引擎（imports 相对当前目录解析）
+    pub fn from_yaml(yaml_content: &str) -> Result<Self> {
+        let config = loader::load_config_str(yaml_content, None)?;
+
+        Self::new(config)
+    }
+
+    /// 从 YAML 字符串创建引擎，imports 相对 `config_path` 所在目录解析
+    pub fn from_yaml_at(yaml_content: &str, config_path: &str) -> Result<Self> {
+        let config = loader::load_config_str(yaml_content, Some(Path::new(config_path)))?;
+
         Self::new(config)
     }
 
     /// 从配置创建引擎
-    pub fn new(config: TestConfig) -> Result<Self, String> {
+    pub fn new(config: TestConfig) -> Result<Self> {
+        Self::with_token_cache(config, Arc::new(Cache::new()))
+    }
+
+    /// 从配置创建引擎，使用共享的 token 缓存
+    pub fn with_token_cache(config: TestConfig, token_cache: Arc<Cache>) -> Result<Self> {
         let debug = config.global.debug;
         
         // 解析环境配置
@@ -45,11 +72,14 @@ impl TestEngine {
         let path_mapping = env_config.path_mapping.clone();
         
         // 创建 HTTP 客户端
-        let client = HttpClient::new(
+        // 超时：全局 < 环境，step 级别的覆盖在请求时生效
+        let client = HttpClient::with_cache(
             env_config.base_url.clone(),
             env_config.auth.clone(),
+            config.global.timeout.merge(&env_config.timeout),
             debug,
-        );
+            token_cache,
+        )?;
         
         // 初始化上下文
         let mut context = HashMap::new();
@@ -65,32 +95,82 @@ impl TestEngine {
             context,
             debug,
             path_mapping,
+            cancel_token: CancellationToken::new(),
+            observer: None,
+            current_case: None,
+            secrets: Arc::new(Secrets::default()),
         })
     }
 
+    /// 设置取消令牌，令牌取消后正在进行的请求和重试等待立即中止
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancel_token = token;
+    }
+
+    /// 设置执行事件观察者
+    pub fn set_observer(&mut self, observer: Arc<dyn ExecutionObserver>) {
+        self.observer = Some(observer);
+    }
+
+    /// 设置密钥库中的密钥，用于替换 `{secret:名称}` 引用
+    pub fn set_secrets(&mut self, secrets: Arc<Secrets>) {
+        self.client.set_secrets(secrets.clone());
+        self.secrets = secrets;
+    }
+
+    fn emit(&self, event: ExecutionEvent) {
+        if let Some(observer) = &self.observer {
+            observer.on_event(&event);
+        }
+    }
+
     /// 解析环境配置
-    fn resolve_env_config(config: &TestConfig) -> Result<EnvironmentConfig, String> {
+    pub(crate) fn resolve_env_config(config: &TestConfig) -> Result<EnvironmentConfig> {
         let active = &config.global.profile.active;
         
         if active.is_empty() {
-            return Err("未配置 profile.active".to_string());
+            return Err(Error::config_parse("未配置 profile.active", None));
         }
         
         config.global.profile.environments
             .get(active)
             .cloned()
-            .ok_or_else(|| format!("环境 '{}' 配置不存在", active))
+            .ok_or_else(|| Error::MissingEnvironment(active.clone()))
+    }
+
+    /// 加载并合并 imports 后的配置
+    pub fn config(&self) -> &TestConfig {
+        &self.config
+    }
+
+    /// 当前环境名（`profile.active`）
+    pub fn active_profile(&self) -> &str {
+        &self.config.global.profile.active
     }
 
-    /// 获取 step 列表
+    /// 获取 step 列表（按 YAML 中的书写顺序）
     pub fn get_step_list(&self) -> Vec<StepInfo> {
         self.config.steps
             .iter()
-            .map(|(id, step)| StepInfo {
-                id: id.clone(),
-                name: if step.name.is_empty() { id.clone() } else { step.name.clone() },
-                method: step.method.clone(),
-                path: step.path.clone(),
+            .map(|(id, step)| {
+                let source = self.config.step_sources.get(id).cloned().unwrap_or_default();
+                let test_cases = self.config.test_cases
+                    .iter()
+                    .filter(|(_, case)| case.steps.contains(id))
+                    .map(|(case_id, _)| case_id.clone())
+                    .collect();
+
+                StepInfo {
+                    id: id.clone(),
+                    name: if step.name.is_empty() { id.clone() } else { step.name.clone() },
+                    method: step.method.clone(),
+                    path: step.path.clone(),
+                    description: step.description.clone(),
+                    tags: step.tags.clone(),
+                    source: source.file,
+                    line: source.line,
+                    test_cases,
+                }
             })
             .collect()
     }
@@ -100,6 +180,28 @@ impl TestEngine {
         &mut self,
         step_name: &str,
         external_variables: Option<HashMap<String, JsonValue>>,
+    ) -> ExecutionResult {
+        self.emit(ExecutionEvent::StepStarted {
+            case_id: self.current_case.clone(),
+            step_name: step_name.to_string(),
+        });
+
+        let mut result = self.run_step(step_name, external_variables).await;
+        self.secrets.mask_result(&mut result);
+
+        self.emit(ExecutionEvent::StepFinished {
+            case_id: self.current_case.clone(),
+            step_name: step_name.to_string(),
+            status: result.status,
+            duration_ms: result.duration_ms,
+        });
+        result
+    }
+
+    async fn run_step(
+        &mut self,
+        step_name: &str,
+        external_variables: Option<HashMap<String, JsonValue>>,
     ) -> ExecutionResult {
         let start = Instant::now();
         
@@ -107,23 +209,222 @@ impl TestEngine {
         let step = match self.config.steps.get(step_name) {
             Some(s) => s.clone(),
             None => {
+                let error = Error::MissingStep(step_name.to_string());
                 return ExecutionResult {
                     success: false,
+                    status: ExecutionStatus::Error,
                     step_name: step_name.to_string(),
                     request_url: String::new(),
                     request_method: String::new(),
-                    request_headers: HashMap::new(),
+                    request_headers: IndexMap::new(),
                     request_body: None,
                     response_status: 0,
-                    response_headers: HashMap::new(),
+                    response_headers: IndexMap::new(),
                     response_body: None,
                     validations: vec![],
                     duration_ms: start.elapsed().as_millis() as u64,
-                    error: Some(format!("步骤 '{}' 不存在", step_name)),
+                    error_kind: error.kind(),
+                    error: Some(error.to_string()),
+                    attempts: vec![],
                 };
             }
         };
 
+        let PreparedRequest {
+            variables: merged_vars,
+            method,
+            path,
+            url: request_url,
+            params: params_str,
+            headers,
+            body,
+        } = self.prepare_request(&step, external_variables);
+
+        if self.debug {
+            println!("📤 请求: {} {}", method, self.secrets.mask(&request_url));
+            if !params_str.is_empty() {
+                println!("   参数: {}", self.secrets.mask(&format!("{:?}", params_str)));
+            }
+            if let Some(ref b) = body {
+                println!("   请求体: {}", serde_json::to_string_pretty(&self.secrets.mask_json(b)).unwrap_or_default());
+            }
+        }
+
+        // 发送请求（GET / DELETE 不带请求体）
+        let http_method = match method.as_str() {
+            "GET" => Some((Method::GET, None)),
+            "POST" => Some((Method::POST, body.as_ref())),
+            "PUT" => Some((Method::PUT, body.as_ref())),
+            "DELETE" => Some((Method::DELETE, None)),
+            _ => None,
+        };
+        let retry = self.env_config.retry.merge(&step.retry);
+        let mut attempts = Vec::new();
+
+        // None 表示执行被取消
+        let result = match http_method {
+            Some(_) if self.cancel_token.is_cancelled() => None,
+            Some((http_method, request_body)) => loop {
+                let attempt = attempts.len() as u32 + 1;
+                self.emit(ExecutionEvent::RequestSent {
+                    case_id: self.current_case.clone(),
+                    step_name: step_name.to_string(),
+                    attempt,
+                    method: method.clone(),
+                    url: self.secrets.mask(&request_url),
+                });
+
+                let attempt_start = Instant::now();
+                let result = tokio::select! {
+                    _ = self.cancel_token.cancelled() => break None,
+                    result = self.client.request(
+                        http_method.clone(),
+                        &path,
+                        request_body,
+                        Some(&params_str),
+                        Some(&headers),
+                        &step.timeout,
+                    ) => result,
+                };
+
+                let (status, error, retryable) = match &result {
+                    Ok((status, ..)) => (status.as_u16(), None, retry.retries_status(status.as_u16())),
+                    Err(e) => (0, Some(e), retry.retries_error(e.kind)),
+                };
+                let record = AttemptRecord {
+                    attempt,
+                    status,
+                    duration_ms: attempt_start.elapsed().as_millis() as u64,
+                    error: error.map(|e| e.message.clone()),
+                    error_kind: error.map(|e| e.kind),
+                };
+                self.emit(ExecutionEvent::ResponseReceived {
+                    case_id: self.current_case.clone(),
+                    step_name: step_name.to_string(),
+                    attempt,
+                    status,
+                    duration_ms: record.duration_ms,
+                    error: record.error.as_deref().map(|e| self.secrets.mask(e)),
+                });
+                attempts.push(record);
+
+                if !retryable || !retry.allows_method(&method) || attempt >= retry.max_attempts() {
+                    break Some(result);
+                }
+
+                let delay = retry.backoff(attempt);
+                if self.debug {
+                    println!("🔁 第 {} 次请求失败，{}ms 后重试", attempt, delay.as_millis());
+                }
+                tokio::select! {
+                    _ = self.cancel_token.cancelled() => break None,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            },
+            None => Some(Err(HttpError::new(
+                ErrorKind::InvalidRequest,
+                format!("不支持的 HTTP 方法: {}", method),
+            ))),
+        };
+
+        match result {
+            Some(Ok((status, actual_request_headers, response_headers, response_body))) => {
+                // 验证响应
+                let mut validations = self.validate_response(&response_body, &step.validate);
+                validations.extend(self.evaluate_asserts(
+                    &step.asserts,
+                    &merged_vars,
+                    status.as_u16(),
+                    &response_headers,
+                    &response_body,
+                ));
+                for validation in &validations {
+                    self.emit(ExecutionEvent::ValidationResult {
+                        case_id: self.current_case.clone(),
+                        step_name: step_name.to_string(),
+                        result: ValidationResult {
+                            expected: self.secrets.mask(&validation.expected),
+                            actual: self.secrets.mask(&validation.actual),
+                            ..validation.clone()
+                        },
+                    });
+                }
+                let all_passed = validations.iter().all(|v| v.passed);
+
+                // 保存响应数据
+                for rule in &step.save_response {
+                    if let Some(value) = get_json_field(&response_body, &rule.field) {
+                        self.context.insert(rule.to.clone(), value);
+                    }
+                }
+
+                let success = all_passed && status.is_success();
+                ExecutionResult {
+                    success,
+                    status: if success { ExecutionStatus::Passed } else { ExecutionStatus::Failed },
+                    step_name: step_name.to_string(),
+                    request_url,
+                    request_method: method,
+                    request_headers: actual_request_headers,
+                    request_body: body,
+                    response_status: status.as_u16(),
+                    response_headers,
+                    response_body: Some(response_body),
+                    validations,
+                    duration_ms: start.elapsed().as_millis() as u64,
+                    error: None,
+                    error_kind: None,
+                    attempts,
+                }
+            }
+            Some(Err(e)) => ExecutionResult {
+                success: false,
+                status: ExecutionStatus::Error,
+                step_name: step_name.to_string(),
+                request_url,
+                request_method: method,
+                request_headers: headers,
+                request_body: body,
+                response_status: 0,
+                response_headers: IndexMap::new(),
+                response_body: None,
+                validations: vec![],
+                duration_ms: start.elapsed().as_millis() as u64,
+                error: Some(e.message),
+                error_kind: Some(e.kind),
+                attempts,
+            },
+            None => {
+                if self.debug {
+                    println!("⏹ 执行已取消: {}", step_name);
+                }
+                ExecutionResult {
+                    success: false,
+                    status: ExecutionStatus::Cancelled,
+                    step_name: step_name.to_string(),
+                    request_url,
+                    request_method: method,
+                    request_headers: headers,
+                    request_body: body,
+                    response_status: 0,
+                    response_headers: IndexMap::new(),
+                    response_body: None,
+                    validations: vec![],
+                    duration_ms: start.elapsed().as_millis() as u64,
+                    error: None,
+                    error_kind: None,
+                    attempts,
+                }
+            }
+        }
+    }
+
+    /// 合并变量并替换路径、参数、请求头、请求体中的占位符
+    fn prepare_request(
+        &self,
+        step: &Step,
+        external_variables: Option<HashMap<String, JsonValue>>,
+    ) -> PreparedRequest {
         // 合并变量：全局 < step级别 < 外部传入
         let mut merged_vars = self.context.clone();
         
@@ -143,14 +444,12 @@ impl TestEngine {
         }
 
         // 处理路径
-        let raw_path = &step.path;
-        let full_path = format!("{}{}", self.env_config.context, raw_path);
+        let full_path = format!("{}{}", self.env_config.context, step.path);
         let full_path = self.replace_variables(&full_path, &merged_vars);
         let path = self.transform_path(&full_path);
 
         // 处理请求参数
-        let params = self.replace_map_variables(&step.params, &merged_vars);
-        let params_str: HashMap<String, String> = params
+        let params = self.replace_map_variables(&step.params, &merged_vars)
             .iter()
             .map(|(k, v)| (k.clone(), json_value_to_string(v)))
             .collect();
@@ -162,79 +461,241 @@ impl TestEngine {
         });
 
         // 处理请求头
-        let headers: HashMap<String, String> = step.headers
+        let headers = step.headers
             .iter()
             .map(|(k, v)| (k.clone(), self.replace_variables(v, &merged_vars)))
             .collect();
 
-        let request_url = format!("{}{}", self.env_config.base_url, path);
-        let method = step.method.to_uppercase();
+        PreparedRequest {
+            method: step.method.to_uppercase(),
+            url: format!("{}{}", self.env_config.base_url, path),
+            path,
+            params,
+            headers,
+            body,
+            variables: merged_vars,
+        }
+    }
 
-        if self.debug {
-            println!("📤 请求: {} {}", method, request_url);
-            if !params_str.is_empty() {
-                println!("   参数: {:?}", params_str);
+    /// 预览步骤最终发出的请求，不发送 HTTP 请求
+    ///
+    /// 认证 token 只使用缓存中已有的，没有时以 `<access_token>` 代替；
+    /// 替换后仍残留的占位符记录在 `unresolved` 中。
+    pub fn render_step(
+        &self,
+        step_name: &str,
+        external_variables: Option<HashMap<String, JsonValue>>,
+    ) -> Result<RenderedRequest> {
+        let step = self.config.steps
+            .get(step_name)
+            .ok_or_else(|| Error::MissingStep(step_name.to_string()))?;
+        let PreparedRequest { method, url, mut params, mut headers, body, .. } =
+            self.prepare_request(step, external_variables);
+
+        // 与实际发送一致：GET / DELETE 不带请求体
+        let body = if matches!(method.as_str(), "POST" | "PUT") { body } else { None };
+        if body.is_some() && !headers.keys().any(|k| k.eq_ignore_ascii_case("content-type")) {
+            headers.insert("content-type".to_string(), "application/json".to_string());
+        }
+
+        let token = self.client.cached_token().unwrap_or_else(|| "<access_token>".to_string());
+        match self.client.auth_placement(&token) {
+            Some(AuthPlacement::Header(key, value)) => {
+                headers.insert(key, value);
             }
-            if let Some(ref b) = body {
-                println!("   请求体: {}", serde_json::to_string_pretty(b).unwrap_or_default());
+            Some(AuthPlacement::Query(key, value)) => {
+                params.insert(key, value);
             }
+            None => {}
         }
 
-        // 发送请求
-        let result = match method.as_str() {
-            "GET" => self.client.get(&path, Some(&params_str), Some(&headers)).await,
-            "POST" => self.client.post(&path, body.as_ref(), Some(&params_str), Some(&headers)).await,
-            "PUT" => self.client.put(&path, body.as_ref(), Some(&params_str), Some(&headers)).await,
-            "DELETE" => self.client.delete(&path, Some(&params_str), Some(&headers)).await,
-            _ => Err(format!("不支持的 HTTP 方法: {}", method)),
+        let mut unresolved = Vec::new();
+        let re = Regex::new(r"\{[^}]+\}").unwrap();
+        let mut collect = |text: &str| {
+            for m in re.find_iter(text) {
+                if is_plain_text(&m.as_str()[1..m.len() - 1]) {
+                    continue;
+                }
+                if !unresolved.iter().any(|u| u == m.as_str()) {
+                    unresolved.push(m.as_str().to_string());
+                }
+            }
         };
+        collect(&url);
+        params.values().for_each(|v| collect(v));
+        headers.values().for_each(|v| collect(v));
+        if let Some(body) = &body {
+            collect_json_strings(body, &mut collect);
+        }
 
-        match result {
-            Ok((status, actual_request_headers, response_headers, response_body)) => {
-                // 验证响应
-                let validations = self.validate_response(&response_body, &step.validate);
-                let all_passed = validations.iter().all(|v| v.passed);
+        // 预览和导出的 curl 命令中不出现密钥值
+        let mask_map = |map: IndexMap<String, String>| -> IndexMap<String, String> {
+            map.into_iter().map(|(k, v)| (k, self.secrets.mask(&v))).collect()
+        };
+        Ok(RenderedRequest {
+            step_name: step_name.to_string(),
+            method,
+            url: self.secrets.mask(&url),
+            query: mask_map(params),
+            headers: mask_map(headers),
+            body: body.map(|b| self.secrets.mask_json(&b)),
+            unresolved,
+        })
+    }
 
-                // 保存响应数据
-                for rule in &step.save_response {
-                    if let Some(value) = get_json_field(&response_body, &rule.field) {
-                        self.context.insert(rule.to.clone(), value);
+    /// 获取认证 token 并放入缓存（已缓存时直接使用），之后的 [`render_step`](Self::render_step) 会使用真实的 token
+    pub async fn fetch_token(&self) -> Result<()> {
+        self.client.get_token().await?;
+        Ok(())
+    }
+
+    /// 认证 token 放置的请求头或查询参数名，未配置认证时返回 `None`
+    pub fn auth_key(&self) -> Option<String> {
+        match self.client.auth_placement("")? {
+            AuthPlacement::Header(key, _) | AuthPlacement::Query(key, _) => Some(key),
+        }
+    }
+
+    /// 获取测试用例列表
+    pub fn get_test_case_list(&self) -> Vec<TestCaseInfo> {
+        self.config.test_cases
+            .iter()
+            .map(|(id, case)| TestCaseInfo {
+                id: id.clone(),
+                name: if case.name.is_empty() { id.clone() } else { case.name.clone() },
+                description: case.description.clone(),
+                tags: self.case_tags(case),
+                steps: case.steps.clone(),
+            })
+            .collect()
+    }
+
+    /// 用例的有效标签：用例自身的标签加上其步骤的标签
+    fn case_tags(&self, case: &TestCase) -> Vec<String> {
+        let mut tags = case.tags.clone();
+        for step_id in &case.steps {
+            if let Some(step) = self.config.steps.get(step_id) {
+                for tag in &step.tags {
+                    if !tags.contains(tag) {
+                        tags.push(tag.clone());
                     }
                 }
+            }
+        }
+        tags
+    }
 
-                ExecutionResult {
-                    success: all_passed && status.is_success(),
-                    step_name: step_name.to_string(),
-                    request_url,
-                    request_method: method,
-                    request_headers: actual_request_headers,
-                    request_body: body,
-                    response_status: status.as_u16(),
-                    response_headers,
-                    response_body: Some(response_body),
-                    validations,
+    /// 按标签表达式筛选测试用例
+    pub fn select_test_cases(&self, filter: &TagFilter) -> Vec<String> {
+        self.config.test_cases
+            .iter()
+            .filter(|(_, case)| filter.matches(&self.case_tags(case)))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// 按标签表达式筛选步骤
+    pub fn select_steps(&self, filter: &TagFilter) -> Vec<String> {
+        self.config.steps
+            .iter()
+            .filter(|(_, step)| filter.matches(&step.tags))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// 执行测试用例
+    ///
+    /// 用例之间互不影响：执行前后恢复上下文，前一个用例保存的响应数据不会带入下一个用例。
+    /// 某个步骤失败或被取消后不再执行后续步骤。
+    pub async fn run_test_case(
+        &mut self,
+        case_id: &str,
+        external_variables: Option<HashMap<String, JsonValue>>,
+    ) -> TestCaseResult {
+        let start = Instant::now();
+
+        let case = match self.config.test_cases.get(case_id) {
+            Some(c) => c.clone(),
+            None => {
+                return TestCaseResult {
+                    case_id: case_id.to_string(),
+                    name: case_id.to_string(),
+                    success: false,
+                    status: ExecutionStatus::Error,
+                    steps: vec![],
                     duration_ms: start.elapsed().as_millis() as u64,
-                    error: None,
-                }
+                    error: Some(Error::MissingTestCase(case_id.to_string()).to_string()),
+                };
             }
-            Err(e) => ExecutionResult {
-                success: false,
-                step_name: step_name.to_string(),
-                request_url,
-                request_method: method,
-                request_headers: headers,
-                request_body: body,
-                response_status: 0,
-                response_headers: HashMap::new(),
-                response_body: None,
-                validations: vec![],
-                duration_ms: start.elapsed().as_millis() as u64,
-                error: Some(e),
-            },
+        };
+
+        // 用例变量优先级高于 step 变量，外部传入的变量最高
+        let mut case_vars: HashMap<String, JsonValue> = case.variables
+            .iter()
+            .map(|(k, v)| (k.clone(), yaml_to_json(v)))
+            .collect();
+        if let Some(ext_vars) = external_variables {
+            case_vars.extend(ext_vars);
+        }
+
+        self.emit(ExecutionEvent::CaseStarted {
+            case_id: case_id.to_string(),
+            steps: case.steps.clone(),
+        });
+
+        let saved_context = self.context.clone();
+        let saved_case = self.current_case.replace(case_id.to_string());
+        let mut results = Vec::new();
+        let mut status = ExecutionStatus::Passed;
+
+        for step_id in &case.steps {
+            let result = self.execute_step(step_id, Some(case_vars.clone())).await;
+            let step_status = result.status;
+            results.push(result);
+            if step_status != ExecutionStatus::Passed {
+                status = step_status;
+                break;
+            }
+        }
+
+        self.context = saved_context;
+        self.current_case = saved_case;
+
+        let duration_ms = start.elapsed().as_millis() as u64;
+        self.emit(ExecutionEvent::CaseFinished {
+            case_id: case_id.to_string(),
+            status,
+            duration_ms,
+        });
+
+        TestCaseResult {
+            case_id: case_id.to_string(),
+            name: if case.name.is_empty() { case_id.to_string() } else { case.name },
+            success: status == ExecutionStatus::Passed,
+            status,
+            steps: results,
+            duration_ms,
+            error: None,
+        }
+    }
+
+    /// 依次执行所有匹配标签表达式的测试用例
+    pub async fn run_test_cases(
+        &mut self,
+        filter: &TagFilter,
+        external_variables: Option<HashMap<String, JsonValue>>,
+    ) -> Vec<TestCaseResult> {
+        let mut results = Vec::new();
+        for case_id in self.select_test_cases(filter) {
+            if self.cancel_token.is_cancelled() {
+                break;
+            }
+            results.push(self.run_test_case(&case_id, external_variables.clone()).await);
         }
+        results
     }
 
-    /// 路径映射转换
+    /// 路径映射转换，多个前缀都匹配时取最先声明的映射
     fn transform_path(&self, path: &str) -> String {
         for (source, target) in &self.path_mapping {
             if path.starts_with(source) {
@@ -244,11 +705,11 @@ impl TestEngine {
         path.to_string()
     }
 
-    /// 替换字符串中的变量
+    /// 替换字符串中的变量，最后替换密钥引用（变量的值也可以是 `{secret:名称}`）
     fn replace_variables(&self, text: &str, variables: &HashMap<String, JsonValue>) -> String {
         let re = Regex::new(r"\{([^}]+)\}").unwrap();
         
-        re.replace_all(text, |caps: &regex::Captures| {
+        let replaced = re.replace_all(text, |caps: &regex::Captures| {
             let var_path = &caps[1];
             
             // 支持嵌套访问 {user.name}
@@ -264,19 +725,29 @@ impl TestEngine {
                 }
             }
             
-            match value {
-                Some(v) => json_value_to_string(v),
-                None => caps[0].to_string(),
+            if let Some(v) = value {
+                return json_value_to_string(v);
+            }
+
+            // 非简单变量引用时按表达式求值，如 {page + 1}；
+            // 不引用任何变量的内容（如 {1+1}、{true}）是普通文本，保持原样
+            match expr::parse(var_path) {
+                Ok(expr) if !expr.variables().is_empty() => match expr.evaluate(variables) {
+                    Ok(v) => json_value_to_string(&v),
+                    Err(_) => caps[0].to_string(),
+                },
+                _ => caps[0].to_string(),
             }
-        }).to_string()
+        });
+        self.secrets.resolve(&replaced)
     }
 
     /// 替换 Map 中的变量
     fn replace_map_variables(
         &self,
-        map: &HashMap<String, YamlValue>,
+        map: &IndexMap<String, YamlValue>,
         variables: &HashMap<String, JsonValue>,
-    ) -> HashMap<String, JsonValue> {
+    ) -> IndexMap<String, JsonValue> {
         map.iter()
             .map(|(k, v)| {
                 let json_v = yaml_to_json(v);
@@ -348,6 +819,76 @@ impl TestEngine {
             }
         }).collect()
     }
+
+    /// 计算表达式断言
+    ///
+    /// 作用域为合并后的变量，加上响应体的顶层字段，以及 `response`、`status`、`headers`
+    fn evaluate_asserts(
+        &self,
+        asserts: &[String],
+        variables: &HashMap<String, JsonValue>,
+        status: u16,
+        headers: &IndexMap<String, String>,
+        response: &JsonValue,
+    ) -> Vec<ValidationResult> {
+        if asserts.is_empty() {
+            return vec![];
+        }
+
+        let mut scope = variables.clone();
+        if let JsonValue::Object(fields) = response {
+            for (k, v) in fields {
+                scope.insert(k.clone(), v.clone());
+            }
+        }
+        scope.insert("response".to_string(), response.clone());
+        scope.insert("status".to_string(), JsonValue::from(status));
+        scope.insert(
+            "headers".to_string(),
+            serde_json::to_value(headers).unwrap_or(JsonValue::Null),
+        );
+
+        asserts.iter().map(|source| {
+            let (actual, passed) = match expr::evaluate(source, &scope) {
+                Ok(v) => (json_value_to_string(&v), expr::is_truthy(&v)),
+                Err(e) => (e, false),
+            };
+
+            ValidationResult {
+                field: source.clone(),
+                operator: "assert".to_string(),
+                expected: "true".to_string(),
+                actual,
+                passed,
+            }
+        }).collect()
+    }
+}
+
+/// 按变量解析后的请求
+struct PreparedRequest {
+    /// 合并后的变量，断言求值时使用
+    variables: HashMap<String, JsonValue>,
+    method: String,
+    path: String,
+    url: String,
+    params: IndexMap<String, String>,
+    headers: IndexMap<String, String>,
+    body: Option<JsonValue>,
+}
+
+fn collect_json_strings(value: &JsonValue, f: &mut impl FnMut(&str)) {
+    match value {
+        JsonValue::String(s) => f(s),
+        JsonValue::Array(items) => items.iter().for_each(|v| collect_json_strings(v, f)),
+        JsonValue::Object(map) => map.values().for_each(|v| collect_json_strings(v, f)),
+        _ => {}
+    }
+}
+
+/// 花括号中的表达式不引用任何变量（如 `{1+1}`、`{true}`）时是普通文本，不是占位符
+fn is_plain_text(inner: &str) -> bool {
+    expr::parse(inner).is_ok_and(|expr| expr.variables().is_empty())
 }
 
 /// YAML Value 转 JSON Value
@@ -0,0 +1,176 @@
+//! 错误类型
+//!
+//! 各层统一返回 [`Error`]，传给前端时序列化为 `{ code, message, details }`，
+//! 前端和命令行可以按 `code` 区分处理。请求失败的 `code` 与执行结果中的
+//! `error_kind`（[`ErrorKind`]）取值相同。
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value as JsonValue};
+
+use crate::config::ErrorKind;
+use crate::http_client::HttpError;
+use crate::source_map::Position;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// 配置文件无法解析或内容不合法，`path` 为出错的键路径（如 `steps.login.validate`）
+    #[error("{message}{}", location_suffix(.file, .line, .column))]
+    ConfigParse {
+        message: String,
+        file: Option<String>,
+        line: Option<usize>,
+        column: Option<usize>,
+        path: Option<String>,
+    },
+
+    #[error("环境 '{0}' 配置不存在")]
+    MissingEnvironment(String),
+
+    #[error("步骤 '{0}' 不存在")]
+    MissingStep(String),
+
+    #[error("测试用例 '{0}' 不存在")]
+    MissingTestCase(String),
+
+    #[error("执行记录 '{0}' 不存在")]
+    MissingRun(String),
+
+    /// 请求失败（超时、连接失败、获取 token 失败等），`kind` 决定错误代码
+    #[error("{message}")]
+    Request { kind: ErrorKind, message: String },
+
+    /// 参数不合法（标签表达式、压测参数等）
+    #[error("{0}")]
+    Validation(String),
+
+    /// 密钥库未解锁、主密码错误或文件损坏
+    #[error("{0}")]
+    Vault(String),
+
+    #[error("{message} '{path}': {source}")]
+    Io {
+        message: String,
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// 执行任务异常中断等内部错误
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl Error {
+    pub fn config_parse(message: impl Into<String>, file: Option<&std::path::Path>) -> Self {
+        Error::ConfigParse {
+            message: message.into(),
+            file: file.map(|f| f.display().to_string()),
+            line: None,
+            column: None,
+            path: None,
+        }
+    }
+
+    /// 带位置的配置错误
+    pub fn config_parse_at(
+        message: impl Into<String>,
+        file: Option<&std::path::Path>,
+        position: Option<Position>,
+        path: Option<String>,
+    ) -> Self {
+        Error::ConfigParse {
+            message: message.into(),
+            file: file.map(|f| f.display().to_string()),
+            line: position.map(|p| p.line),
+            column: position.map(|p| p.column),
+            path,
+        }
+    }
+
+    pub fn io(message: impl Into<String>, path: impl AsRef<std::path::Path>, source: std::io::Error) -> Self {
+        Error::Io {
+            message: message.into(),
+            path: path.as_ref().display().to_string(),
+            source,
+        }
+    }
+
+    /// 错误代码
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::ConfigParse { .. } => "config_parse",
+            Error::MissingEnvironment(_) => "missing_environment",
+            Error::MissingStep(_) => "missing_step",
+            Error::MissingTestCase(_) => "missing_test_case",
+            Error::MissingRun(_) => "missing_run",
+            Error::Request { kind, .. } => kind.code(),
+            Error::Validation(_) => "validation",
+            Error::Vault(_) => "vault",
+            Error::Io { .. } => "io",
+            Error::Internal(_) => "internal",
+        }
+    }
+
+    /// 对应的执行错误类型，用于填写执行结果的 `error_kind`
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match self {
+            Error::Request { kind, .. } => Some(*kind),
+            Error::MissingStep(_) => Some(ErrorKind::MissingStep),
+            _ => None,
+        }
+    }
+
+    /// 附加信息
+    pub fn details(&self) -> Option<JsonValue> {
+        match self {
+            Error::ConfigParse { file, line, column, path, .. } => Some(json!({
+                "file": file,
+                "line": line,
+                "column": column,
+                "path": path,
+            })),
+            Error::MissingEnvironment(name) => Some(json!({ "environment": name })),
+            Error::MissingStep(name) => Some(json!({ "step": name })),
+            Error::MissingTestCase(name) => Some(json!({ "test_case": name })),
+            Error::MissingRun(id) => Some(json!({ "run": id })),
+            Error::Io { path, source, .. } => Some(json!({ "path": path, "reason": source.to_string() })),
+            _ => None,
+        }
+    }
+}
+
+fn location_suffix(file: &Option<String>, line: &Option<usize>, column: &Option<usize>) -> String {
+    let position = match (line, column) {
+        (Some(line), Some(column)) => format!("第 {} 行第 {} 列", line, column),
+        (Some(line), None) => format!("第 {} 行", line),
+        _ => String::new(),
+    };
+    match (file, position.is_empty()) {
+        (Some(file), true) => format!(" ({})", file),
+        (Some(file), false) => format!(" ({} {})", file, position),
+        (None, false) => format!(" ({})", position),
+        (None, true) => String::new(),
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Error", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<HttpError> for Error {
+    fn from(e: HttpError) -> Self {
+        match e.kind {
+            // HTTP 客户端不会产生步骤不存在的错误，消息中也没有步骤名
+            ErrorKind::MissingStep => Error::Internal(e.message),
+            kind => Error::Request { kind, message: e.message },
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! 执行事件
+//!
+//! 引擎在执行过程中通过 [`ExecutionObserver`] 发出结构化事件，GUI 通过 [`TauriObserver`]
+//! 以窗口事件的形式实时接收执行进度。
+//!
+//! 每次执行（单个 step、测试用例、标签筛选、套件）以一对 `run_started` / `run_finished` 开始和结束，
+//! 其中每个测试用例再以 `case_started` / `case_finished` 标记。
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::config::{ExecutionStatus, ValidationResult};
+use crate::history::RunKind;
+
+/// 前端监听的事件名
+pub const EXECUTION_EVENT: &str = "execution-event";
+
+/// 执行事件，`case_id` 为所属的测试用例（单独执行 step 时为空）
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExecutionEvent {
+    /// 执行开始，`target` 为步骤名、用例名或标签表达式
+    RunStarted {
+        kind: RunKind,
+        target: String,
+    },
+    /// 测试用例开始执行
+    CaseStarted {
+        case_id: String,
+        steps: Vec<String>,
+    },
+    StepStarted {
+        case_id: Option<String>,
+        step_name: String,
+    },
+    /// 发出请求（每次重试都会发出）
+    RequestSent {
+        case_id: Option<String>,
+        step_name: String,
+        attempt: u32,
+        method: String,
+        url: String,
+    },
+    /// 收到响应，请求失败时 status 为 0 并带有错误信息
+    ResponseReceived {
+        case_id: Option<String>,
+        step_name: String,
+        attempt: u32,
+        status: u16,
+        duration_ms: u64,
+        error: Option<String>,
+    },
+    ValidationResult {
+        case_id: Option<String>,
+        step_name: String,
+        result: ValidationResult,
+    },
+    StepFinished {
+        case_id: Option<String>,
+        step_name: String,
+        status: ExecutionStatus,
+        duration_ms: u64,
+    },
+    /// 测试用例执行结束
+    CaseFinished {
+        case_id: String,
+        status: ExecutionStatus,
+        duration_ms: u64,
+    },
+    /// 执行结束，`status` 为所有步骤 / 用例中最差的状态
+    RunFinished {
+        kind: RunKind,
+        status: ExecutionStatus,
+        duration_ms: u64,
+    },
+}
+
+/// 执行事件观察者
+pub trait ExecutionObserver: Send + Sync {
+    fn on_event(&self, event: &ExecutionEvent);
+}
+
+/// 转发给前端的事件负载
+#[derive(Debug, Clone, Serialize)]
+struct EventPayload<'a> {
+    execution_id: &'a str,
+    #[serde(flatten)]
+    event: &'a ExecutionEvent,
+}
+
+/// 将执行事件作为 Tauri 窗口事件转发给前端
+pub struct TauriObserver {
+    app: AppHandle,
+    execution_id: String,
+}
+
+impl TauriObserver {
+    pub fn new(app: AppHandle, execution_id: String) -> Self {
+        Self { app, execution_id }
+    }
+}
+
+impl ExecutionObserver for TauriObserver {
+    fn on_event(&self, event: &ExecutionEvent) {
+        let payload = EventPayload {
+            execution_id: &self.execution_id,
+            event,
+        };
+        if let Err(e) = self.app.emit(EXECUTION_EVENT, payload) {
+            eprintln!("发送执行事件失败: {}", e);
+        }
+    }
+}
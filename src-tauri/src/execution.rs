@@ -0,0 +1,70 @@
+//! 执行任务管理
+//!
+//! 每次执行分配一个 ID 并登记取消令牌，前端可通过 `cancel_execution` 中止正在进行的执行。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{Error, Result};
+
+/// 正在进行的执行
+#[derive(Default)]
+pub struct ExecutionRegistry {
+    tokens: Mutex<HashMap<String, CancellationToken>>,
+    counter: AtomicU64,
+}
+
+impl ExecutionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一次执行，未指定 ID 时自动生成，返回 (ID, 取消令牌)
+    ///
+    /// 指定的 ID 正被另一次执行使用时返回错误，避免覆盖对方的取消令牌
+    pub fn start(&self, id: Option<String>) -> Result<(String, CancellationToken)> {
+        let id = id.unwrap_or_else(|| {
+            let n = self.counter.fetch_add(1, Ordering::SeqCst);
+            format!("exec-{}-{}", chrono::Utc::now().timestamp_millis(), n)
+        });
+        let token = CancellationToken::new();
+
+        let mut tokens = self.tokens
+            .lock()
+            .map_err(|_| Error::Internal("执行登记状态异常".to_string()))?;
+        if tokens.contains_key(&id) {
+            return Err(Error::Validation(format!("执行 ID '{}' 正在使用中", id)));
+        }
+        tokens.insert(id.clone(), token.clone());
+
+        Ok((id, token))
+    }
+
+    /// 取消执行，ID 不存在（已结束）时返回 false
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.tokens.lock().ok().and_then(|tokens| tokens.get(id).cloned()) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 执行结束，移除登记
+    pub fn finish(&self, id: &str) {
+        if let Ok(mut tokens) = self.tokens.lock() {
+            tokens.remove(id);
+        }
+    }
+
+    /// 正在进行的执行 ID 列表
+    pub fn running(&self) -> Vec<String> {
+        self.tokens
+            .lock()
+            .map(|tokens| tokens.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,854 @@
+//! 表达式语言
+//!
+//! 用于占位符 `{page + 1}` 和 `assert` 断言，只能读取传入的变量，不能访问文件、
+//! 网络或调用任意函数。支持：
+//! - 字面量：数字、字符串（单/双引号）、`true` / `false` / `null`、列表 `[1, 2]`
+//! - 变量与字段访问：`user.name`、`items[0]`
+//! - 运算：`+ - * / %`、比较 `== != < <= > >=`、逻辑 `and or not`（或 `&& || !`）
+//! - 三元表达式：`cond ? a : b`
+//! - 过滤器：`items | length`、`name | upper`、`value | default(0)`
+
+use serde_json::{Number, Value as JsonValue};
+use std::collections::HashMap;
+
+/// 表达式最大长度
+const MAX_SOURCE_LEN: usize = 1024;
+/// 最大嵌套深度
+const MAX_DEPTH: usize = 64;
+
+/// 在给定变量作用域中求值表达式
+pub fn evaluate(source: &str, scope: &HashMap<String, JsonValue>) -> Result<JsonValue, String> {
+    parse(source)?.evaluate(scope)
+}
+
+/// 解析表达式
+pub fn parse(source: &str) -> Result<Expr, String> {
+    if source.len() > MAX_SOURCE_LEN {
+        return Err(format!("表达式过长（最多 {} 个字符）", MAX_SOURCE_LEN));
+    }
+
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens, pos: 0, depth: 0 };
+    let expr = parser.parse_expr()?;
+
+    match parser.peek() {
+        Token::End => Ok(expr),
+        token => Err(format!("表达式解析错误: 多余的 {}", token.describe())),
+    }
+}
+
+/// 判断值的真假：`null`、`false`、`0`、空字符串、空列表、空对象为假
+pub fn is_truthy(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Null => false,
+        JsonValue::Bool(b) => *b,
+        JsonValue::Number(n) => n.as_f64().map(|f| f != 0.0).unwrap_or(false),
+        JsonValue::String(s) => !s.is_empty(),
+        JsonValue::Array(a) => !a.is_empty(),
+        JsonValue::Object(o) => !o.is_empty(),
+    }
+}
+
+// ============ 词法分析 ============
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64, bool),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+    End,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Number(n, _) => format!("数字 {}", n),
+            Token::Str(s) => format!("字符串 '{}'", s),
+            Token::Ident(s) => format!("标识符 '{}'", s),
+            Token::Op(op) => format!("'{}'", op),
+            Token::End => "表达式结尾".to_string(),
+        }
+    }
+}
+
+/// 运算符，按长度从长到短排列，保证最长匹配
+const OPERATORS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||",
+    "+", "-", "*", "/", "%", "<", ">", "!", "?", ":", "|", ".", ",", "(", ")", "[", "]",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let mut is_int = true;
+            if i + 1 < chars.len() && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+                is_int = false;
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text.parse::<f64>().map_err(|_| format!("无效的数字: {}", text))?;
+            tokens.push(Token::Number(n, is_int));
+        } else if c == '"' || c == '\'' {
+            let quote = c;
+            let mut s = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err("表达式解析错误: 字符串缺少结束引号".to_string()),
+                    Some(&ch) if ch == quote => break,
+                    Some('\\') => {
+                        let escaped = chars.get(i + 1).copied()
+                            .ok_or_else(|| "表达式解析错误: 字符串缺少结束引号".to_string())?;
+                        s.push(match escaped {
+                            'n' => '\n',
+                            't' => '\t',
+                            other => other,
+                        });
+                        i += 2;
+                    }
+                    Some(&ch) => {
+                        s.push(ch);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            tokens.push(Token::Str(s));
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| format!("表达式解析错误: 无法识别的字符 '{}'", c))?;
+            tokens.push(Token::Op(op));
+            i += op.chars().count();
+        }
+    }
+
+    tokens.push(Token::End);
+    Ok(tokens)
+}
+
+// ============ 语法分析 ============
+
+/// 表达式语法树
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Literal(JsonValue),
+    List(Vec<Expr>),
+    Var(String),
+    Member(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+    Filter(Box<Expr>, String, Vec<Expr>),
+}
+
+impl Expr {
+    /// 在给定变量作用域中求值
+    pub fn evaluate(&self, scope: &HashMap<String, JsonValue>) -> Result<JsonValue, String> {
+        eval(self, scope)
+    }
+
+    /// 表达式引用的变量名（按出现顺序去重）
+    pub fn variables(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        self.collect_variables(&mut names);
+        names
+    }
+
+    fn collect_variables(&self, names: &mut Vec<String>) {
+        match self {
+            Expr::Literal(_) => {}
+            Expr::Var(name) => {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+            Expr::List(items) => items.iter().for_each(|e| e.collect_variables(names)),
+            Expr::Member(e, _) | Expr::Not(e) | Expr::Neg(e) => e.collect_variables(names),
+            Expr::Index(a, b) | Expr::Binary(_, a, b) | Expr::And(a, b) | Expr::Or(a, b) => {
+                a.collect_variables(names);
+                b.collect_variables(names);
+            }
+            Expr::Ternary(cond, a, b) => {
+                cond.collect_variables(names);
+                a.collect_variables(names);
+                b.collect_variables(names);
+            }
+            Expr::Filter(e, _, args) => {
+                e.collect_variables(names);
+                args.iter().for_each(|a| a.collect_variables(names));
+            }
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Token::Op(o) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Token::Ident(s) if s == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_op(&mut self, op: &str) -> Result<(), String> {
+        if self.eat_op(op) {
+            Ok(())
+        } else {
+            Err(format!("表达式解析错误: 期望 '{}'，实际为 {}", op, self.peek().describe()))
+        }
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("表达式嵌套过深".to_string());
+        }
+        let result = self.parse_ternary();
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_ternary(&mut self) -> Result<Expr, String> {
+        let cond = self.parse_or()?;
+        if self.eat_op("?") {
+            let then = self.parse_expr()?;
+            self.expect_op(":")?;
+            let otherwise = self.parse_expr()?;
+            return Ok(Expr::Ternary(Box::new(cond), Box::new(then), Box::new(otherwise)));
+        }
+        Ok(cond)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.eat_op("||") || self.eat_keyword("or") {
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_comparison()?;
+        while self.eat_op("&&") || self.eat_keyword("and") {
+            let right = self.parse_comparison()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let left = self.parse_additive()?;
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat_op(op) {
+                let right = self.parse_additive()?;
+                return Ok(Expr::Binary(op, Box::new(left), Box::new(right)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = if self.eat_op("+") {
+                "+"
+            } else if self.eat_op("-") {
+                "-"
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_multiplicative()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = if self.eat_op("*") {
+                "*"
+            } else if self.eat_op("/") {
+                "/"
+            } else if self.eat_op("%") {
+                "%"
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_unary()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.eat_op("!") || self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.parse_nested_unary()?)));
+        }
+        if self.eat_op("-") {
+            return Ok(Expr::Neg(Box::new(self.parse_nested_unary()?)));
+        }
+        self.parse_postfix()
+    }
+
+    fn parse_nested_unary(&mut self) -> Result<Expr, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("表达式嵌套过深".to_string());
+        }
+        let result = self.parse_unary();
+        self.depth -= 1;
+        result
+    }
+
+    /// 字段访问、下标和过滤器，过滤器只作用于紧邻的操作数：`a | length * b` 即 `(a | length) * b`
+    fn parse_postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            if self.eat_op(".") {
+                match self.next() {
+                    Token::Ident(name) => expr = Expr::Member(Box::new(expr), name),
+                    token => return Err(format!("表达式解析错误: '.' 后应为字段名，实际为 {}", token.describe())),
+                }
+            } else if self.eat_op("[") {
+                let index = self.parse_expr()?;
+                self.expect_op("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else if self.eat_op("|") {
+                let name = match self.next() {
+                    Token::Ident(name) => name,
+                    token => return Err(format!("表达式解析错误: '|' 后应为过滤器名，实际为 {}", token.describe())),
+                };
+                let mut args = Vec::new();
+                if self.eat_op("(") && !self.eat_op(")") {
+                    loop {
+                        args.push(self.parse_expr()?);
+                        if self.eat_op(")") {
+                            break;
+                        }
+                        self.expect_op(",")?;
+                    }
+                }
+                expr = Expr::Filter(Box::new(expr), name, args);
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Token::Number(n, is_int) => Ok(Expr::Literal(make_number(n, is_int))),
+            Token::Str(s) => Ok(Expr::Literal(JsonValue::String(s))),
+            Token::Ident(name) => Ok(match name.as_str() {
+                "true" => Expr::Literal(JsonValue::Bool(true)),
+                "false" => Expr::Literal(JsonValue::Bool(false)),
+                "null" => Expr::Literal(JsonValue::Null),
+                _ => Expr::Var(name),
+            }),
+            Token::Op("(") => {
+                let expr = self.parse_expr()?;
+                self.expect_op(")")?;
+                Ok(expr)
+            }
+            Token::Op("[") => {
+                let mut items = Vec::new();
+                if !self.eat_op("]") {
+                    loop {
+                        items.push(self.parse_expr()?);
+                        if self.eat_op("]") {
+                            break;
+                        }
+                        self.expect_op(",")?;
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            token => Err(format!("表达式解析错误: 意外的 {}", token.describe())),
+        }
+    }
+}
+
+// ============ 求值 ============
+
+fn eval(expr: &Expr, scope: &HashMap<String, JsonValue>) -> Result<JsonValue, String> {
+    match expr {
+        Expr::Literal(v) => Ok(v.clone()),
+        Expr::List(items) => items
+            .iter()
+            .map(|item| eval(item, scope))
+            .collect::<Result<Vec<_>, _>>()
+            .map(JsonValue::Array),
+        Expr::Var(name) => scope
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("未定义的变量: {}", name)),
+        Expr::Member(target, field) => {
+            let target = eval(target, scope)?;
+            target
+                .get(field)
+                .cloned()
+                .ok_or_else(|| format!("字段不存在: {}", field))
+        }
+        Expr::Index(target, index) => {
+            let target = eval(target, scope)?;
+            let index = eval(index, scope)?;
+            let value = match (&target, &index) {
+                (JsonValue::Array(arr), JsonValue::Number(n)) => {
+                    let i = n.as_f64().unwrap_or(-1.0);
+                    let i = if i < 0.0 { arr.len() as f64 + i } else { i };
+                    if i >= 0.0 { arr.get(i as usize) } else { None }
+                }
+                (JsonValue::Object(obj), JsonValue::String(key)) => obj.get(key),
+                _ => None,
+            };
+            value.cloned().ok_or_else(|| format!("下标不存在: {}", to_display(&index)))
+        }
+        Expr::Not(inner) => Ok(JsonValue::Bool(!is_truthy(&eval(inner, scope)?))),
+        Expr::Neg(inner) => {
+            let value = eval(inner, scope)?;
+            let n = as_number(&value).ok_or_else(|| format!("无法对 {} 取负", to_display(&value)))?;
+            Ok(make_number(-n, is_integer(&value)))
+        }
+        Expr::And(left, right) => {
+            let left = eval(left, scope)?;
+            if !is_truthy(&left) {
+                return Ok(JsonValue::Bool(false));
+            }
+            Ok(JsonValue::Bool(is_truthy(&eval(right, scope)?)))
+        }
+        Expr::Or(left, right) => {
+            let left = eval(left, scope)?;
+            if is_truthy(&left) {
+                return Ok(JsonValue::Bool(true));
+            }
+            Ok(JsonValue::Bool(is_truthy(&eval(right, scope)?)))
+        }
+        Expr::Ternary(cond, then, otherwise) => {
+            if is_truthy(&eval(cond, scope)?) {
+                eval(then, scope)
+            } else {
+                eval(otherwise, scope)
+            }
+        }
+        Expr::Binary(op, left, right) => {
+            let left = eval(left, scope)?;
+            let right = eval(right, scope)?;
+            eval_binary(op, &left, &right)
+        }
+        Expr::Filter(input, name, args) => {
+            // default 过滤器在输入求值失败（变量或字段不存在）时同样生效
+            if name == "default" {
+                let fallback = match args.first() {
+                    Some(arg) => eval(arg, scope)?,
+                    None => JsonValue::Null,
+                };
+                return Ok(match eval(input, scope) {
+                    Ok(JsonValue::Null) | Err(_) => fallback,
+                    Ok(value) => value,
+                });
+            }
+            let input = eval(input, scope)?;
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, scope))
+                .collect::<Result<Vec<_>, _>>()?;
+            apply_filter(name, &input, &args)
+        }
+    }
+}
+
+fn eval_binary(op: &str, left: &JsonValue, right: &JsonValue) -> Result<JsonValue, String> {
+    match op {
+        "==" => Ok(JsonValue::Bool(values_equal(left, right))),
+        "!=" => Ok(JsonValue::Bool(!values_equal(left, right))),
+        "<" | "<=" | ">" | ">=" => {
+            let ordering = match (left, right) {
+                (JsonValue::String(a), JsonValue::String(b)) => Some(a.cmp(b)),
+                _ => match (as_number(left), as_number(right)) {
+                    (Some(a), Some(b)) => a.partial_cmp(&b),
+                    _ => None,
+                },
+            };
+            let ordering = ordering.ok_or_else(|| {
+                format!("无法比较 {} 和 {}", to_display(left), to_display(right))
+            })?;
+            Ok(JsonValue::Bool(match op {
+                "<" => ordering.is_lt(),
+                "<=" => ordering.is_le(),
+                ">" => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        "+" => match (left, right) {
+            (JsonValue::String(_), _) | (_, JsonValue::String(_)) => {
+                Ok(JsonValue::String(format!("{}{}", to_display(left), to_display(right))))
+            }
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                Ok(JsonValue::Array(a.iter().chain(b.iter()).cloned().collect()))
+            }
+            _ => arithmetic(op, left, right),
+        },
+        _ => arithmetic(op, left, right),
+    }
+}
+
+fn arithmetic(op: &str, left: &JsonValue, right: &JsonValue) -> Result<JsonValue, String> {
+    let (a, b) = match (as_number(left), as_number(right)) {
+        (Some(a), Some(b)) => (a, b),
+        _ => {
+            return Err(format!(
+                "无法对 {} 和 {} 执行 '{}' 运算",
+                to_display(left),
+                to_display(right),
+                op
+            ))
+        }
+    };
+    let both_int = is_integer(left) && is_integer(right);
+
+    let result = match op {
+        "+" => a + b,
+        "-" => a - b,
+        "*" => a * b,
+        "/" | "%" if b == 0.0 => return Err("除数不能为 0".to_string()),
+        "/" => return Ok(make_number(a / b, both_int && (a / b).fract() == 0.0)),
+        "%" => a % b,
+        _ => return Err(format!("不支持的运算符: {}", op)),
+    };
+    Ok(make_number(result, both_int))
+}
+
+fn apply_filter(name: &str, input: &JsonValue, args: &[JsonValue]) -> Result<JsonValue, String> {
+    let arg_str = |i: usize, default: &str| {
+        args.get(i).map(to_display).unwrap_or_else(|| default.to_string())
+    };
+
+    match name {
+        "length" | "len" | "count" => {
+            let len = match input {
+                JsonValue::String(s) => s.chars().count(),
+                JsonValue::Array(a) => a.len(),
+                JsonValue::Object(o) => o.len(),
+                JsonValue::Null => 0,
+                other => return Err(format!("{} 没有长度", to_display(other))),
+            };
+            Ok(JsonValue::from(len))
+        }
+        "upper" => Ok(JsonValue::String(to_display(input).to_uppercase())),
+        "lower" => Ok(JsonValue::String(to_display(input).to_lowercase())),
+        "trim" => Ok(JsonValue::String(to_display(input).trim().to_string())),
+        "string" => Ok(JsonValue::String(to_display(input))),
+        "number" | "float" => as_number(input)
+            .map(|n| make_number(n, false))
+            .ok_or_else(|| format!("无法转换为数字: {}", to_display(input))),
+        "int" => as_number(input)
+            .map(|n| make_number(n.trunc(), true))
+            .ok_or_else(|| format!("无法转换为整数: {}", to_display(input))),
+        "abs" => as_number(input)
+            .map(|n| make_number(n.abs(), is_integer(input)))
+            .ok_or_else(|| format!("无法取绝对值: {}", to_display(input))),
+        "round" => {
+            let n = as_number(input).ok_or_else(|| format!("无法取整: {}", to_display(input)))?;
+            let digits = args.first().and_then(as_number).unwrap_or(0.0) as i32;
+            let factor = 10f64.powi(digits);
+            Ok(make_number((n * factor).round() / factor, digits <= 0))
+        }
+        "first" | "last" => {
+            let arr = input.as_array().ok_or_else(|| format!("{} 不是列表", to_display(input)))?;
+            let item = if name == "first" { arr.first() } else { arr.last() };
+            Ok(item.cloned().unwrap_or(JsonValue::Null))
+        }
+        "keys" => match input {
+            JsonValue::Object(o) => Ok(JsonValue::Array(o.keys().cloned().map(JsonValue::String).collect())),
+            other => Err(format!("{} 不是对象", to_display(other))),
+        },
+        "values" => match input {
+            JsonValue::Object(o) => Ok(JsonValue::Array(o.values().cloned().collect())),
+            other => Err(format!("{} 不是对象", to_display(other))),
+        },
+        "sum" | "min" | "max" => {
+            let arr = input.as_array().ok_or_else(|| format!("{} 不是列表", to_display(input)))?;
+            let numbers = arr
+                .iter()
+                .map(|v| as_number(v).ok_or_else(|| format!("列表中包含非数字: {}", to_display(v))))
+                .collect::<Result<Vec<_>, _>>()?;
+            let all_int = arr.iter().all(is_integer);
+            let result = match name {
+                "sum" => Some(numbers.iter().sum()),
+                "min" => numbers.iter().copied().reduce(f64::min),
+                _ => numbers.iter().copied().reduce(f64::max),
+            };
+            Ok(result.map(|n| make_number(n, all_int)).unwrap_or(JsonValue::Null))
+        }
+        "join" => {
+            let arr = input.as_array().ok_or_else(|| format!("{} 不是列表", to_display(input)))?;
+            let sep = arg_str(0, ",");
+            Ok(JsonValue::String(arr.iter().map(to_display).collect::<Vec<_>>().join(&sep)))
+        }
+        "contains" => {
+            let needle = args.first().cloned().unwrap_or(JsonValue::Null);
+            let found = match input {
+                JsonValue::String(s) => s.contains(&to_display(&needle)),
+                JsonValue::Array(a) => a.iter().any(|v| values_equal(v, &needle)),
+                JsonValue::Object(o) => o.contains_key(&to_display(&needle)),
+                _ => false,
+            };
+            Ok(JsonValue::Bool(found))
+        }
+        _ => Err(format!("未知的过滤器: {}", name)),
+    }
+}
+
+// ============ 辅助函数 ============
+
+fn make_number(n: f64, is_int: bool) -> JsonValue {
+    if is_int && n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 {
+        JsonValue::Number(Number::from(n as i64))
+    } else {
+        Number::from_f64(n).map(JsonValue::Number).unwrap_or(JsonValue::Null)
+    }
+}
+
+fn is_integer(value: &JsonValue) -> bool {
+    matches!(value, JsonValue::Number(n) if n.is_i64() || n.is_u64())
+}
+
+/// 数字或可解析为数字的字符串
+fn as_number(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::Number(n) => n.as_f64(),
+        JsonValue::String(s) => s.trim().parse::<f64>().ok(),
+        JsonValue::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+        _ => None,
+    }
+}
+
+/// 相等判断，数字按数值比较（`1 == 1.0`）
+fn values_equal(left: &JsonValue, right: &JsonValue) -> bool {
+    match (left, right) {
+        (JsonValue::Number(a), JsonValue::Number(b)) => a.as_f64() == b.as_f64(),
+        _ => left == right,
+    }
+}
+
+/// 值转为展示用字符串，字符串不带引号
+fn to_display(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        JsonValue::Number(n) => n.to_string(),
+        JsonValue::Bool(b) => b.to_string(),
+        JsonValue::Null => "null".to_string(),
+        _ => serde_json::to_string(value).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn scope() -> HashMap<String, JsonValue> {
+        [
+            ("page", json!(2)),
+            ("price", json!(2.5)),
+            ("items", json!([1, 2, 3])),
+            ("name", json!("Alice")),
+            ("user", json!({ "name": "Bob", "tags": ["a", "b"] })),
+            ("count", json!("10")),
+            ("empty", json!(null)),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect()
+    }
+
+    fn eval_ok(source: &str) -> JsonValue {
+        evaluate(source, &scope()).unwrap_or_else(|e| panic!("{}: {}", source, e))
+    }
+
+    fn eval_err(source: &str) -> String {
+        evaluate(source, &scope()).expect_err(source)
+    }
+
+    #[test]
+    fn arithmetic_precedence() {
+        assert_eq!(eval_ok("1 + 2 * 3"), json!(7));
+        assert_eq!(eval_ok("(1 + 2) * 3"), json!(9));
+        assert_eq!(eval_ok("10 - 4 - 3"), json!(3));
+        assert_eq!(eval_ok("7 % 4 * 2"), json!(6));
+        assert_eq!(eval_ok("-page + 5"), json!(3));
+        assert_eq!(eval_ok("6 / 3"), json!(2));
+        assert_eq!(eval_ok("7 / 2"), json!(3.5));
+        assert_eq!(eval_ok("page * price"), json!(5.0));
+    }
+
+    #[test]
+    fn logical_precedence() {
+        assert_eq!(eval_ok("true or false and false"), json!(true));
+        assert_eq!(eval_ok("(true or false) and false"), json!(false));
+        assert_eq!(eval_ok("not false and false"), json!(false));
+        assert_eq!(eval_ok("!(page > 1) || page == 2"), json!(true));
+        assert_eq!(eval_ok("page + 1 > 2 && name == 'Alice'"), json!(true));
+    }
+
+    #[test]
+    fn ternary_is_right_associative() {
+        assert_eq!(eval_ok("page > 5 ? 'big' : page > 1 ? 'medium' : 'small'"), json!("medium"));
+        assert_eq!(eval_ok("empty ? 1 : 2"), json!(2));
+    }
+
+    #[test]
+    fn filter_binds_to_adjacent_operand() {
+        // a | length * b 即 (a | length) * b
+        assert_eq!(eval_ok("items | length * page"), json!(6));
+        assert_eq!(eval_ok("page * items | length"), json!(6));
+        assert_eq!(eval_ok("name | upper + '!'"), json!("ALICE!"));
+        assert_eq!(eval_ok("(items | sum) / 2"), json!(3));
+        assert_eq!(eval_ok("items | join('-') | length"), json!(5));
+    }
+
+    #[test]
+    fn member_index_and_filters() {
+        assert_eq!(eval_ok("user.name"), json!("Bob"));
+        assert_eq!(eval_ok("user.tags[1]"), json!("b"));
+        assert_eq!(eval_ok("user['name']"), json!("Bob"));
+        assert_eq!(eval_ok("items[-1]"), json!(3));
+        assert_eq!(eval_ok("user | keys"), json!(["name", "tags"]));
+        assert_eq!(eval_ok("items | contains(2)"), json!(true));
+        assert_eq!(eval_ok("price | round"), json!(3));
+        assert_eq!(eval_ok("missing | default(5)"), json!(5));
+        assert_eq!(eval_ok("empty | default('x')"), json!("x"));
+        assert_eq!(eval_ok("user.age | default(0)"), json!(0));
+    }
+
+    #[test]
+    fn comparisons_between_mixed_types() {
+        // 数字按数值比较，数字字符串参与大小比较时转为数字
+        assert_eq!(eval_ok("1 == 1.0"), json!(true));
+        assert_eq!(eval_ok("count > 9"), json!(true));
+        assert_eq!(eval_ok("count == 10"), json!(false));
+        assert_eq!(eval_ok("'10' < '9'"), json!(true));
+        assert_eq!(eval_ok("empty == null"), json!(true));
+        assert_eq!(eval_ok("[1, 2] == [1, 2]"), json!(true));
+        assert_eq!(eval_ok("'page ' + page"), json!("page 2"));
+        assert_eq!(eval_ok("true + 1"), json!(2.0));
+        assert!(eval_err("name < 1").contains("无法比较"));
+        assert!(eval_err("user > 1").contains("无法比较"));
+        assert!(eval_err("user * 2").contains("无法对"));
+    }
+
+    #[test]
+    fn evaluation_errors() {
+        assert!(eval_err("missing + 1").contains("未定义的变量: missing"));
+        assert!(eval_err("user.age").contains("字段不存在: age"));
+        assert!(eval_err("items[5]").contains("下标不存在: 5"));
+        assert!(eval_err("page / 0").contains("除数不能为 0"));
+        assert!(eval_err("page % 0").contains("除数不能为 0"));
+        assert!(eval_err("name | nope").contains("未知的过滤器: nope"));
+        assert!(eval_err("page | length").contains("没有长度"));
+        assert!(eval_err("-name").contains("无法对"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("1 +").unwrap_err().contains("意外的 表达式结尾"));
+        assert!(parse("(1 + 2").unwrap_err().contains("期望 ')'"));
+        assert!(parse("1 2").unwrap_err().contains("多余的"));
+        assert!(parse("'abc").unwrap_err().contains("缺少结束引号"));
+        assert!(parse("a # b").unwrap_err().contains("无法识别的字符 '#'"));
+        assert!(parse("user.1").unwrap_err().contains("'.' 后应为字段名"));
+        assert!(parse("a | 1").unwrap_err().contains("'|' 后应为过滤器名"));
+        assert!(parse("a ? 1").unwrap_err().contains("期望 ':'"));
+    }
+
+    #[test]
+    fn source_length_limit() {
+        let at_limit = format!("'{}'", "a".repeat(MAX_SOURCE_LEN - 2));
+        assert!(parse(&at_limit).is_ok());
+        let too_long = format!("'{}'", "a".repeat(MAX_SOURCE_LEN - 1));
+        assert!(parse(&too_long).unwrap_err().contains("表达式过长"));
+    }
+
+    #[test]
+    fn nesting_depth_limit() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH - 1)).is_ok());
+        assert!(parse(&nested(MAX_DEPTH)).unwrap_err().contains("嵌套过深"));
+        assert!(parse(&"!".repeat(MAX_DEPTH + 1)).unwrap_err().contains("嵌套过深"));
+        assert!(parse(&format!("{}1", "-".repeat(MAX_DEPTH + 1))).unwrap_err().contains("嵌套过深"));
+    }
+
+    #[test]
+    fn variables_are_collected_in_order() {
+        let expr = parse("user.name + page + items[page] | default(name)").unwrap();
+        assert_eq!(expr.variables(), vec!["user", "page", "items", "name"]);
+        assert!(parse("1 + 1").unwrap().variables().is_empty());
+    }
+
+    #[test]
+    fn truthiness() {
+        for value in [json!(null), json!(false), json!(0), json!(""), json!([]), json!({})] {
+            assert!(!is_truthy(&value), "{}", value);
+        }
+        for value in [json!(true), json!(1), json!("0"), json!([0]), json!({ "a": null })] {
+            assert!(is_truthy(&value), "{}", value);
+        }
+    }
+}
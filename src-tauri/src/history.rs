@@ -0,0 +1,492 @@
+//! 执行历史
+//!
+//! 每次在界面中执行（单个步骤、测试用例、套件等）的结果保存在应用数据目录的 `history/` 下：
+//! - `runs/<id>.json` 为完整的执行记录
+//! - `index.jsonl` 每行一条记录摘要，只追加；删除记录时重写
+//!
+//! 同一步骤的两次执行可以逐项比较请求、响应和验证结果的差异。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::{ExecutionResult, ExecutionStatus, TestCaseResult, TestConfig};
+use crate::error::{Error, Result};
+use crate::random;
+use crate::report::validation_label;
+use crate::storage::AppData;
+
+const HISTORY_DIR: &str = "history";
+const RUNS_DIR: &str = "runs";
+const INDEX_FILE: &str = "index.jsonl";
+
+/// 执行方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunKind {
+    Step,
+    TestCase,
+    TestCases,
+    Suite,
+    TaggedSteps,
+}
+
+/// 执行记录摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub id: String,
+    pub kind: RunKind,
+    /// 执行对象：步骤名、用例名或标签表达式
+    pub target: String,
+    /// 配置文件所在的工作区，不在任何最近打开的工作区中时为空
+    pub workspace: Option<String>,
+    pub file: String,
+    /// 执行时配置文件及其导入文件内容的哈希，用于判断两次执行之间配置是否修改过
+    #[serde(default)]
+    pub config_hash: Option<String>,
+    pub profile: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// 执行的步骤数
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub success: bool,
+}
+
+/// 完整的执行记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    #[serde(flatten)]
+    pub summary: RunSummary,
+    /// 直接执行的步骤（单步、按标签执行步骤）
+    #[serde(default)]
+    pub steps: Vec<ExecutionResult>,
+    /// 执行的测试用例
+    #[serde(default)]
+    pub cases: Vec<TestCaseResult>,
+}
+
+impl RunRecord {
+    /// 执行开始时创建记录，结束后调用 `finish_steps` / `finish_cases` 填入结果
+    pub fn start(kind: RunKind, target: &str, file: &str, config: &TestConfig) -> Self {
+        let file = normalize_path(file);
+        Self {
+            summary: RunSummary {
+                id: new_run_id(),
+                kind,
+                target: target.to_string(),
+                workspace: workspace_of(&file),
+                config_hash: content_hash(Path::new(&file), &config.imported_files),
+                file,
+                profile: config.global.profile.active.clone(),
+                started_at: Utc::now(),
+                duration_ms: 0,
+                total: 0,
+                passed: 0,
+                failed: 0,
+                success: false,
+            },
+            steps: Vec::new(),
+            cases: Vec::new(),
+        }
+    }
+
+    pub fn finish_steps(mut self, steps: Vec<ExecutionResult>) -> Self {
+        self.steps = steps;
+        self.finish()
+    }
+
+    pub fn finish_cases(mut self, cases: Vec<TestCaseResult>) -> Self {
+        self.cases = cases;
+        self.finish()
+    }
+
+    fn finish(mut self) -> Self {
+        let elapsed = Utc::now() - self.summary.started_at;
+        let (total, passed) = self
+            .all_steps()
+            .fold((0, 0), |(total, passed), step| (total + 1, passed + step.success as usize));
+
+        self.summary.duration_ms = elapsed.num_milliseconds().max(0) as u64;
+        self.summary.total = total;
+        self.summary.passed = passed;
+        self.summary.failed = total - passed;
+        self.summary.success = total > 0
+            && self.all_steps().all(|s| s.success)
+            && self.cases.iter().all(|c| c.success);
+        self
+    }
+
+    fn all_steps(&self) -> impl Iterator<Item = &ExecutionResult> {
+        self.steps.iter().chain(self.cases.iter().flat_map(|c| c.steps.iter()))
+    }
+
+    /// 查找步骤的执行结果，执行了多次时取最后一次
+    pub fn find_step(&self, step_name: &str) -> Option<&ExecutionResult> {
+        self.all_steps().filter(|s| s.step_name == step_name).last()
+    }
+}
+
+/// 列出执行记录时的筛选条件，未设置的条件不筛选
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunFilter {
+    #[serde(default)]
+    pub workspace: Option<String>,
+    #[serde(default)]
+    pub file: Option<String>,
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// 最多返回的条数
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+impl RunFilter {
+    fn matches(&self, run: &RunSummary) -> bool {
+        self.workspace.as_ref().is_none_or(|w| run.workspace.as_ref() == Some(w))
+            && self.file.as_ref().is_none_or(|f| &run.file == f)
+            && self.profile.as_ref().is_none_or(|p| &run.profile == p)
+    }
+}
+
+/// 执行历史存储
+pub struct History {
+    dir: PathBuf,
+}
+
+impl History {
+    /// 打开应用数据目录下的执行历史
+    pub fn open() -> Result<Self> {
+        let dir = AppData::get_data_dir()
+            .ok_or_else(|| Error::Internal("无法获取应用数据目录".to_string()))?;
+        Ok(Self::at(dir.join(HISTORY_DIR)))
+    }
+
+    /// 使用指定目录
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 保存一条执行记录
+    pub fn record(&self, run: &RunRecord) -> Result<()> {
+        let runs_dir = self.dir.join(RUNS_DIR);
+        fs::create_dir_all(&runs_dir).map_err(|e| Error::io("创建历史目录失败", &runs_dir, e))?;
+
+        let path = self.run_path(&run.summary.id);
+        let content = serde_json::to_string(run).map_err(|e| Error::io("序列化执行记录失败", &path, e.into()))?;
+        fs::write(&path, content).map_err(|e| Error::io("写入执行记录失败", &path, e))?;
+
+        let index = self.dir.join(INDEX_FILE);
+        let line = serde_json::to_string(&run.summary).map_err(|e| Error::io("序列化执行记录失败", &index, e.into()))?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&index)
+            .and_then(|mut file| writeln!(file, "{}", line))
+            .map_err(|e| Error::io("写入历史索引失败", &index, e))
+    }
+
+    /// 按条件列出执行记录，最新的在前；`file` 按 [`normalize_path`] 统一写法后比较
+    pub fn list(&self, filter: &RunFilter) -> Result<Vec<RunSummary>> {
+        let filter = RunFilter { file: filter.file.as_deref().map(normalize_path), ..filter.clone() };
+        let mut runs: Vec<RunSummary> = self.read_index()?.into_iter().filter(|r| filter.matches(r)).collect();
+        runs.sort_by_key(|run| Reverse(run.started_at));
+        if let Some(limit) = filter.limit {
+            runs.truncate(limit);
+        }
+        Ok(runs)
+    }
+
+    /// 读取完整的执行记录
+    pub fn load(&self, id: &str) -> Result<RunRecord> {
+        let path = self.run_path(id);
+        if !path.exists() {
+            return Err(Error::MissingRun(id.to_string()));
+        }
+        let content = fs::read_to_string(&path).map_err(|e| Error::io("读取执行记录失败", &path, e))?;
+        serde_json::from_str(&content).map_err(|e| Error::io("解析执行记录失败", &path, e.into()))
+    }
+
+    /// 删除执行记录，返回实际删除的条数
+    pub fn delete(&self, ids: &[String]) -> Result<usize> {
+        self.remove_where(|run| ids.contains(&run.id))
+    }
+
+    /// 清理旧记录：删除早于 `older_than_days` 天的记录，并且每个配置文件只保留最近 `keep_per_file` 条
+    pub fn prune(&self, keep_per_file: Option<usize>, older_than_days: Option<u32>) -> Result<usize> {
+        // 超出可表示范围的天数相当于没有记录早于截止时间
+        let cutoff = older_than_days
+            .and_then(|days| chrono::Duration::try_days(days as i64))
+            .and_then(|age| Utc::now().checked_sub_signed(age));
+
+        let mut runs = self.read_index()?;
+        runs.sort_by_key(|run| Reverse(run.started_at));
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        let expired: Vec<String> = runs
+            .iter()
+            .filter(|run| {
+                let count = seen.entry(run.file.as_str()).or_default();
+                *count += 1;
+                cutoff.is_some_and(|c| run.started_at < c) || keep_per_file.is_some_and(|keep| *count > keep)
+            })
+            .map(|run| run.id.clone())
+            .collect();
+
+        self.delete(&expired)
+    }
+
+    fn remove_where(&self, remove: impl Fn(&RunSummary) -> bool) -> Result<usize> {
+        let (removed, kept): (Vec<_>, Vec<_>) = self.read_index()?.into_iter().partition(|r| remove(r));
+        if removed.is_empty() {
+            return Ok(0);
+        }
+
+        for run in &removed {
+            let path = self.run_path(&run.id);
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| Error::io("删除执行记录失败", &path, e))?;
+            }
+        }
+
+        let index = self.dir.join(INDEX_FILE);
+        let mut content = String::new();
+        for run in &kept {
+            let line = serde_json::to_string(run).map_err(|e| Error::io("序列化执行记录失败", &index, e.into()))?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+        // 先写临时文件再替换，避免写到一半中断导致索引损坏
+        let temp = self.dir.join(format!("{}.tmp", INDEX_FILE));
+        fs::write(&temp, content).map_err(|e| Error::io("写入历史索引失败", &temp, e))?;
+        fs::rename(&temp, &index).map_err(|e| Error::io("写入历史索引失败", &index, e))?;
+
+        Ok(removed.len())
+    }
+
+    /// 读取索引，跳过无法解析的行（如写入中断留下的半行）
+    fn read_index(&self) -> Result<Vec<RunSummary>> {
+        let index = self.dir.join(INDEX_FILE);
+        if !index.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&index).map_err(|e| Error::io("读取历史索引失败", &index, e))?;
+        Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+    }
+
+    fn run_path(&self, id: &str) -> PathBuf {
+        // id 来自前端，只取文件名部分，避免访问历史目录以外的文件
+        let name = Path::new(id).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        self.dir.join(RUNS_DIR).join(format!("{}.json", name))
+    }
+}
+
+/// 保存执行记录；历史只是辅助信息，保存失败不影响执行结果
+pub fn save(run: &RunRecord) {
+    if let Err(e) = History::open().and_then(|history| history.record(run)) {
+        eprintln!("保存执行历史失败: {}", e);
+    }
+}
+
+/// 执行记录 id：开始时间加随机后缀，按字符串排序即按时间排序
+fn new_run_id() -> String {
+    let suffix = random::next_u64() & 0xffff_ffff;
+    format!("{}-{:08x}", Utc::now().format("%Y%m%d%H%M%S%3f"), suffix)
+}
+
+/// 配置文件及其导入文件内容的 FNV-1a 哈希，跨版本稳定；任一文件无法读取时为 `None`
+fn content_hash(file: &Path, imports: &[PathBuf]) -> Option<String> {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for path in std::iter::once(file).chain(imports.iter().map(PathBuf::as_path)) {
+        hash = fs::read(path)
+            .ok()?
+            .iter()
+            .fold(hash, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3));
+    }
+    Some(format!("{:016x}", hash))
+}
+
+/// 配置文件所在的最近工作区，`file` 已经过 [`normalize_path`]
+fn workspace_of(file: &str) -> Option<String> {
+    AppData::load()
+        .recent_workspaces
+        .into_iter()
+        .map(|w| w.path)
+        .filter(|w| Path::new(file).starts_with(normalize_path(w)))
+        .max_by_key(|w| w.len())
+}
+
+/// 统一配置文件路径的写法，保存执行记录和按文件查询时使用同一形式
+///
+/// 解析符号链接（如 macOS 的 `/var` -> `/private/var`）和相对路径，并去掉 Windows 的 `\\?\` 前缀；
+/// 路径不存在时原样返回。
+pub fn normalize_path(path: &str) -> String {
+    let Ok(canonical) = fs::canonicalize(path) else {
+        return path.to_string();
+    };
+    let canonical = canonical.to_string_lossy();
+    match canonical.strip_prefix(r"\\?\UNC\") {
+        Some(share) => format!(r"\\{}", share),
+        None => canonical.strip_prefix(r"\\?\").unwrap_or(&canonical).to_string(),
+    }
+}
+
+/// 同一步骤两次执行的差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepDiff {
+    pub step_name: String,
+    pub before: StepRun,
+    pub after: StepRun,
+    /// 耗时变化（after - before），毫秒
+    pub duration_delta_ms: i64,
+    pub changes: Vec<Change>,
+}
+
+/// 参与比较的一次执行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRun {
+    pub run_id: String,
+    pub started_at: DateTime<Utc>,
+    pub status: ExecutionStatus,
+    pub response_status: u16,
+    pub duration_ms: u64,
+}
+
+/// 一处差异，值不存在时为空
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Change {
+    /// 所属部分：`status`、`error`、`request_url`、`request_headers`、`request_body`、
+    /// `response_status`、`response_headers`、`response_body`、`validations`
+    pub section: String,
+    /// 部分内的位置，如 `data.items[0].id`、请求头名、验证名称；整体变化时为空
+    pub path: String,
+    pub before: Option<JsonValue>,
+    pub after: Option<JsonValue>,
+}
+
+/// 比较两次执行中同一步骤的结果
+pub fn diff_step(before: &RunRecord, after: &RunRecord, step_name: &str) -> Result<StepDiff> {
+    let (old, new) = (find_step(before, step_name)?, find_step(after, step_name)?);
+
+    let mut changes = Vec::new();
+    let mut scalar = |section: &str, a: JsonValue, b: JsonValue| {
+        if a != b {
+            changes.push(Change { section: section.to_string(), path: String::new(), before: Some(a), after: Some(b) });
+        }
+    };
+    scalar("status", json(&old.status), json(&new.status));
+    scalar("error", json(&old.error), json(&new.error));
+    scalar("request_url", json(&old.request_url), json(&new.request_url));
+    scalar("response_status", json(&old.response_status), json(&new.response_status));
+
+    diff_value("request_headers", &json(&old.request_headers), &json(&new.request_headers), &mut changes);
+    diff_value("request_body", &json(&old.request_body), &json(&new.request_body), &mut changes);
+    diff_value("response_headers", &json(&old.response_headers), &json(&new.response_headers), &mut changes);
+    diff_value("response_body", &json(&old.response_body), &json(&new.response_body), &mut changes);
+
+    // 验证按名称对应，比较是否通过和实际值
+    let validations = |result: &ExecutionResult| -> serde_json::Map<String, JsonValue> {
+        result
+            .validations
+            .iter()
+            .map(|v| (validation_label(v), serde_json::json!({ "passed": v.passed, "actual": v.actual })))
+            .collect()
+    };
+    let (old_validations, new_validations) = (validations(old), validations(new));
+    for key in old_validations.keys().chain(new_validations.keys().filter(|k| !old_validations.contains_key(*k))) {
+        let (a, b) = (old_validations.get(key), new_validations.get(key));
+        if a != b {
+            changes.push(Change { section: "validations".to_string(), path: key.clone(), before: a.cloned(), after: b.cloned() });
+        }
+    }
+
+    let step_run = |run: &RunRecord, result: &ExecutionResult| StepRun {
+        run_id: run.summary.id.clone(),
+        started_at: run.summary.started_at,
+        status: result.status,
+        response_status: result.response_status,
+        duration_ms: result.duration_ms,
+    };
+
+    Ok(StepDiff {
+        step_name: step_name.to_string(),
+        before: step_run(before, old),
+        after: step_run(after, new),
+        duration_delta_ms: new.duration_ms as i64 - old.duration_ms as i64,
+        changes,
+    })
+}
+
+fn find_step<'a>(run: &'a RunRecord, step_name: &str) -> Result<&'a ExecutionResult> {
+    run.find_step(step_name)
+        .ok_or_else(|| Error::Validation(format!("执行记录 '{}' 中没有步骤 '{}'", run.summary.id, step_name)))
+}
+
+fn json<T: Serialize>(value: &T) -> JsonValue {
+    serde_json::to_value(value).unwrap_or(JsonValue::Null)
+}
+
+/// 递归比较：对象按键、数组按下标，其余整体比较
+fn diff_value(section: &str, before: &JsonValue, after: &JsonValue, changes: &mut Vec<Change>) {
+    fn walk(section: &str, path: String, before: Option<&JsonValue>, after: Option<&JsonValue>, changes: &mut Vec<Change>) {
+        match (before, after) {
+            (Some(JsonValue::Object(a)), Some(JsonValue::Object(b))) => {
+                for key in a.keys().chain(b.keys().filter(|k| !a.contains_key(*k))) {
+                    let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    walk(section, child, a.get(key), b.get(key), changes);
+                }
+            }
+            (Some(JsonValue::Array(a)), Some(JsonValue::Array(b))) => {
+                for i in 0..a.len().max(b.len()) {
+                    walk(section, format!("{}[{}]", path, i), a.get(i), b.get(i), changes);
+                }
+            }
+            (a, b) if a != b => changes.push(Change {
+                section: section.to_string(),
+                path,
+                before: a.filter(|v| !v.is_null()).cloned(),
+                after: b.filter(|v| !v.is_null()).cloned(),
+            }),
+            _ => {}
+        }
+    }
+    walk(section, String::new(), Some(before), Some(after), changes);
+}
+
+// ============ Tauri 命令 ============
+
+/// 列出执行记录，最新的在前
+#[tauri::command]
+pub fn list_runs(filter: Option<RunFilter>) -> Result<Vec<RunSummary>> {
+    History::open()?.list(&filter.unwrap_or_default())
+}
+
+/// 读取一次执行的完整记录
+#[tauri::command]
+pub fn load_run(run_id: String) -> Result<RunRecord> {
+    History::open()?.load(&run_id)
+}
+
+/// 删除执行记录，返回删除的条数
+#[tauri::command]
+pub fn delete_runs(run_ids: Vec<String>) -> Result<usize> {
+    History::open()?.delete(&run_ids)
+}
+
+/// 清理旧的执行记录，返回删除的条数
+#[tauri::command]
+pub fn prune_runs(keep_per_file: Option<usize>, older_than_days: Option<u32>) -> Result<usize> {
+    History::open()?.prune(keep_per_file, older_than_days)
+}
+
+/// 比较两次执行中同一步骤的结果
+#[tauri::command]
+pub fn diff_step_runs(before_run: String, after_run: String, step_name: String) -> Result<StepDiff> {
+    let history = History::open()?;
+    diff_step(&history.load(&before_run)?, &history.load(&after_run)?, &step_name)
+}
@@ -1,57 +1,175 @@
 //! HTTP 客户端封装
 
-use reqwest::{Client, StatusCode};
+use indexmap::IndexMap;
+use reqwest::{Client, Method, StatusCode};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 
 use crate::cache::Cache;
-use crate::config::AuthConfig;
+use crate::config::{AuthConfig, ErrorKind, TimeoutConfig};
+use crate::error::Result;
+use crate::secrets::Secrets;
+
+/// 未配置超时时的默认总超时（毫秒）
+const DEFAULT_TOTAL_TIMEOUT_MS: u64 = 30_000;
+
+/// 响应数据：(状态码, 实际发送的请求头, 响应头, 响应体)
+pub type HttpResponse = (StatusCode, IndexMap<String, String>, IndexMap<String, String>, Value);
+
+/// 请求错误
+#[derive(Debug, Clone)]
+pub struct HttpError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl HttpError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+
+    fn from_reqwest(e: reqwest::Error) -> Self {
+        let kind = if e.is_timeout() {
+            ErrorKind::Timeout
+        } else if e.is_connect() {
+            ErrorKind::Connection
+        } else if e.is_builder() {
+            ErrorKind::InvalidRequest
+        } else {
+            ErrorKind::Network
+        };
+        // reqwest 的错误信息只有外层描述，拼上底层原因便于排查
+        let mut message = e.to_string();
+        let mut source = std::error::Error::source(&e);
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        Self::new(kind, message)
+    }
+}
+
+/// 认证 token 的放置位置：(键名, 值)
+#[derive(Debug, Clone)]
+pub enum AuthPlacement {
+    Header(String, String),
+    Query(String, String),
+}
 
 pub struct HttpClient {
     client: Client,
     base_url: String,
     auth_config: Option<AuthConfig>,
-    cache: Cache,
+    cache: Arc<Cache>,
+    timeout: TimeoutConfig,
+    /// 步骤覆盖了连接 / 读取超时时使用的客户端，按合并后的超时配置复用
+    step_clients: Mutex<HashMap<TimeoutConfig, Client>>,
     debug: bool,
+    /// 用于解析认证配置中的密钥引用和调试输出脱敏
+    secrets: Arc<Secrets>,
 }
 
 impl HttpClient {
-    pub fn new(base_url: String, auth_config: Option<AuthConfig>, debug: bool) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+    pub fn new(
+        base_url: String,
+        auth_config: Option<AuthConfig>,
+        timeout: TimeoutConfig,
+        debug: bool,
+    ) -> Result<Self> {
+        Self::with_cache(base_url, auth_config, timeout, debug, Arc::new(Cache::new()))
+    }
 
-        Self {
+    /// 使用共享的 token 缓存创建客户端，多个客户端之间复用已获取的 token
+    pub fn with_cache(
+        base_url: String,
+        auth_config: Option<AuthConfig>,
+        timeout: TimeoutConfig,
+        debug: bool,
+        cache: Arc<Cache>,
+    ) -> Result<Self> {
+        let client = build_client(&timeout)?;
+
+        Ok(Self {
             client,
             base_url,
             auth_config,
-            cache: Cache::new(),
+            cache,
+            timeout,
+            step_clients: Mutex::new(HashMap::new()),
             debug,
-        }
+            secrets: Arc::new(Secrets::default()),
+        })
     }
 
-    /// 获取 token
-    async fn get_token(&self) -> Option<String> {
+    /// 设置密钥，获取 token 时替换认证配置中的 `{secret:名称}`
+    pub fn set_secrets(&mut self, secrets: Arc<Secrets>) {
+        self.secrets = secrets;
+    }
+
+    /// 缓存中已有的 token，不发起请求
+    pub fn cached_token(&self) -> Option<String> {
         let auth = self.auth_config.as_ref()?;
-        
-        if auth.token_url.is_empty() {
-            return None;
-        }
+        self.cache
+            .get(token_cache_key(auth))?
+            .get("access_token")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    }
 
-        // 先检查缓存
-        let cache_key = if auth.token_cache_key.is_empty() {
-            "default_token"
+    /// 计算 token 的放置位置，未配置认证时返回 `None`
+    pub fn auth_placement(&self, token: &str) -> Option<AuthPlacement> {
+        let auth = self.auth_config.as_ref().filter(|a| !a.token_url.is_empty())?;
+        let auth_position = if auth.auth_position.is_empty() {
+            "header"
         } else {
-            &auth.token_cache_key
+            &auth.auth_position
         };
 
-        if let Some(cached) = self.cache.get(cache_key) {
-            if let Some(token) = cached.get("access_token").and_then(|v| v.as_str()) {
-                return Some(token.to_string());
+        match auth_position {
+            "header" => {
+                let key = if auth.auth_key.is_empty() {
+                    "Authorization"
+                } else {
+                    &auth.auth_key
+                };
+                let prefix = if auth.auth_prefix.is_empty() {
+                    "Bearer"
+                } else {
+                    &auth.auth_prefix
+                };
+                Some(AuthPlacement::Header(key.to_string(), format!("{} {}", prefix, token)))
+            }
+            "query" => {
+                let key = if auth.auth_key.is_empty() {
+                    "access_token"
+                } else {
+                    &auth.auth_key
+                };
+                Some(AuthPlacement::Query(key.to_string(), token.to_string()))
             }
+            _ => None,
         }
+    }
+
+    /// 获取 token（优先使用缓存），未配置认证时返回 `None`
+    pub(crate) async fn get_token(&self) -> std::result::Result<Option<String>, HttpError> {
+        let Some(auth) = self.auth_config.as_ref() else {
+            return Ok(None);
+        };
+        
+        if auth.token_url.is_empty() {
+            return Ok(None);
+        }
+
+        // 先检查缓存
+        if let Some(token) = self.cached_token() {
+            return Ok(Some(token));
+        }
+        let cache_key = token_cache_key(auth);
+        let auth = self.secrets.resolve_auth(auth);
 
         // 请求新 token
         let mut form = HashMap::new();
@@ -60,45 +178,44 @@ impl HttpClient {
         form.insert("username", auth.username.as_str());
         form.insert("password", auth.password.as_str());
 
+        let auth_error = |message: String| HttpError::new(ErrorKind::Auth, message);
+
         let response = self.client
             .post(&auth.token_url)
             .form(&form)
             .send()
             .await
-            .ok()?;
-
-        if response.status().is_success() {
-            let token_data: Value = response.json().await.ok()?;
-            
-            // 缓存 token
-            self.cache.set(cache_key, token_data.clone(), Some(3600));
-            
-            token_data.get("access_token")
-                .and_then(|v| v.as_str())
-                .map(|s| s.to_string())
-        } else {
-            None
+            .map_err(|e| auth_error(format!("请求 token 失败: {}", HttpError::from_reqwest(e).message)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(auth_error(format!("获取 token 失败: HTTP {}", status.as_u16())));
         }
+
+        let token_data: Value = response
+            .json()
+            .await
+            .map_err(|e| auth_error(format!("token 响应解析失败: {}", e)))?;
+        let token = token_data
+            .get("access_token")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| auth_error("token 响应中没有 access_token".to_string()))?;
+
+        // 缓存 token
+        self.cache.set(cache_key, token_data, Some(3600));
+
+        Ok(Some(token))
     }
 
     /// 发送 GET 请求
     pub async fn get(
         &self,
         path: &str,
-        params: Option<&HashMap<String, String>>,
-        headers: Option<&HashMap<String, String>>,
-    ) -> Result<(StatusCode, HashMap<String, String>, HashMap<String, String>, Value), String> {
-        let url = format!("{}{}", self.base_url, path);
-        
-        let mut request = self.client.get(&url);
-        
-        if let Some(p) = params {
-            request = request.query(p);
-        }
-        
-        let (request, request_headers) = self.apply_headers(request, headers).await;
-        
-        self.execute_request(request, request_headers, &url, "GET").await
+        params: Option<&IndexMap<String, String>>,
+        headers: Option<&IndexMap<String, String>>,
+    ) -> std::result::Result<HttpResponse, HttpError> {
+        self.request(Method::GET, path, None, params, headers, &TimeoutConfig::default()).await
     }
 
     /// 发送 POST 请求
@@ -106,30 +223,10 @@ impl HttpClient {
         &self,
         path: &str,
         body: Option<&Value>,
-        params: Option<&HashMap<String, String>>,
-        headers: Option<&HashMap<String, String>>,
-    ) -> Result<(StatusCode, HashMap<String, String>, HashMap<String, String>, Value), String> {
-        let url = format!("{}{}", self.base_url, path);
-        
-        let mut request = self.client.post(&url);
-        
-        if let Some(p) = params {
-            request = request.query(p);
-        }
-        
-        let has_body = body.is_some();
-        if let Some(b) = body {
-            request = request.json(b);
-        }
-        
-        let (request, mut request_headers) = self.apply_headers(request, headers).await;
-        
-        // 如果使用了 .json()，记录 Content-Type (reqwest 会自动添加)
-        if has_body && !request_headers.contains_key("Content-Type") && !request_headers.contains_key("content-type") {
-            request_headers.insert("content-type".to_string(), "application/json".to_string());
-        }
-        
-        self.execute_request(request, request_headers, &url, "POST").await
+        params: Option<&IndexMap<String, String>>,
+        headers: Option<&IndexMap<String, String>>,
+    ) -> std::result::Result<HttpResponse, HttpError> {
+        self.request(Method::POST, path, body, params, headers, &TimeoutConfig::default()).await
     }
 
     /// 发送 PUT 请求
@@ -137,59 +234,85 @@ impl HttpClient {
         &self,
         path: &str,
         body: Option<&Value>,
-        params: Option<&HashMap<String, String>>,
-        headers: Option<&HashMap<String, String>>,
-    ) -> Result<(StatusCode, HashMap<String, String>, HashMap<String, String>, Value), String> {
+        params: Option<&IndexMap<String, String>>,
+        headers: Option<&IndexMap<String, String>>,
+    ) -> std::result::Result<HttpResponse, HttpError> {
+        self.request(Method::PUT, path, body, params, headers, &TimeoutConfig::default()).await
+    }
+
+    /// 发送 DELETE 请求
+    pub async fn delete(
+        &self,
+        path: &str,
+        params: Option<&IndexMap<String, String>>,
+        headers: Option<&IndexMap<String, String>>,
+    ) -> std::result::Result<HttpResponse, HttpError> {
+        self.request(Method::DELETE, path, None, params, headers, &TimeoutConfig::default()).await
+    }
+
+    /// 发送请求，`timeout` 为步骤级别的超时覆盖
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+        params: Option<&IndexMap<String, String>>,
+        headers: Option<&IndexMap<String, String>>,
+        timeout: &TimeoutConfig,
+    ) -> std::result::Result<HttpResponse, HttpError> {
         let url = format!("{}{}", self.base_url, path);
-        
-        let mut request = self.client.put(&url);
-        
+
+        // 只覆盖总超时时可以按请求设置；连接、读取超时属于客户端配置，需要单独的客户端
+        let client = if timeout.connect_ms.is_some() || timeout.read_ms.is_some() {
+            self.step_client(timeout)?
+        } else {
+            self.client.clone()
+        };
+
+        let mut request = client.request(method.clone(), &url);
+
+        if let Some(ms) = timeout.total_ms {
+            request = request.timeout(Duration::from_millis(ms));
+        }
+
         if let Some(p) = params {
             request = request.query(p);
         }
-        
+
         let has_body = body.is_some();
         if let Some(b) = body {
             request = request.json(b);
         }
-        
-        let (request, mut request_headers) = self.apply_headers(request, headers).await;
-        
+
+        let (request, mut request_headers) = self.apply_headers(request, headers).await?;
+
         // 如果使用了 .json()，记录 Content-Type (reqwest 会自动添加)
         if has_body && !request_headers.contains_key("Content-Type") && !request_headers.contains_key("content-type") {
             request_headers.insert("content-type".to_string(), "application/json".to_string());
         }
-        
-        self.execute_request(request, request_headers, &url, "PUT").await
+
+        self.execute_request(request, request_headers, &url, method.as_str()).await
     }
 
-    /// 发送 DELETE 请求
-    pub async fn delete(
-        &self,
-        path: &str,
-        params: Option<&HashMap<String, String>>,
-        headers: Option<&HashMap<String, String>>,
-    ) -> Result<(StatusCode, HashMap<String, String>, HashMap<String, String>, Value), String> {
-        let url = format!("{}{}", self.base_url, path);
-        
-        let mut request = self.client.delete(&url);
-        
-        if let Some(p) = params {
-            request = request.query(p);
+    /// 步骤级别超时对应的客户端，相同配置的步骤共用连接池
+    fn step_client(&self, timeout: &TimeoutConfig) -> std::result::Result<Client, HttpError> {
+        let merged = self.timeout.merge(timeout);
+        let mut clients = self.step_clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(&merged) {
+            return Ok(client.clone());
         }
-        
-        let (request, request_headers) = self.apply_headers(request, headers).await;
-        
-        self.execute_request(request, request_headers, &url, "DELETE").await
+        let client = build_client(&merged)?;
+        clients.insert(merged, client.clone());
+        Ok(client)
     }
 
     /// 应用请求头和认证，返回 (request, 完整的请求头)
     async fn apply_headers(
         &self,
         mut request: reqwest::RequestBuilder,
-        custom_headers: Option<&HashMap<String, String>>,
-    ) -> (reqwest::RequestBuilder, HashMap<String, String>) {
-        let mut all_headers: HashMap<String, String> = HashMap::new();
+        custom_headers: Option<&IndexMap<String, String>>,
+    ) -> std::result::Result<(reqwest::RequestBuilder, IndexMap<String, String>), HttpError> {
+        let mut all_headers: IndexMap<String, String> = IndexMap::new();
         
         // 添加自定义 headers
         if let Some(headers) = custom_headers {
@@ -200,76 +323,102 @@ impl HttpClient {
         }
         
         // 添加认证 token
-        if let Some(token) = self.get_token().await {
-            if let Some(auth) = &self.auth_config {
-                let auth_position = if auth.auth_position.is_empty() {
-                    "header"
-                } else {
-                    &auth.auth_position
-                };
-                
-                match auth_position {
-                    "header" => {
-                        let key = if auth.auth_key.is_empty() {
-                            "Authorization"
-                        } else {
-                            &auth.auth_key
-                        };
-                        let prefix = if auth.auth_prefix.is_empty() {
-                            "Bearer"
-                        } else {
-                            &auth.auth_prefix
-                        };
-                        let value = format!("{} {}", prefix, token);
-                        request = request.header(key, value.clone());
-                        all_headers.insert(key.to_string(), value);
-                    }
-                    "query" => {
-                        let key = if auth.auth_key.is_empty() {
-                            "access_token"
-                        } else {
-                            &auth.auth_key
-                        };
-                        request = request.query(&[(key, &token)]);
-                        // query 参数不加到 headers 中
-                    }
-                    _ => {}
+        if let Some(token) = self.get_token().await? {
+            match self.auth_placement(&token) {
+                Some(AuthPlacement::Header(key, value)) => {
+                    request = request.header(&key, &value);
+                    all_headers.insert(key, value);
                 }
+                Some(AuthPlacement::Query(key, value)) => {
+                    // query 参数不加到 headers 中
+                    request = request.query(&[(key, value)]);
+                }
+                None => {}
             }
         }
         
-        (request, all_headers)
+        Ok((request, all_headers))
     }
 
     /// 执行请求
     async fn execute_request(
         &self,
         request: reqwest::RequestBuilder,
-        request_headers: HashMap<String, String>,
+        request_headers: IndexMap<String, String>,
         url: &str,
         method: &str,
-    ) -> Result<(StatusCode, HashMap<String, String>, HashMap<String, String>, Value), String> {
+    ) -> std::result::Result<HttpResponse, HttpError> {
         if self.debug {
-            println!("🔍 [DEBUG] {} {}", method, url);
+            println!("🔍 [DEBUG] {} {}", method, self.secrets.mask(url));
         }
 
-        let response = request.send().await.map_err(|e| e.to_string())?;
+        let response = request.send().await.map_err(HttpError::from_reqwest)?;
         let status = response.status();
         
         // 提取响应头
-        let response_headers: HashMap<String, String> = response
+        let response_headers: IndexMap<String, String> = response
             .headers()
             .iter()
             .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
             .collect();
         
-        let body: Value = response.json().await.unwrap_or(Value::Null);
+        // 读取响应体同样受读取超时和总超时限制；非 JSON 响应体记为 null
+        let bytes = response.bytes().await.map_err(HttpError::from_reqwest)?;
+        let body: Value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
         
         if self.debug {
             println!("🔍 [DEBUG] Status: {}", status);
-            println!("🔍 [DEBUG] Response: {}", serde_json::to_string_pretty(&body).unwrap_or_default());
+            println!("🔍 [DEBUG] Response: {}", serde_json::to_string_pretty(&self.secrets.mask_json(&body)).unwrap_or_default());
         }
         
         Ok((status, request_headers, response_headers, body))
     }
 }
+
+fn token_cache_key(auth: &AuthConfig) -> &str {
+    if auth.token_cache_key.is_empty() {
+        "default_token"
+    } else {
+        &auth.token_cache_key
+    }
+}
+
+/// 按超时配置创建 reqwest 客户端
+fn build_client(timeout: &TimeoutConfig) -> std::result::Result<Client, HttpError> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_millis(timeout.total_ms.unwrap_or(DEFAULT_TOTAL_TIMEOUT_MS)));
+
+    if let Some(ms) = timeout.connect_ms {
+        builder = builder.connect_timeout(Duration::from_millis(ms));
+    }
+    if let Some(ms) = timeout.read_ms {
+        builder = builder.read_timeout(Duration::from_millis(ms));
+    }
+
+    builder
+        .build()
+        .map_err(|e| HttpError::new(ErrorKind::InvalidRequest, format!("创建 HTTP 客户端失败: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeout(connect_ms: Option<u64>, read_ms: Option<u64>) -> TimeoutConfig {
+        TimeoutConfig { connect_ms, total_ms: None, read_ms }
+    }
+
+    #[test]
+    fn step_clients_are_reused_per_merged_timeout() {
+        let client = HttpClient::new(String::new(), None, timeout(Some(1000), None), false).unwrap();
+
+        client.step_client(&timeout(None, Some(500))).unwrap();
+        client.step_client(&timeout(None, Some(500))).unwrap();
+        // 合并后与上一个配置相同
+        client.step_client(&timeout(Some(1000), Some(500))).unwrap();
+        assert_eq!(client.step_clients.lock().unwrap().len(), 1);
+
+        client.step_client(&timeout(Some(200), Some(500))).unwrap();
+        assert_eq!(client.step_clients.lock().unwrap().len(), 2);
+    }
+}
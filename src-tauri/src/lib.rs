@@ -2,19 +2,54 @@
 //! 
 //! Rust 重写的测试引擎，支持：
 //! - YAML 配置文件解析
+//! - 跨文件导入 (imports)
+//! - 配置静态检查
 //! - 环境切换 (profile)
-//! - 变量替换
+//! - 变量替换与表达式求值
 //! - 路径映射
+//! - 测试用例与标签筛选
+//! - 测试用例并行执行
+//! - 压测模式
+//! - 取消正在进行的执行
+//! - 执行进度事件
+//! - curl 命令导入导出
+//! - 从 OpenAPI 文档生成测试，由步骤导出 OpenAPI 文档
+//! - Postman 集合导入导出
+//! - 从 HAR 抓包生成步骤
+//! - JUnit XML、HTML、Allure 报告
+//! - 命令行执行（CI）
+//! - 执行历史与结果比较
+//! - 通过率统计与不稳定测试检测
+//! - 加密密钥库与 `{secret:名称}` 引用
 //! - HTTP 请求执行
 
 pub mod engine;
+pub mod expr;
 pub mod config;
+pub mod error;
+pub mod loader;
+pub mod lint;
 pub mod http_client;
 pub mod cache;
 pub mod commands;
+pub mod convert;
+pub mod report;
+pub mod cli;
 pub mod storage;
+pub mod history;
+pub mod analytics;
+pub mod secrets;
+pub mod runner;
+pub mod load;
+pub mod execution;
+pub mod events;
+pub mod source_map;
+pub mod tags;
+pub mod mask;
+pub mod random;
 
 pub use engine::TestEngine;
 pub use config::*;
+pub use error::Error;
 pub use commands::*;
 pub use storage::*;
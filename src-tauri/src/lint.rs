@@ -0,0 +1,307 @@
+//! 配置文件静态检查
+//!
+//! 不发送请求，只检查配置本身：激活的环境是否存在、测试用例引用的步骤是否存在、
+//! 验证操作符和 HTTP 方法是否支持、断言能否解析、占位符引用的变量是否有来源等。
+//! 诊断位置通过 [`source_map`] 从原文中定位。
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::config::*;
+use crate::error::{Error, Result};
+use crate::expr;
+use crate::loader;
+use crate::source_map;
+
+/// 引擎初始化时写入上下文的变量
+const BUILTIN_VARIABLES: &[&str] = &["brand", "timestamp"];
+
+/// 诊断级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// 执行时必然出错
+    Error,
+    /// 可能有问题，例如变量需要运行时从外部传入
+    Warning,
+}
+
+/// 诊断信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// 键路径，如 `steps.login.validate.0.operator`
+    pub path: String,
+    /// 所在文件，来自 imports 的步骤为被导入的文件
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// 检查配置文件
+pub fn lint_file(path: &Path) -> Result<Vec<Diagnostic>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::io("无法读取配置文件", path, e))?;
+
+    Ok(lint_str(&content, Some(path)))
+}
+
+/// 检查 YAML 内容，`origin` 为对应的文件路径（用于解析 imports）
+///
+/// 配置无法加载时返回一条错误诊断
+pub fn lint_str(content: &str, origin: Option<&Path>) -> Vec<Diagnostic> {
+    match loader::load_config_str(content, origin) {
+        Ok(config) => lint_config(&config, content, origin),
+        Err(Error::ConfigParse { message, file, line, column, path }) => vec![Diagnostic {
+            severity: Severity::Error,
+            message,
+            path: path.unwrap_or_default(),
+            file,
+            line,
+            column,
+        }],
+        Err(e) => vec![Diagnostic {
+            severity: Severity::Error,
+            message: e.to_string(),
+            path: String::new(),
+            file: origin.map(|p| p.display().to_string()),
+            line: None,
+            column: None,
+        }],
+    }
+}
+
+/// 检查已加载的配置，`content` 为主文件的原文
+pub fn lint_config(config: &TestConfig, content: &str, origin: Option<&Path>) -> Vec<Diagnostic> {
+    let mut linter = Linter {
+        config,
+        content,
+        file: origin.map(|p| p.to_string_lossy().to_string()),
+        diagnostics: Vec::new(),
+    };
+
+    linter.check_profile();
+    linter.check_test_cases();
+    linter.check_steps();
+
+    linter.diagnostics
+}
+
+struct Linter<'a> {
+    config: &'a TestConfig,
+    content: &'a str,
+    file: Option<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn push(&mut self, severity: Severity, message: String, path: &[&str]) {
+        let (file, position) = self.locate(path);
+        self.diagnostics.push(Diagnostic {
+            severity,
+            message,
+            path: path.join("."),
+            file,
+            line: position.map(|p| p.line),
+            column: position.map(|p| p.column),
+        });
+    }
+
+    /// 定位键路径；来自 imports 的步骤只能定位到步骤所在行
+    fn locate(&self, path: &[&str]) -> (Option<String>, Option<source_map::Position>) {
+        if let ["steps", id, ..] = path {
+            if let Some(source) = self.config.step_sources.get(*id) {
+                if source.file != self.file {
+                    let position = source.line.map(|line| source_map::Position { line, column: 1 });
+                    return (source.file.clone(), position);
+                }
+            }
+        }
+
+        (self.file.clone(), source_map::locate_nearest(self.content, path))
+    }
+
+    fn check_profile(&mut self) {
+        let profile = &self.config.global.profile;
+
+        if profile.active.is_empty() {
+            self.push(Severity::Error, "未配置 profile.active".to_string(), &["global", "profile", "active"]);
+        } else if !profile.environments.contains_key(&profile.active) {
+            let available: Vec<&str> = profile.environments.keys().map(String::as_str).collect();
+            self.push(
+                Severity::Error,
+                format!("环境 '{}' 配置不存在，可用的环境: {}", profile.active, available.join(", ")),
+                &["global", "profile", "active"],
+            );
+        }
+
+        // 配置文件通常会提交到仓库，密码应放在密钥库中
+        for (name, env) in &profile.environments {
+            let plaintext = env.auth.as_ref().is_some_and(|a| !a.password.is_empty() && !a.password.contains("{secret:"));
+            if plaintext {
+                self.push(
+                    Severity::Warning,
+                    format!("环境 '{}' 的认证密码以明文保存，建议改用 {{secret:名称}} 引用密钥库", name),
+                    &["global", "profile", name, "auth", "password"],
+                );
+            }
+        }
+    }
+
+    fn check_test_cases(&mut self) {
+        for (case_id, case) in &self.config.test_cases {
+            if case.steps.is_empty() {
+                self.push(Severity::Warning, format!("测试用例 '{}' 没有步骤", case_id), &["test_cases", case_id]);
+            }
+
+            for (i, step_id) in case.steps.iter().enumerate() {
+                if !self.config.steps.contains_key(step_id) {
+                    let index = i.to_string();
+                    self.push(
+                        Severity::Error,
+                        format!("测试用例 '{}' 引用的步骤 '{}' 不存在", case_id, step_id),
+                        &["test_cases", case_id, "steps", &index],
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_steps(&mut self) {
+        // 任何步骤保存的响应字段在运行时都可能进入上下文
+        let saved: HashSet<&str> = self.config.steps
+            .values()
+            .flat_map(|step| step.save_response.iter().map(|rule| rule.to.as_str()))
+            .collect();
+
+        for (id, step) in &self.config.steps {
+            if !HTTP_METHODS.contains(&step.method.to_uppercase().as_str()) {
+                self.push(
+                    Severity::Error,
+                    format!("不支持的 HTTP 方法: {}", step.method),
+                    &["steps", id, "method"],
+                );
+            }
+
+            for (i, validation) in step.validate.iter().enumerate() {
+                if !VALIDATION_OPERATORS.contains(&validation.operator.as_str()) {
+                    let index = i.to_string();
+                    self.push(
+                        Severity::Error,
+                        format!(
+                            "未知的验证操作符 '{}'，支持: {}",
+                            validation.operator,
+                            VALIDATION_OPERATORS.join(", ")
+                        ),
+                        &["steps", id, "validate", &index, "operator"],
+                    );
+                }
+            }
+
+            for (i, assertion) in step.asserts.iter().enumerate() {
+                if let Err(e) = expr::parse(assertion) {
+                    let index = i.to_string();
+                    self.push(Severity::Error, e, &["steps", id, "assert", &index]);
+                }
+            }
+
+            self.check_placeholders(id, step, &saved);
+        }
+    }
+
+    /// 检查占位符引用的变量是否有来源
+    fn check_placeholders(&mut self, id: &str, step: &Step, saved: &HashSet<&str>) {
+        let mut known: HashSet<&str> = BUILTIN_VARIABLES.iter().copied().collect();
+        known.extend(saved.iter().copied());
+        known.extend(self.config.variables.keys().map(String::as_str));
+        known.extend(step.variables.keys().map(String::as_str));
+        for case in self.config.test_cases.values().filter(|c| c.steps.iter().any(|s| s == id)) {
+            known.extend(case.variables.keys().map(String::as_str));
+        }
+
+        let mut strings = vec![(vec!["path".to_string()], step.path.clone())];
+        for (key, value) in &step.headers {
+            strings.push((vec!["headers".to_string(), key.clone()], value.clone()));
+        }
+        for (key, value) in &step.params {
+            collect_strings(value, vec!["params".to_string(), key.clone()], &mut strings);
+        }
+        if let Some(body) = &step.body {
+            collect_strings(body, vec!["body".to_string()], &mut strings);
+        }
+
+        let re = Regex::new(r"\{([^}]+)\}").unwrap();
+        for (sub_path, text) in strings {
+            for caps in re.captures_iter(&text) {
+                let placeholder = &caps[1];
+                // 密钥在执行时从密钥库解析，静态检查无法确认是否存在
+                if placeholder.starts_with("secret:") {
+                    continue;
+                }
+                let path: Vec<&str> = ["steps", id]
+                    .into_iter()
+                    .chain(sub_path.iter().map(String::as_str))
+                    .collect();
+
+                let variables = match placeholder_variables(placeholder) {
+                    Ok(variables) => variables,
+                    Err(e) => {
+                        self.push(
+                            Severity::Warning,
+                            format!("占位符 '{}' 无法解析，将按原文发送: {}", &caps[0], e),
+                            &path,
+                        );
+                        continue;
+                    }
+                };
+
+                for name in variables.iter().filter(|name| !known.contains(name.as_str())) {
+                    self.push(
+                        Severity::Warning,
+                        format!("占位符 '{}' 引用的变量 '{}' 未定义，需要在执行时传入", &caps[0], name),
+                        &path,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// 占位符引用的变量：简单路径（`user.name`）取第一段，否则按表达式解析
+fn placeholder_variables(placeholder: &str) -> std::result::Result<Vec<String>, String> {
+    let is_path = placeholder
+        .split('.')
+        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_'));
+    if is_path {
+        let root = placeholder.split('.').next().unwrap_or_default();
+        return Ok(vec![root.to_string()]);
+    }
+
+    expr::parse(placeholder).map(|e| e.variables())
+}
+
+/// 收集 YAML 值中的所有字符串及其键路径
+fn collect_strings(value: &serde_yaml::Value, path: Vec<String>, out: &mut Vec<(Vec<String>, String)>) {
+    match value {
+        serde_yaml::Value::String(s) => out.push((path, s.clone())),
+        serde_yaml::Value::Sequence(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let mut child = path.clone();
+                child.push(i.to_string());
+                collect_strings(item, child, out);
+            }
+        }
+        serde_yaml::Value::Mapping(map) => {
+            for (key, item) in map {
+                let mut child = path.clone();
+                child.push(key.as_str().map(str::to_string).unwrap_or_else(|| format!("{:?}", key)));
+                collect_strings(item, child, out);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,322 @@
+//! 压测模式
+//!
+//! 用 N 个虚拟用户反复执行某个 step 或测试用例，统计吞吐量、错误率和延迟分布。
+//! 每个虚拟用户使用独立的引擎实例，共享 token 缓存；延迟取每次请求的 `duration_ms`。
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::cache::Cache;
+use crate::config::*;
+use crate::engine::TestEngine;
+use crate::error::{Error, Result};
+use crate::loader;
+use crate::secrets::Secrets;
+
+/// 虚拟用户数上限，每个虚拟用户都有独立的引擎和 HTTP 客户端
+pub const MAX_VIRTUAL_USERS: usize = 1000;
+
+/// 持续时间和逐步启动时间的上限（秒），即 24 小时
+pub const MAX_DURATION_SECS: u64 = 24 * 60 * 60;
+
+/// 压测目标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "name", rename_all = "snake_case")]
+pub enum LoadTarget {
+    Step(String),
+    TestCase(String),
+}
+
+/// 压测参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadOptions {
+    pub target: LoadTarget,
+    /// 虚拟用户数，最多 [`MAX_VIRTUAL_USERS`]
+    #[serde(default = "default_virtual_users")]
+    pub virtual_users: usize,
+    /// 持续时间（秒），与 iterations 至少指定一个，最多 [`MAX_DURATION_SECS`]
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+    /// 总迭代次数（所有虚拟用户合计）
+    #[serde(default)]
+    pub iterations: Option<u64>,
+    /// 在该时间内逐步启动全部虚拟用户（秒），最多 [`MAX_DURATION_SECS`]
+    #[serde(default)]
+    pub ramp_up_secs: u64,
+}
+
+fn default_virtual_users() -> usize { 1 }
+
+/// 延迟统计（毫秒）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+}
+
+/// 压测报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadReport {
+    pub target: LoadTarget,
+    pub virtual_users: usize,
+    /// 完成的迭代次数（一次 step 或一次完整的测试用例）
+    pub iterations: u64,
+    pub total_requests: u64,
+    pub successful: u64,
+    pub failed: u64,
+    /// 失败请求占比（0 ~ 1）
+    pub error_rate: f64,
+    /// 每秒请求数
+    pub throughput_rps: f64,
+    pub duration_ms: u64,
+    pub latency: LatencyStats,
+    /// 错误信息及出现次数
+    pub errors: IndexMap<String, u64>,
+}
+
+/// 单个虚拟用户的采样结果
+#[derive(Default)]
+struct Samples {
+    iterations: u64,
+    durations: Vec<u64>,
+    failed: u64,
+    errors: IndexMap<String, u64>,
+}
+
+impl Samples {
+    fn record(&mut self, result: &ExecutionResult) {
+        // 被取消的请求没有完成，不计入统计
+        if result.status == ExecutionStatus::Cancelled {
+            return;
+        }
+        self.durations.push(result.duration_ms);
+        if !result.success {
+            self.failed += 1;
+            let message = result.error.clone().unwrap_or_else(|| {
+                if result.response_status >= 400 {
+                    format!("HTTP {}", result.response_status)
+                } else {
+                    "验证失败".to_string()
+                }
+            });
+            *self.errors.entry(message).or_insert(0) += 1;
+        }
+    }
+
+    fn merge(&mut self, other: Samples) {
+        self.iterations += other.iterations;
+        self.durations.extend(other.durations);
+        self.failed += other.failed;
+        for (message, count) in other.errors {
+            *self.errors.entry(message).or_insert(0) += count;
+        }
+    }
+}
+
+/// 从配置文件运行压测
+pub async fn run_load_test_file(
+    config_path: &str,
+    options: LoadOptions,
+    cancel_token: CancellationToken,
+    secrets: Arc<Secrets>,
+) -> Result<LoadReport> {
+    let config = loader::load_config_file(Path::new(config_path))?;
+    run_load_test(config, options, cancel_token, secrets).await
+}
+
+/// 运行压测，`cancel_token` 取消后提前结束并返回已完成部分的统计；`secrets` 用于解析密钥引用
+pub async fn run_load_test(
+    config: TestConfig,
+    options: LoadOptions,
+    cancel_token: CancellationToken,
+    secrets: Arc<Secrets>,
+) -> Result<LoadReport> {
+    if options.duration_secs.is_none() && options.iterations.is_none() {
+        return Err(Error::Validation("压测需要指定 duration_secs 或 iterations".to_string()));
+    }
+    if options.virtual_users > MAX_VIRTUAL_USERS {
+        return Err(Error::Validation(format!("虚拟用户数不能超过 {}", MAX_VIRTUAL_USERS)));
+    }
+    if options.duration_secs.unwrap_or_default().max(options.ramp_up_secs) > MAX_DURATION_SECS {
+        return Err(Error::Validation(format!("持续时间和逐步启动时间不能超过 {} 秒", MAX_DURATION_SECS)));
+    }
+    match &options.target {
+        LoadTarget::Step(name) if !config.steps.contains_key(name) => {
+            return Err(Error::MissingStep(name.clone()));
+        }
+        LoadTarget::TestCase(name) if !config.test_cases.contains_key(name) => {
+            return Err(Error::MissingTestCase(name.clone()));
+        }
+        _ => {}
+    }
+
+    let virtual_users = options.virtual_users.max(1);
+    let user_count = u32::try_from(virtual_users)
+        .map_err(|_| Error::Validation(format!("虚拟用户数不能超过 {}", MAX_VIRTUAL_USERS)))?;
+    let token_cache = Arc::new(Cache::new());
+    let config = Arc::new(config);
+    let remaining = Arc::new(AtomicU64::new(options.iterations.unwrap_or(u64::MAX)));
+    let start = Instant::now();
+    let deadline = match options.duration_secs {
+        Some(secs) => {
+            Some(start.checked_add(Duration::from_secs(secs)).ok_or_else(|| Error::Validation("持续时间过长".to_string()))?)
+        }
+        None => None,
+    };
+    let ramp_step = if virtual_users > 1 {
+        Duration::from_secs(options.ramp_up_secs) / user_count
+    } else {
+        Duration::ZERO
+    };
+
+    let mut handles = Vec::new();
+    for vu in 0..user_count {
+        let mut engine = TestEngine::with_token_cache((*config).clone(), token_cache.clone())?;
+        engine.set_cancellation_token(cancel_token.clone());
+        engine.set_secrets(secrets.clone());
+        let cancel_token = cancel_token.clone();
+        let target = options.target.clone();
+        let remaining = remaining.clone();
+        let delay = ramp_step * vu;
+
+        handles.push(tokio::spawn(async move {
+            let mut samples = Samples::default();
+            tokio::select! {
+                _ = cancel_token.cancelled() => return samples,
+                _ = tokio::time::sleep(delay) => {}
+            }
+
+            loop {
+                if cancel_token.is_cancelled() || deadline.is_some_and(|d| Instant::now() >= d) {
+                    break;
+                }
+                // 领取一次迭代额度
+                if remaining
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                    .is_err()
+                {
+                    break;
+                }
+
+                match &target {
+                    LoadTarget::Step(name) => {
+                        let result = engine.execute_step(name, None).await;
+                        if result.status == ExecutionStatus::Cancelled {
+                            break;
+                        }
+                        samples.record(&result);
+                    }
+                    LoadTarget::TestCase(name) => {
+                        let result = engine.run_test_case(name, None).await;
+                        for step in &result.steps {
+                            samples.record(step);
+                        }
+                        if result.status == ExecutionStatus::Cancelled {
+                            break;
+                        }
+                    }
+                }
+                samples.iterations += 1;
+            }
+
+            samples
+        }));
+    }
+
+    let mut samples = Samples::default();
+    for handle in handles {
+        samples.merge(handle.await.map_err(|e| Error::Internal(format!("虚拟用户执行中断: {}", e)))?);
+    }
+
+    let elapsed = start.elapsed();
+    let total = samples.durations.len() as u64;
+
+    Ok(LoadReport {
+        target: options.target,
+        virtual_users,
+        iterations: samples.iterations,
+        total_requests: total,
+        successful: total - samples.failed,
+        failed: samples.failed,
+        error_rate: if total > 0 { samples.failed as f64 / total as f64 } else { 0.0 },
+        throughput_rps: if elapsed.as_secs_f64() > 0.0 { total as f64 / elapsed.as_secs_f64() } else { 0.0 },
+        duration_ms: elapsed.as_millis() as u64,
+        latency: latency_stats(&mut samples.durations),
+        errors: samples.errors,
+    })
+}
+
+/// 计算延迟统计，百分位使用最近秩法
+fn latency_stats(durations: &mut [u64]) -> LatencyStats {
+    if durations.is_empty() {
+        return LatencyStats::default();
+    }
+    durations.sort_unstable();
+
+    let percentile = |p: f64| {
+        let rank = (p / 100.0 * durations.len() as f64).ceil() as usize;
+        durations[rank.clamp(1, durations.len()) - 1]
+    };
+
+    LatencyStats {
+        min: durations[0],
+        max: durations[durations.len() - 1],
+        mean: durations.iter().sum::<u64>() as f64 / durations.len() as f64,
+        p50: percentile(50.0),
+        p90: percentile(90.0),
+        p99: percentile(99.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn percentiles(durations: &mut [u64]) -> (u64, u64, u64) {
+        let stats = latency_stats(durations);
+        (stats.p50, stats.p90, stats.p99)
+    }
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let mut durations: Vec<u64> = (1..=100).rev().collect();
+        assert_eq!(percentiles(&mut durations), (50, 90, 99));
+
+        // 秩向上取整：10 个样本的 p99 取第 10 个
+        let mut durations: Vec<u64> = (1..=10).map(|n| n * 10).collect();
+        assert_eq!(percentiles(&mut durations), (50, 90, 100));
+
+        let mut durations = vec![300, 100, 200];
+        assert_eq!(percentiles(&mut durations), (200, 300, 300));
+    }
+
+    #[test]
+    fn single_sample() {
+        let stats = latency_stats(&mut [42]);
+        assert_eq!((stats.min, stats.max, stats.p50, stats.p90, stats.p99), (42, 42, 42, 42, 42));
+        assert_eq!(stats.mean, 42.0);
+    }
+
+    #[test]
+    fn min_max_and_mean() {
+        let stats = latency_stats(&mut [30, 10, 20, 40]);
+        assert_eq!((stats.min, stats.max), (10, 40));
+        assert_eq!(stats.mean, 25.0);
+    }
+
+    #[test]
+    fn no_samples() {
+        let stats = latency_stats(&mut []);
+        assert_eq!((stats.min, stats.max, stats.p50, stats.p99), (0, 0, 0, 0));
+        assert_eq!(stats.mean, 0.0);
+    }
+}
@@ -0,0 +1,314 @@
+//! 配置加载与 imports 合并
+//!
+//! 被导入文件的路径相对于导入方文件解析，step / test case 会加上命名空间前缀
+//! （如 `auth.login`）。合并时导入方优先：同名的环境、变量、step 不会被覆盖。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::*;
+use crate::error::{Error, Result};
+use crate::source_map;
+
+/// 从文件加载配置，并递归合并 imports
+pub fn load_config_file(path: &Path) -> Result<TestConfig> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::io("无法读取配置文件", path, e))?;
+
+    load_config_str(&content, Some(path))
+}
+
+/// 从 YAML 字符串加载配置
+///
+/// `origin` 为该内容对应的文件路径，用于解析相对 imports；为 `None` 时相对当前目录解析。
+pub fn load_config_str(content: &str, origin: Option<&Path>) -> Result<TestConfig> {
+    let mut stack: Vec<PathBuf> = origin.map(canonical).into_iter().collect();
+    let config = parse_config(content, origin)?;
+    let config = resolve_imports(config, content, origin, &mut stack)?;
+
+    // global 可以来自 imports，合并后仍没有任何环境时才视为缺失
+    let has_global = serde_yaml::from_str::<serde_yaml::Value>(content)
+        .map(|root| root.get("global").is_some())
+        .unwrap_or(false);
+    if !has_global && config.global.profile.environments.is_empty() {
+        return Err(Error::config_parse_at(
+            "缺少 global 配置，需要在 global.profile 中定义环境",
+            origin,
+            Some(source_map::Position { line: 1, column: 1 }),
+            Some("global".to_string()),
+        ));
+    }
+
+    Ok(config)
+}
+
+fn parse_config(content: &str, origin: Option<&Path>) -> Result<TestConfig> {
+    serde_yaml::from_str(content).map_err(|e| parse_error(e, content, origin))
+}
+
+/// 将 serde_yaml 的错误转换为带行列和键路径的配置错误
+fn parse_error(e: serde_yaml::Error, content: &str, origin: Option<&Path>) -> Error {
+    let position = e
+        .location()
+        .map(|l| source_map::Position { line: l.line(), column: l.column() });
+
+    // 位置单独返回，去掉消息里的 "at line X column Y"
+    let mut message = e.to_string();
+    if let Some(p) = position {
+        message = message.replacen(&format!(" at line {} column {}", p.line, p.column), "", 1);
+    }
+
+    // 类型错误的消息以键路径开头，如 `steps.login.validate[0]: invalid type ...`；
+    // 语法错误没有路径，按出错的行反查
+    let path = match message.split_once(": ") {
+        Some((prefix, rest)) if !prefix.contains(' ') => {
+            let path = prefix.replace('[', ".").replace(']', "");
+            message = rest.to_string();
+            Some(path)
+        }
+        _ => position
+            .map(|p| source_map::path_at(content, p.line).join("."))
+            .filter(|path| !path.is_empty()),
+    };
+
+    Error::config_parse_at(format!("YAML 解析错误: {}", message), origin, position, path)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 解析并合并 `config` 的 imports，`stack` 记录当前导入链用于检测循环
+fn resolve_imports(
+    mut config: TestConfig,
+    content: &str,
+    origin: Option<&Path>,
+    stack: &mut Vec<PathBuf>,
+) -> Result<TestConfig> {
+    let file = origin.map(|p| p.to_string_lossy().to_string());
+    for id in config.steps.keys() {
+        let line = source_map::locate(content, &["steps", id]).map(|p| p.line);
+        config
+            .step_sources
+            .entry(id.clone())
+            .or_insert_with(|| StepSource { file: file.clone(), line });
+    }
+
+    let base_dir = origin
+        .and_then(|p| p.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    for (i, entry) in config.imports.clone().into_iter().enumerate() {
+        let import_path = base_dir.join(entry.path());
+        let key = canonical(&import_path);
+
+        if stack.contains(&key) {
+            let chain: Vec<String> = stack
+                .iter()
+                .chain(std::iter::once(&key))
+                .map(|p| p.display().to_string())
+                .collect();
+            let index = i.to_string();
+            return Err(Error::config_parse_at(
+                format!("检测到循环导入: {}", chain.join(" -> ")),
+                origin,
+                source_map::locate_nearest(content, &["imports", &index]),
+                Some(format!("imports.{}", i)),
+            ));
+        }
+
+        let content = fs::read_to_string(&import_path)
+            .map_err(|e| Error::io("无法读取导入文件", &import_path, e))?;
+        let imported = parse_config(&content, Some(&import_path))?;
+
+        stack.push(key);
+        let imported = resolve_imports(imported, &content, Some(&import_path), stack)?;
+        stack.pop();
+
+        config.imported_files.push(import_path);
+
+        merge_config(&mut config, imported, &entry.namespace());
+    }
+
+    Ok(config)
+}
+
+/// 给 id 加上命名空间前缀
+fn qualify(namespace: &str, id: &str) -> String {
+    if namespace.is_empty() {
+        id.to_string()
+    } else {
+        format!("{}.{}", namespace, id)
+    }
+}
+
+/// 将被导入的配置合并进目标配置，目标中已存在的条目优先
+fn merge_config(target: &mut TestConfig, imported: TestConfig, namespace: &str) {
+    target.global.debug |= imported.global.debug;
+    target.imported_files.extend(imported.imported_files);
+
+    let profile = &mut target.global.profile;
+    if profile.active.is_empty() {
+        profile.active = imported.global.profile.active;
+    }
+    for (name, env) in imported.global.profile.environments {
+        profile.environments.entry(name).or_insert(env);
+    }
+
+    for (name, value) in imported.variables {
+        target.variables.entry(name).or_insert(value);
+    }
+
+    for (id, mut case) in imported.test_cases {
+        // 用例中引用的是被导入文件内的 step，需要同样加上命名空间
+        case.steps = case
+            .steps
+            .iter()
+            .map(|s| {
+                if imported.steps.contains_key(s) {
+                    qualify(namespace, s)
+                } else {
+                    s.clone()
+                }
+            })
+            .collect();
+        target.test_cases.entry(qualify(namespace, &id)).or_insert(case);
+    }
+
+    for (id, step) in imported.steps {
+        let key = qualify(namespace, &id);
+        if target.steps.contains_key(&key) {
+            continue;
+        }
+        if let Some(source) = imported.step_sources.get(&id) {
+            target.step_sources.insert(key.clone(), source.clone());
+        }
+        target.steps.insert(key, step);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在临时目录中写入一组配置文件，返回目录
+    fn write_tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("yuml-ddt-{}-{}", name, std::process::id()));
+        for (path, content) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn load(dir: &Path) -> Result<TestConfig> {
+        let result = load_config_file(&dir.join("main.yml"));
+        fs::remove_dir_all(dir).ok();
+        result
+    }
+
+    const ENV: &str = "global:\n  profile:\n    active: dev\n    dev:\n      base_url: http://localhost\n";
+
+    #[test]
+    fn imports_resolve_relative_to_importing_file() {
+        let dir = write_tree(
+            "relative",
+            &[
+                ("main.yml", "imports:\n  - common/auth.yml\n"),
+                ("common/auth.yml", "imports:\n  - base.yml\nsteps:\n  login:\n    method: POST\n    path: /login\n"),
+                ("common/base.yml", ENV),
+            ],
+        );
+        let config = load(&dir).unwrap();
+
+        assert_eq!(config.global.profile.active, "dev");
+        assert_eq!(config.steps["auth.login"].path, "/login");
+    }
+
+    #[test]
+    fn diamond_imports_are_not_cycles() {
+        let shared = "steps:\n  ping:\n    method: GET\n    path: /ping\n";
+        let dir = write_tree(
+            "diamond",
+            &[
+                ("main.yml", &format!("imports:\n  - a.yml\n  - b.yml\n{}", ENV)),
+                ("a.yml", "imports:\n  - shared.yml\n"),
+                ("b.yml", "imports:\n  - shared.yml\n"),
+                ("shared.yml", shared),
+            ],
+        );
+        let config = load(&dir).unwrap();
+
+        let ids: Vec<_> = config.steps.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["a.shared.ping", "b.shared.ping"]);
+    }
+
+    #[test]
+    fn import_cycle_is_an_error() {
+        let dir = write_tree(
+            "cycle",
+            &[
+                ("main.yml", &format!("imports:\n  - a.yml\n{}", ENV)),
+                ("a.yml", "imports:\n  - b.yml\n"),
+                ("b.yml", "imports:\n  - ./a.yml\n"),
+            ],
+        );
+        let message = load(&dir).unwrap_err().to_string();
+
+        assert!(message.contains("检测到循环导入"), "{}", message);
+        // 导入链：main.yml -> a.yml -> b.yml -> a.yml
+        assert_eq!(message.matches("a.yml -> ").count(), 1, "{}", message);
+        assert_eq!(message.matches("b.yml -> ").count(), 1, "{}", message);
+        assert_eq!(message.matches(".yml -> ").count(), 3, "{}", message);
+    }
+
+    #[test]
+    fn self_import_is_an_error() {
+        let dir = write_tree("self", &[("main.yml", &format!("imports:\n  - main.yml\n{}", ENV))]);
+        assert!(load(&dir).unwrap_err().to_string().contains("检测到循环导入"));
+    }
+
+    #[test]
+    fn imported_ids_are_namespaced() {
+        let dir = write_tree(
+            "namespace",
+            &[
+                (
+                    "main.yml",
+                    &format!(
+                        "imports:\n  - lib.yml\n  - path: lib.yml\n    as: ''\n  - path: lib.yml\n    as: x\n\
+                         variables:\n  user: main\n{}steps:\n  logout:\n    method: POST\n    path: /main-logout\n",
+                        ENV
+                    ),
+                ),
+                (
+                    "lib.yml",
+                    "variables:\n  user: lib\n  token: t\n\
+                     steps:\n  login:\n    method: POST\n    path: /login\n  logout:\n    method: POST\n    path: /logout\n\
+                     test_cases:\n  flow:\n    steps:\n      - login\n      - setup\n",
+                ),
+            ],
+        );
+        let config = load(&dir).unwrap();
+
+        for id in ["lib.login", "lib.logout", "login", "x.login"] {
+            assert!(config.steps.contains_key(id), "{}", id);
+        }
+        // 导入方的同名定义优先
+        assert_eq!(config.steps["logout"].path, "/main-logout");
+        assert_eq!(config.variables["user"], "main");
+        assert_eq!(config.variables["token"], "t");
+        // 用例中只有被导入文件内的 step 加前缀
+        assert_eq!(config.test_cases["lib.flow"].steps, ["lib.login", "setup"]);
+        assert_eq!(config.test_cases["flow"].steps, ["login", "setup"]);
+        assert_eq!(config.test_cases["x.flow"].steps, ["x.login", "setup"]);
+    }
+
+    #[test]
+    fn qualify_skips_empty_namespace() {
+        assert_eq!(qualify("", "login"), "login");
+        assert_eq!(qualify("auth", "login"), "auth.login");
+    }
+}
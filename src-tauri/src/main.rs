@@ -1,20 +1,50 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use yuml_ddt_lib::cli;
+use yuml_ddt_lib::analytics;
 use yuml_ddt_lib::commands;
+use yuml_ddt_lib::execution::ExecutionRegistry;
+use yuml_ddt_lib::history;
+use yuml_ddt_lib::secrets::{self, VaultState};
 use yuml_ddt_lib::storage;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run_from_args(&args) {
+        std::process::exit(code);
+    }
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(ExecutionRegistry::new())
+        .manage(VaultState::new())
         .invoke_handler(tauri::generate_handler![
             commands::list_yaml_files,
             commands::read_yaml_file,
             commands::save_yaml_file,
             commands::parse_yaml_steps,
+            commands::lint_yaml,
             commands::execute_step,
+            commands::preview_step,
+            commands::export_curl,
+            commands::import_curl,
+            commands::import_openapi,
+            commands::import_postman,
+            commands::list_har_entries,
+            commands::import_har,
+            commands::export_postman,
+            commands::export_openapi,
             commands::get_step_list,
+            commands::get_test_case_list,
+            commands::run_test_case,
+            commands::run_test_cases,
+            commands::run_suite,
+            commands::export_report,
+            commands::run_load_test,
+            commands::run_tagged_steps,
+            commands::cancel_execution,
             // 存储相关命令
             storage::get_app_data,
             storage::save_app_data,
@@ -24,6 +54,21 @@ fn main() {
             storage::get_recent_workspaces,
             storage::update_settings,
             storage::get_settings,
+            // 执行历史
+            history::list_runs,
+            history::load_run,
+            history::delete_runs,
+            history::prune_runs,
+            history::diff_step_runs,
+            analytics::get_history_stats,
+            analytics::list_flaky_tests,
+            // 密钥库
+            secrets::get_vault_status,
+            secrets::unlock_vault,
+            secrets::lock_vault,
+            secrets::add_secret,
+            secrets::list_secrets,
+            secrets::remove_secret,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
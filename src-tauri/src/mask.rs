@@ -0,0 +1,76 @@
+//! 凭据脱敏
+//!
+//! 报告、curl 导出和密钥库共用同一套规则：名称中包含敏感词的请求头 / 查询参数视为凭据，
+//! 带认证前缀的值（如 `Bearer xxx`）只隐藏 token 部分，隐藏的内容统一替换为 [`MASK`]。
+
+use reqwest::Url;
+
+/// 脱敏后的取值
+pub const MASK: &str = "******";
+
+/// 名称中包含这些词的请求头 / 查询参数视为凭据
+const SENSITIVE_WORDS: &[&str] = &["authorization", "cookie", "token", "secret", "password", "api-key", "apikey"];
+
+/// 按名称判断并隐藏凭据
+#[derive(Debug, Clone, Default)]
+pub struct Masker {
+    keys: Vec<String>,
+}
+
+impl Masker {
+    /// `keys` 为额外需要脱敏的键（不区分大小写），如认证配置中放置 token 的键
+    pub fn new(keys: &[String]) -> Self {
+        Self { keys: keys.iter().map(|k| k.to_lowercase()).collect() }
+    }
+
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.keys.contains(&key) || SENSITIVE_WORDS.iter().any(|w| key.contains(w))
+    }
+
+    /// 请求头 / 响应头的值，敏感时脱敏
+    pub fn header(&self, key: &str, value: &str) -> String {
+        if self.is_sensitive(key) {
+            mask_value(value)
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// 查询参数的值，敏感时整体脱敏
+    pub fn param(&self, key: &str, value: &str) -> String {
+        if self.is_sensitive(key) {
+            MASK.to_string()
+        } else {
+            value.to_string()
+        }
+    }
+
+    /// URL 中敏感的查询参数脱敏
+    pub fn url(&self, url: &str) -> String {
+        let Ok(mut parsed) = Url::parse(url) else {
+            return url.to_string();
+        };
+        if !parsed.query_pairs().any(|(key, _)| self.is_sensitive(&key)) {
+            return url.to_string();
+        }
+
+        let pairs: Vec<(String, String)> = parsed
+            .query_pairs()
+            .map(|(key, value)| {
+                let value = self.param(&key, &value);
+                (key.into_owned(), value)
+            })
+            .collect();
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+        parsed.to_string()
+    }
+}
+
+/// 带认证前缀的值（如 `Bearer xxx`）只隐藏 token 部分
+pub fn mask_value(value: &str) -> String {
+    match value.split_once(' ') {
+        Some((prefix, _)) if !prefix.contains(['=', ';']) => format!("{} {}", prefix, MASK),
+        _ => MASK.to_string(),
+    }
+}
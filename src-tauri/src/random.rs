@@ -0,0 +1,39 @@
+//! 随机数
+//!
+//! 统一从操作系统的随机源读取：重试抖动、执行记录 ID、Allure 结果的 UUID 和密钥库的盐 / nonce。
+
+use crate::error::{Error, Result};
+
+/// 用随机字节填满 `buf`，系统随机源不可用时返回错误（用于加密，不能退化）
+pub fn fill(buf: &mut [u8]) -> Result<()> {
+    getrandom::getrandom(buf).map_err(|e| Error::Internal(format!("生成随机数失败: {}", e)))
+}
+
+/// 随机的 u64，系统随机源不可用时退回到当前时间的纳秒数（只用于非安全场景）
+pub fn next_u64() -> u64 {
+    let mut bytes = [0u8; 8];
+    match fill(&mut bytes) {
+        Ok(()) => u64::from_le_bytes(bytes),
+        Err(_) => chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64,
+    }
+}
+
+/// [0, 1) 区间的随机数
+pub fn next_f64() -> f64 {
+    (next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// 随机的 UUID（v4）
+pub fn uuid_v4() -> String {
+    let (high, low) = (next_u64(), next_u64());
+    let high = (high & !0xf000) | 0x4000;
+    let low = (low & !(0xc000 << 48)) | (0x8000 << 48);
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0xffff,
+        low >> 48,
+        low & 0xffff_ffff_ffff,
+    )
+}
@@ -0,0 +1,225 @@
+//! Allure 结果
+//!
+//! 写入 Allure 结果目录（`allure generate` 的输入）：
+//! - 每个测试用例一个 `<uuid>-result.json`，步骤为 Allure step，验证为其下的子步骤
+//! - 请求体、响应体作为附件，写入 `<uuid>-attachment.json`
+//! - 用例的标签写入 `tag` label，配置文件名写入 `suite` label
+//! - 当前环境写入 `environment.properties`
+//! - 提供历史统计时，不稳定的用例（或其中有不稳定的步骤）在 `statusDetails` 中标记 `flaky`
+//!
+//! 执行结果中没有记录开始时间，时间线以生成报告的时间为结束时间，按各步骤耗时倒推。
+
+use serde_json::{json, Value as JsonValue};
+use std::fs;
+use std::path::Path;
+
+use crate::analytics::HistoryStats;
+use crate::config::{EnvironmentConfig, ExecutionResult, ExecutionStatus, SuiteResult, TestCaseResult, TestConfig};
+use crate::error::{Error, Result};
+use crate::mask::Masker;
+use crate::random;
+
+use super::{failure_details, validation_label};
+
+/// 把套件执行结果写入 Allure 结果目录 `dir`
+pub fn write_results(
+    result: &SuiteResult,
+    name: &str,
+    config: &TestConfig,
+    env: Option<&EnvironmentConfig>,
+    mask: &[String],
+    stats: Option<&HistoryStats>,
+    dir: &Path,
+) -> Result<()> {
+    fs::create_dir_all(dir).map_err(|e| Error::io("无法创建目录", dir, e))?;
+
+    let writer = Writer { dir, masker: Masker::new(mask), stats };
+    let stop = chrono::Utc::now().timestamp_millis();
+    let start = stop - result.duration_ms as i64;
+
+    for case in &result.cases {
+        let tags = config.test_cases.get(&case.case_id).map(|c| c.tags.as_slice()).unwrap_or_default();
+        let test = writer.test_result(case, name, tags, start)?;
+        writer.write_json(&format!("{}-result.json", test["uuid"].as_str().unwrap_or_default()), &test)?;
+    }
+
+    if let Some(env) = env {
+        writer.write_environment(&config.global.profile.active, env)?;
+    }
+    Ok(())
+}
+
+struct Writer<'a> {
+    dir: &'a Path,
+    masker: Masker,
+    stats: Option<&'a HistoryStats>,
+}
+
+impl Writer<'_> {
+    /// 用例并行执行，各自从套件开始时间起算
+    fn test_result(&self, case: &TestCaseResult, suite: &str, tags: &[String], start: i64) -> Result<JsonValue> {
+        let name = if case.name.is_empty() { &case.case_id } else { &case.name };
+        let full_name = format!("{}.{}", suite, case.case_id);
+
+        let mut labels = vec![
+            json!({ "name": "suite", "value": suite }),
+            json!({ "name": "framework", "value": "yuml-ddt" }),
+        ];
+        labels.extend(tags.iter().map(|tag| json!({ "name": "tag", "value": tag })));
+
+        let mut steps = Vec::new();
+        let mut step_start = start;
+        for step in &case.steps {
+            steps.push(self.step(step, step_start)?);
+            step_start += step.duration_ms as i64;
+        }
+
+        let details = match (case.status, case.steps.iter().find(|s| !s.success && s.status != ExecutionStatus::Cancelled)) {
+            (ExecutionStatus::Passed, _) => None,
+            (_, Some(step)) => Some(step_details(step)),
+            (_, None) => case.error.as_ref().map(|e| json!({ "message": e })),
+        };
+
+        let mut test = json!({
+            "uuid": random::uuid_v4(),
+            "historyId": full_name,
+            "testCaseId": full_name,
+            "name": name,
+            "fullName": full_name,
+            "status": status(case.status),
+            "stage": "finished",
+            "start": start,
+            "stop": start + case.duration_ms as i64,
+            "labels": labels,
+            "steps": steps,
+        });
+        if let Some(details) = details {
+            test["statusDetails"] = details;
+        }
+        let flaky = self.stats.is_some_and(|stats| {
+            stats.is_flaky_case(&case.case_id) || case.steps.iter().any(|s| stats.is_flaky_step(&s.step_name))
+        });
+        if flaky {
+            test["statusDetails"]["flaky"] = true.into();
+        }
+        Ok(test)
+    }
+
+    fn step(&self, step: &ExecutionResult, start: i64) -> Result<JsonValue> {
+        let mut attachments = Vec::new();
+        if let Some(body) = &step.request_body {
+            attachments.push(self.attachment("请求体", body)?);
+        }
+        if let Some(body) = step.response_body.as_ref().filter(|b| !b.is_null()) {
+            attachments.push(self.attachment("响应体", body)?);
+        }
+
+        let mut parameters = vec![
+            json!({ "name": "method", "value": step.request_method }),
+            json!({ "name": "url", "value": self.masker.url(&step.request_url) }),
+        ];
+        if step.response_status > 0 {
+            parameters.push(json!({ "name": "status", "value": step.response_status.to_string() }));
+        }
+        if step.attempts.len() > 1 {
+            parameters.push(json!({ "name": "attempts", "value": step.attempts.len().to_string() }));
+        }
+        for (key, value) in &step.request_headers {
+            let value = self.masker.header(key, value);
+            parameters.push(json!({ "name": key, "value": value }));
+        }
+
+        let stop = start + step.duration_ms as i64;
+        let validations: Vec<JsonValue> = step
+            .validations
+            .iter()
+            .map(|v| {
+                let mut sub = json!({
+                    "name": validation_label(v),
+                    "status": if v.passed { "passed" } else { "failed" },
+                    "stage": "finished",
+                    "start": stop,
+                    "stop": stop,
+                });
+                if !v.passed {
+                    sub["statusDetails"] = json!({ "message": format!("expected: {}\nactual: {}", v.expected, v.actual) });
+                }
+                sub
+            })
+            .collect();
+
+        let mut result = json!({
+            "name": step.step_name,
+            "status": status(step.status),
+            "stage": "finished",
+            "start": start,
+            "stop": stop,
+            "parameters": parameters,
+            "attachments": attachments,
+            "steps": validations,
+        });
+        if !step.success && step.status != ExecutionStatus::Cancelled {
+            result["statusDetails"] = step_details(step);
+        }
+        Ok(result)
+    }
+
+    /// 写入附件文件，返回附件引用
+    fn attachment(&self, name: &str, body: &JsonValue) -> Result<JsonValue> {
+        let (content, mime, extension) = match body {
+            JsonValue::String(text) => (text.clone(), "text/plain", "txt"),
+            other => (serde_json::to_string_pretty(other).unwrap_or_default(), "application/json", "json"),
+        };
+        let source = format!("{}-attachment.{}", random::uuid_v4(), extension);
+        self.write(&source, &content)?;
+        Ok(json!({ "name": name, "source": source, "type": mime }))
+    }
+
+    fn write_environment(&self, profile: &str, env: &EnvironmentConfig) -> Result<()> {
+        let mut lines = vec![format!("profile={}", profile), format!("base_url={}", env.base_url)];
+        if !env.context.is_empty() {
+            lines.push(format!("context={}", env.context));
+        }
+        if !env.brand.is_empty() {
+            lines.push(format!("brand={}", env.brand));
+        }
+        // properties 文件按 ISO-8859-1 读取，非 ASCII 字符需要转义
+        let content: String = lines
+            .join("\n")
+            .chars()
+            .map(|c| if c.is_ascii() { c.to_string() } else { format!("\\u{:04x}", c as u32) })
+            .collect();
+        self.write("environment.properties", &format!("{}\n", content))
+    }
+
+    fn write_json(&self, file: &str, value: &JsonValue) -> Result<()> {
+        let content = serde_json::to_string_pretty(value)
+            .map_err(|e| Error::Internal(format!("序列化 Allure 结果失败: {}", e)))?;
+        self.write(file, &content)
+    }
+
+    fn write(&self, file: &str, content: &str) -> Result<()> {
+        let path = self.dir.join(file);
+        fs::write(&path, content).map_err(|e| Error::io("无法写入文件", &path, e))
+    }
+}
+
+fn step_details(step: &ExecutionResult) -> JsonValue {
+    match (step.status, &step.error) {
+        (ExecutionStatus::Error, Some(error)) => json!({ "message": error }),
+        _ => {
+            let (message, trace) = failure_details(step);
+            json!({ "message": message, "trace": trace })
+        }
+    }
+}
+
+/// 失败对应 failed，请求未完成对应 broken，取消对应 skipped
+fn status(status: ExecutionStatus) -> &'static str {
+    match status {
+        ExecutionStatus::Passed => "passed",
+        ExecutionStatus::Failed => "failed",
+        ExecutionStatus::Error => "broken",
+        ExecutionStatus::Cancelled => "skipped",
+    }
+}
@@ -0,0 +1,271 @@
+//! HTML 报告
+//!
+//! 生成单个 HTML 文件（样式和脚本内联），便于作为附件发送：
+//! 汇总、各用例及步骤的耗时条形图、请求 / 响应详情和验证结果表。
+//! 凭据类请求头、查询参数在报告中脱敏。提供历史统计时标出不稳定的用例和步骤，并显示历史通过率。
+
+use serde_json::Value as JsonValue;
+use std::fmt::Write;
+
+use crate::analytics::{HistoryStats, TestStats};
+use crate::config::{ExecutionResult, ExecutionStatus, SuiteResult, TestCaseResult};
+use crate::mask::Masker;
+
+use super::escape_xml as escape;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", "PingFang SC", "Microsoft YaHei", sans-serif; margin: 0; padding: 24px; background: #f5f6f8; color: #1f2328; }
+h1 { margin: 0 0 16px; font-size: 22px; }
+.summary { display: flex; gap: 12px; flex-wrap: wrap; margin-bottom: 20px; }
+.card { background: #fff; border-radius: 8px; padding: 12px 20px; min-width: 110px; box-shadow: 0 1px 2px rgba(0,0,0,.08); }
+.card .value { font-size: 24px; font-weight: 600; }
+.card .label { color: #656d76; font-size: 12px; }
+.passed { color: #1a7f37; } .failed, .error { color: #cf222e; } .cancelled { color: #9a6700; }
+.toolbar { margin-bottom: 12px; }
+.toolbar button { border: 1px solid #d0d7de; background: #fff; border-radius: 6px; padding: 4px 12px; cursor: pointer; }
+.toolbar button.active { background: #0969da; color: #fff; border-color: #0969da; }
+.case { background: #fff; border-radius: 8px; margin-bottom: 12px; box-shadow: 0 1px 2px rgba(0,0,0,.08); }
+.case > summary, .step > summary { cursor: pointer; padding: 10px 14px; display: flex; align-items: center; gap: 10px; }
+.case > summary .name { font-weight: 600; flex: 0 0 260px; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+.step { border-top: 1px solid #eaeef2; margin-left: 14px; }
+.step > summary .name { flex: 0 0 246px; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+.bar { flex: 1; background: #eaeef2; height: 8px; border-radius: 4px; overflow: hidden; }
+.bar span { display: block; height: 100%; background: #54aeff; }
+.bar.failed span, .bar.error span { background: #ff8182; }
+.time { flex: 0 0 80px; text-align: right; color: #656d76; font-size: 12px; }
+.badge { font-size: 12px; font-weight: 600; flex: 0 0 70px; }
+.flaky { font-size: 11px; color: #9a6700; border: 1px solid #d4a72c; border-radius: 10px; padding: 0 6px; }
+.detail { padding: 4px 14px 14px 28px; }
+.detail h4 { margin: 12px 0 6px; font-size: 13px; }
+table { border-collapse: collapse; width: 100%; font-size: 12px; }
+th, td { border: 1px solid #d0d7de; padding: 4px 8px; text-align: left; vertical-align: top; word-break: break-all; }
+th { background: #f6f8fa; }
+pre { background: #f6f8fa; padding: 8px; border-radius: 6px; font-size: 12px; overflow: auto; max-height: 400px; margin: 0; }
+.message { color: #cf222e; font-size: 13px; }
+body.only-failed .case.passed { display: none; }
+"#;
+
+const SCRIPT: &str = r#"
+document.querySelectorAll('.toolbar button').forEach(function (button) {
+  button.addEventListener('click', function () {
+    document.querySelectorAll('.toolbar button').forEach(function (b) { b.classList.remove('active'); });
+    button.classList.add('active');
+    document.body.classList.toggle('only-failed', button.dataset.filter === 'failed');
+  });
+});
+"#;
+
+/// 把套件执行结果转换为 HTML 报告，`mask` 为额外需要脱敏的键
+pub fn to_html(result: &SuiteResult, name: &str, mask: &[String], stats: Option<&HistoryStats>) -> String {
+    let masker = Masker::new(mask);
+    let max_case_ms = result.cases.iter().map(|c| c.duration_ms).max().unwrap_or(0);
+    let steps = result.cases.iter().flat_map(|c| c.steps.iter());
+    let step_count = steps.clone().count();
+    let step_passed = steps.filter(|s| s.status == ExecutionStatus::Passed).count();
+    let pass_rate = (result.passed * 100).checked_div(result.total).unwrap_or(0);
+
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"UTF-8\">\n<title>{title} - 测试报告</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title} <span class=\"{status}\">{label}</span></h1>\n",
+        title = escape(name),
+        status = if result.success { "passed" } else { "failed" },
+        label = if result.success { "通过" } else { "未通过" },
+    );
+
+    html.push_str("<div class=\"summary\">\n");
+    for (value, label, class) in [
+        (result.total.to_string(), "用例", ""),
+        (result.passed.to_string(), "通过", "passed"),
+        (result.failed.to_string(), "失败", "failed"),
+        (format!("{}%", pass_rate), "通过率", ""),
+        (format!("{}/{}", step_passed, step_count), "步骤通过", ""),
+        (format!("{}ms", result.duration_ms), "总耗时", ""),
+        (chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(), "生成时间", ""),
+    ] {
+        let _ = writeln!(
+            html,
+            "<div class=\"card\"><div class=\"value {}\">{}</div><div class=\"label\">{}</div></div>",
+            class,
+            escape(&value),
+            label
+        );
+    }
+    if let Some(stats) = stats {
+        let flaky = result.cases.iter().filter(|c| stats.is_flaky_case(&c.case_id)).count()
+            + result.cases.iter().flat_map(|c| c.steps.iter()).filter(|s| stats.is_flaky_step(&s.step_name)).count();
+        let _ = writeln!(
+            html,
+            "<div class=\"card\" title=\"最近 {} 次执行中结果翻转的用例和步骤\"><div class=\"value cancelled\">{}</div><div class=\"label\">不稳定</div></div>",
+            stats.runs, flaky
+        );
+    }
+    html.push_str("</div>\n");
+
+    html.push_str("<div class=\"toolbar\"><button class=\"active\" data-filter=\"all\">全部</button> <button data-filter=\"failed\">只看失败</button></div>\n");
+
+    for case in &result.cases {
+        html.push_str(&case_html(case, max_case_ms, &masker, stats));
+    }
+
+    let _ = write!(html, "<script>{SCRIPT}</script>\n</body>\n</html>\n");
+    html
+}
+
+fn case_html(case: &TestCaseResult, max_case_ms: u64, masker: &Masker, stats: Option<&HistoryStats>) -> String {
+    let status = status_class(case.status);
+    let name = if case.name.is_empty() { &case.case_id } else { &case.name };
+    let max_step_ms = case.steps.iter().map(|s| s.duration_ms).max().unwrap_or(0);
+
+    let mut html = String::new();
+    let _ = writeln!(
+        html,
+        "<details class=\"case {status}\"{open}>\n<summary><span class=\"badge {status}\">{label}</span><span class=\"name\" title=\"{id}\">{name}</span>{flaky}{bar}<span class=\"time\">{ms}ms</span></summary>",
+        open = if case.success { "" } else { " open" },
+        label = status_label(case.status),
+        id = escape(&case.case_id),
+        name = escape(name),
+        flaky = flaky_badge(stats.and_then(|s| s.case(&case.case_id))),
+        bar = bar(case.duration_ms, max_case_ms, status),
+        ms = case.duration_ms,
+    );
+    if let Some(error) = &case.error {
+        let _ = writeln!(html, "<div class=\"detail message\">{}</div>", escape(error));
+    }
+    for step in &case.steps {
+        html.push_str(&step_html(step, max_step_ms, masker, stats.and_then(|s| s.step(&step.step_name))));
+    }
+    html.push_str("</details>\n");
+    html
+}
+
+fn step_html(step: &ExecutionResult, max_step_ms: u64, masker: &Masker, stats: Option<&TestStats>) -> String {
+    let status = status_class(step.status);
+    let mut html = String::new();
+    let _ = writeln!(
+        html,
+        "<details class=\"step\">\n<summary><span class=\"badge {status}\">{label}</span><span class=\"name\">{name}</span>{flaky}{bar}<span class=\"time\">{ms}ms</span></summary>\n<div class=\"detail\">",
+        label = status_label(step.status),
+        name = escape(&step.step_name),
+        flaky = flaky_badge(stats),
+        bar = bar(step.duration_ms, max_step_ms, status),
+        ms = step.duration_ms,
+    );
+
+    if let Some(error) = &step.error {
+        let _ = writeln!(html, "<div class=\"message\">{}</div>", escape(error));
+    }
+
+    let _ = writeln!(
+        html,
+        "<h4>请求</h4>\n<pre>{} {}</pre>",
+        escape(&step.request_method),
+        escape(&masker.url(&step.request_url)),
+    );
+    html.push_str(&headers_table(step.request_headers.iter(), masker));
+    if let Some(body) = &step.request_body {
+        let _ = writeln!(html, "<h4>请求体</h4>\n<pre>{}</pre>", escape(&pretty(body)));
+    }
+
+    if step.response_status > 0 {
+        let _ = writeln!(html, "<h4>响应 {}</h4>", step.response_status);
+        html.push_str(&headers_table(step.response_headers.iter(), masker));
+        if let Some(body) = &step.response_body {
+            let _ = writeln!(html, "<pre>{}</pre>", escape(&pretty(body)));
+        }
+    }
+
+    if !step.validations.is_empty() {
+        html.push_str("<h4>验证</h4>\n<table>\n<tr><th>字段 / 断言</th><th>操作符</th><th>期望值</th><th>实际值</th><th>结果</th></tr>\n");
+        for v in &step.validations {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td></tr>",
+                escape(&v.field),
+                escape(&v.operator),
+                escape(&v.expected),
+                escape(&v.actual),
+                if v.passed { "passed" } else { "failed" },
+                if v.passed { "通过" } else { "失败" },
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    if step.attempts.len() > 1 {
+        html.push_str("<h4>请求尝试</h4>\n<table>\n<tr><th>#</th><th>状态码</th><th>耗时</th><th>错误</th></tr>\n");
+        for attempt in &step.attempts {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}ms</td><td>{}</td></tr>",
+                attempt.attempt,
+                attempt.status,
+                attempt.duration_ms,
+                escape(attempt.error.as_deref().unwrap_or_default()),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</div>\n</details>\n");
+    html
+}
+
+fn headers_table<'a>(headers: impl Iterator<Item = (&'a String, &'a String)>, masker: &Masker) -> String {
+    let rows: String = headers
+        .map(|(key, value)| {
+            let value = masker.header(key, value);
+            format!("<tr><th>{}</th><td>{}</td></tr>\n", escape(key), escape(&value))
+        })
+        .collect();
+    if rows.is_empty() {
+        String::new()
+    } else {
+        format!("<table>\n{}</table>\n", rows)
+    }
+}
+
+/// 不稳定标记，提示中显示历史通过率和平均耗时
+fn flaky_badge(stats: Option<&TestStats>) -> String {
+    match stats.filter(|s| s.flaky) {
+        Some(stats) => format!(
+            "<span class=\"flaky\" title=\"最近 {} 次通过 {} 次（{:.0}%），平均耗时 {}ms\">不稳定</span>",
+            stats.runs,
+            stats.passed,
+            stats.pass_rate * 100.0,
+            stats.avg_duration_ms,
+        ),
+        None => String::new(),
+    }
+}
+
+/// 耗时条，宽度相对同级中最长的耗时
+fn bar(duration_ms: u64, max_ms: u64, status: &str) -> String {
+    let width = if max_ms > 0 { duration_ms as f64 * 100.0 / max_ms as f64 } else { 0.0 };
+    format!("<span class=\"bar {}\"><span style=\"width: {:.1}%\"></span></span>", status, width)
+}
+
+fn pretty(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    }
+}
+
+fn status_class(status: ExecutionStatus) -> &'static str {
+    match status {
+        ExecutionStatus::Passed => "passed",
+        ExecutionStatus::Failed => "failed",
+        ExecutionStatus::Error => "error",
+        ExecutionStatus::Cancelled => "cancelled",
+    }
+}
+
+fn status_label(status: ExecutionStatus) -> &'static str {
+    match status {
+        ExecutionStatus::Passed => "通过",
+        ExecutionStatus::Failed => "失败",
+        ExecutionStatus::Error => "错误",
+        ExecutionStatus::Cancelled => "已取消",
+    }
+}
@@ -0,0 +1,141 @@
+//! JUnit XML 报告
+//!
+//! - 测试用例对应 `<testsuite>`，其中的步骤对应 `<testcase>`
+//! - 未通过的验证、断言和状态码对应 `<failure>`，请求未完成（超时、连接失败等）对应 `<error>`
+//! - 被取消的步骤对应 `<skipped>`
+//! - `duration_ms` 换算为秒写入 `time`
+//! - 提供历史统计时，不稳定的用例和步骤带 `flaky`、`pass_rate` 属性（`<properties>`）
+
+use crate::analytics::{HistoryStats, TestStats};
+use crate::config::{ExecutionResult, ExecutionStatus, SuiteResult, TestCaseResult};
+
+use super::{escape_xml, failure_details, seconds};
+
+/// 把套件执行结果转换为 JUnit XML
+pub fn to_junit(result: &SuiteResult, name: &str, stats: Option<&HistoryStats>) -> String {
+    let suites: Vec<Suite> = result.cases.iter().map(|case| Suite::new(case, stats)).collect();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\">\n",
+        escape_xml(name),
+        suites.iter().map(|s| s.tests).sum::<usize>(),
+        suites.iter().map(|s| s.failures).sum::<usize>(),
+        suites.iter().map(|s| s.errors).sum::<usize>(),
+        suites.iter().map(|s| s.skipped).sum::<usize>(),
+        seconds(result.duration_ms),
+    ));
+    for suite in &suites {
+        xml.push_str(&suite.xml);
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// 一个测试用例对应的 `<testsuite>` 及其统计
+struct Suite {
+    tests: usize,
+    failures: usize,
+    errors: usize,
+    skipped: usize,
+    xml: String,
+}
+
+impl Suite {
+    fn new(case: &TestCaseResult, stats: Option<&HistoryStats>) -> Self {
+        let mut suite = Self { tests: 0, failures: 0, errors: 0, skipped: 0, xml: String::new() };
+        let mut body = flaky_properties(stats.and_then(|s| s.case(&case.case_id)), "    ");
+
+        for step in &case.steps {
+            let step_stats = stats.and_then(|s| s.step(&step.step_name));
+            body.push_str(&suite.testcase(case, step, step_stats));
+        }
+        // 用例在执行任何步骤之前就失败了（如步骤不存在），记为一个出错的 testcase
+        if case.steps.is_empty() {
+            if let Some(error) = &case.error {
+                suite.tests += 1;
+                suite.errors += 1;
+                body.push_str(&format!(
+                    "    <testcase name=\"{}\" classname=\"{}\" time=\"0.000\">\n      <error message=\"{}\"/>\n    </testcase>\n",
+                    escape_xml(&case.name),
+                    escape_xml(&case.case_id),
+                    escape_xml(error),
+                ));
+            }
+        }
+
+        let name = if case.name.is_empty() { &case.case_id } else { &case.name };
+        suite.xml = format!(
+            "  <testsuite name=\"{}\" id=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{}\">\n{}  </testsuite>\n",
+            escape_xml(name),
+            escape_xml(&case.case_id),
+            suite.tests,
+            suite.failures,
+            suite.errors,
+            suite.skipped,
+            seconds(case.duration_ms),
+            body,
+        );
+        suite
+    }
+
+    fn testcase(&mut self, case: &TestCaseResult, step: &ExecutionResult, stats: Option<&TestStats>) -> String {
+        self.tests += 1;
+        let properties = flaky_properties(stats, "      ");
+        let outcome = match step.status {
+            ExecutionStatus::Passed => String::new(),
+            ExecutionStatus::Failed => {
+                self.failures += 1;
+                failure(step)
+            }
+            ExecutionStatus::Error => {
+                self.errors += 1;
+                let kind = step.error_kind
+                    .and_then(|k| serde_json::to_value(k).ok())
+                    .and_then(|k| k.as_str().map(str::to_string))
+                    .unwrap_or_default();
+                format!(
+                    "      <error message=\"{}\" type=\"{}\"/>\n",
+                    escape_xml(step.error.as_deref().unwrap_or("请求失败")),
+                    escape_xml(&kind),
+                )
+            }
+            ExecutionStatus::Cancelled => {
+                self.skipped += 1;
+                "      <skipped message=\"执行已取消\"/>\n".to_string()
+            }
+        };
+
+        let open = format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+            escape_xml(&step.step_name),
+            escape_xml(&case.case_id),
+            seconds(step.duration_ms),
+        );
+        if outcome.is_empty() && properties.is_empty() {
+            format!("{}/>\n", open)
+        } else {
+            format!("{}>\n{}{}    </testcase>\n", open, properties, outcome)
+        }
+    }
+}
+
+fn failure(step: &ExecutionResult) -> String {
+    let (message, details) = failure_details(step);
+    format!(
+        "      <failure message=\"{}\" type=\"validation\">{}</failure>\n",
+        escape_xml(&message),
+        escape_xml(&details),
+    )
+}
+
+/// 不稳定时输出 `flaky` 和 `pass_rate` 属性
+fn flaky_properties(stats: Option<&TestStats>, indent: &str) -> String {
+    match stats.filter(|s| s.flaky) {
+        Some(stats) => format!(
+            "{indent}<properties>\n{indent}  <property name=\"flaky\" value=\"true\"/>\n{indent}  <property name=\"pass_rate\" value=\"{:.2}\"/>\n{indent}</properties>\n",
+            stats.pass_rate,
+        ),
+        None => String::new(),
+    }
+}
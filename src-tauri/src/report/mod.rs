@@ -0,0 +1,133 @@
+//! 执行结果报告
+//!
+//! 把一次套件执行的结果（`SuiteResult`）输出为 CI 等外部系统可读的格式。
+//! 认证配置中放置 token 的键和常见的凭据请求头在报告中脱敏。
+
+pub mod allure;
+pub mod html;
+pub mod junit;
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::analytics::HistoryStats;
+use crate::config::{ExecutionResult, SuiteResult, TestConfig, ValidationResult};
+use crate::convert;
+use crate::engine::TestEngine;
+use crate::error::{Error, Result};
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    /// JUnit XML，Jenkins、GitLab 等 CI 系统可直接读取
+    Junit,
+    /// 单个 HTML 文件，包含请求 / 响应详情
+    Html,
+    /// Allure 结果目录，每个用例一个 JSON 文件
+    Allure,
+}
+
+impl ReportFormat {
+    /// 按名称解析，用于命令行参数
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "junit" => Ok(Self::Junit),
+            "html" => Ok(Self::Html),
+            "allure" => Ok(Self::Allure),
+            other => Err(Error::Validation(format!("不支持的报告格式: {}", other))),
+        }
+    }
+
+    /// 未指定输出路径时使用的默认路径
+    pub fn default_output(&self) -> &'static str {
+        match self {
+            Self::Junit => "junit.xml",
+            Self::Html => "report.html",
+            Self::Allure => "allure-results",
+        }
+    }
+}
+
+/// 生成报告并写入 `output`（Allure 为目录）
+///
+/// `config` 为执行所用的配置，用于确定当前环境和需要脱敏的认证键；`name` 为套件名称（通常取配置文件名）；
+/// `stats` 为该配置文件的历史统计，提供时在报告中标出不稳定的步骤和用例
+pub fn write_report(
+    result: &SuiteResult,
+    format: ReportFormat,
+    config: &TestConfig,
+    name: &str,
+    stats: Option<&HistoryStats>,
+    output: &Path,
+) -> Result<()> {
+    let env = TestEngine::resolve_env_config(config).ok();
+    let mask: Vec<String> = convert::auth_target(env.as_ref()).map(|(key, _)| key).into_iter().collect();
+
+    match format {
+        ReportFormat::Junit => write_file(output, &junit::to_junit(result, name, stats)),
+        ReportFormat::Html => write_file(output, &html::to_html(result, name, &mask, stats)),
+        ReportFormat::Allure => allure::write_results(result, name, config, env.as_ref(), &mask, stats, output),
+    }
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| Error::io("无法创建目录", dir, e))?;
+    }
+    fs::write(path, content).map_err(|e| Error::io("无法写入文件", path, e))
+}
+
+/// 步骤未通过的原因：未通过的验证逐条列出期望值和实际值；验证都通过时为状态码不是 2xx
+///
+/// 返回 (概要, 详情)
+pub(crate) fn failure_details(step: &ExecutionResult) -> (String, String) {
+    let failed: Vec<_> = step.validations.iter().filter(|v| !v.passed).collect();
+
+    match failed.first() {
+        Some(first) => {
+            let message = format!("{} 未通过", validation_label(first));
+            let details: Vec<String> = failed
+                .iter()
+                .map(|v| format!("{}\n  expected: {}\n  actual: {}", validation_label(v), v.expected, v.actual))
+                .collect();
+            (message, details.join("\n"))
+        }
+        None => {
+            let message = format!("HTTP 状态码 {}", step.response_status);
+            (message.clone(), message)
+        }
+    }
+}
+
+/// 验证的显示名称，断言的 field 是表达式本身
+pub(crate) fn validation_label(v: &ValidationResult) -> String {
+    match v.operator.as_str() {
+        "assert" => v.field.clone(),
+        operator => format!("{} {}", v.field, operator),
+    }
+}
+
+/// 转义 XML / HTML 文本和属性值
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // XML 1.0 不允许的控制字符
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 毫秒转换为秒，保留三位小数
+pub(crate) fn seconds(duration_ms: u64) -> String {
+    format!("{:.3}", duration_ms as f64 / 1000.0)
+}
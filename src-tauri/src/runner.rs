@@ -0,0 +1,329 @@
+//! 测试套件并行执行
+//!
+//! 每个测试用例使用独立的引擎实例（上下文互不影响），所有实例共享 token 缓存。
+//! - 并发数由 `max_workers` 限制
+//! - `serial: true` 的用例独占执行，开始前等待其他用例结束，执行期间不启动新用例
+//! - `locks` 中声明了相同资源的用例不会同时执行
+
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{
+    Mutex, OwnedMutexGuard, OwnedRwLockReadGuard, OwnedRwLockWriteGuard, OwnedSemaphorePermit, RwLock, Semaphore,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::cache::Cache;
+use crate::config::*;
+use crate::engine::TestEngine;
+use crate::error::Result;
+use crate::events::ExecutionObserver;
+use crate::loader;
+use crate::secrets::Secrets;
+use crate::tags::TagFilter;
+
+/// 最大并发数，配置或命令行中更大的值按此处理
+pub const MAX_WORKERS: usize = 256;
+
+pub struct SuiteRunner {
+    config: Arc<TestConfig>,
+    max_workers: usize,
+    token_cache: Arc<Cache>,
+    cancel_token: CancellationToken,
+    observer: Option<Arc<dyn ExecutionObserver>>,
+    secrets: Arc<Secrets>,
+}
+
+impl SuiteRunner {
+    /// 从配置创建执行器，并发数取 `global.workers`，未配置时按 CPU 核数，最多 [`MAX_WORKERS`]
+    pub fn new(config: TestConfig) -> Self {
+        let max_workers = if config.global.workers > 0 {
+            config.global.workers
+        } else {
+            std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)
+        };
+        let max_workers = max_workers.min(MAX_WORKERS);
+
+        Self {
+            config: Arc::new(config),
+            max_workers,
+            token_cache: Arc::new(Cache::new()),
+            cancel_token: CancellationToken::new(),
+            observer: None,
+            secrets: Arc::new(Secrets::default()),
+        }
+    }
+
+    /// 从配置文件创建执行器
+    pub fn from_file(config_path: &str) -> Result<Self> {
+        Ok(Self::new(loader::load_config_file(Path::new(config_path))?))
+    }
+
+    /// 设置最大并发数，取值范围 1 到 [`MAX_WORKERS`]
+    pub fn max_workers(mut self, max_workers: usize) -> Self {
+        self.max_workers = max_workers.clamp(1, MAX_WORKERS);
+        self
+    }
+
+    /// 使用外部的 token 缓存（例如多次执行之间复用）
+    pub fn token_cache(mut self, token_cache: Arc<Cache>) -> Self {
+        self.token_cache = token_cache;
+        self
+    }
+
+    /// 设置取消令牌，取消后正在执行的用例立即中止，尚未开始的用例不再执行，结果标记为已取消
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = token;
+        self
+    }
+
+    /// 设置执行事件观察者，所有用例共享
+    pub fn observer(mut self, observer: Arc<dyn ExecutionObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// 设置密钥库中的密钥，所有用例共享
+    pub fn secrets(mut self, secrets: Arc<Secrets>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// 并行执行所有匹配标签表达式的测试用例，结果按用例定义顺序返回
+    pub async fn run(
+        &self,
+        filter: &TagFilter,
+        external_variables: Option<HashMap<String, JsonValue>>,
+    ) -> Result<SuiteResult> {
+        let start = Instant::now();
+
+        let case_ids = TestEngine::with_token_cache((*self.config).clone(), self.token_cache.clone())?
+            .select_test_cases(filter);
+
+        let suite_locks = Arc::new(SuiteLocks::new(&self.config, self.max_workers));
+
+        let mut handles = Vec::new();
+        for case_id in case_ids {
+            let case = &self.config.test_cases[&case_id];
+            let name = if case.name.is_empty() { case_id.clone() } else { case.name.clone() };
+            let config = self.config.clone();
+            let token_cache = self.token_cache.clone();
+            let suite_locks = suite_locks.clone();
+            let variables = external_variables.clone();
+            let cancel_token = self.cancel_token.clone();
+            let observer = self.observer.clone();
+            let secrets = self.secrets.clone();
+
+            handles.push((case_id.clone(), name.clone(), tokio::spawn(async move {
+                let _guard = suite_locks.acquire(&config.test_cases[&case_id]).await;
+
+                // 等待期间已取消的用例不再执行
+                if cancel_token.is_cancelled() {
+                    return unfinished_case(case_id, name, ExecutionStatus::Cancelled, None);
+                }
+
+                match TestEngine::with_token_cache((*config).clone(), token_cache) {
+                    Ok(mut engine) => {
+                        engine.set_cancellation_token(cancel_token);
+                        engine.set_secrets(secrets);
+                        if let Some(observer) = observer {
+                            engine.set_observer(observer);
+                        }
+                        engine.run_test_case(&case_id, variables).await
+                    }
+                    Err(e) => unfinished_case(case_id, name, ExecutionStatus::Error, Some(e.to_string())),
+                }
+            })));
+        }
+
+        let mut cases = Vec::new();
+        for (case_id, name, handle) in handles {
+            // 单个用例异常中止时记为错误，不影响其他用例的结果
+            cases.push(handle.await.unwrap_or_else(|e| {
+                unfinished_case(case_id, name, ExecutionStatus::Error, Some(format!("测试用例执行中断: {}", e)))
+            }));
+        }
+
+        let passed = cases.iter().filter(|c| c.success).count();
+        Ok(SuiteResult {
+            success: passed == cases.len(),
+            total: cases.len(),
+            passed,
+            failed: cases.len() - passed,
+            cases,
+            duration_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+}
+
+/// 一次执行中所有用例共享的锁
+struct SuiteLocks {
+    /// 串行用例持有写锁，其他用例持有读锁
+    serial: Arc<RwLock<()>>,
+    /// `locks` 中声明的资源
+    resources: HashMap<String, Arc<Mutex<()>>>,
+    /// 并发名额
+    workers: Arc<Semaphore>,
+}
+
+/// 用例执行期间持有的锁，离开作用域时释放
+struct CaseGuard {
+    _exclusive: Option<OwnedRwLockWriteGuard<()>>,
+    _shared: Option<OwnedRwLockReadGuard<()>>,
+    _resources: Vec<OwnedMutexGuard<()>>,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl SuiteLocks {
+    fn new(config: &TestConfig, max_workers: usize) -> Self {
+        Self {
+            serial: Arc::new(RwLock::new(())),
+            resources: config
+                .test_cases
+                .values()
+                .flat_map(|case| case.locks.iter())
+                .map(|name| (name.clone(), Arc::new(Mutex::new(()))))
+                .collect(),
+            workers: Arc::new(Semaphore::new(max_workers)),
+        }
+    }
+
+    /// 依次获取串行锁、资源锁和并发名额
+    ///
+    /// 资源锁按名称排序后获取，避免不同用例交叉等待造成死锁；
+    /// 拿到全部锁之后才占用并发名额，等待锁的用例不占用名额。
+    async fn acquire(&self, case: &TestCase) -> CaseGuard {
+        let (exclusive, shared) = if case.serial {
+            (Some(self.serial.clone().write_owned().await), None)
+        } else {
+            (None, Some(self.serial.clone().read_owned().await))
+        };
+        let mut resources = Vec::new();
+        for name in case.locks.iter().collect::<BTreeSet<_>>() {
+            if let Some(lock) = self.resources.get(name) {
+                resources.push(lock.clone().lock_owned().await);
+            }
+        }
+        let permit = self.workers.clone().acquire_owned().await.ok();
+
+        CaseGuard { _exclusive: exclusive, _shared: shared, _resources: resources, _permit: permit }
+    }
+}
+
+/// 没有执行步骤就结束的用例结果
+fn unfinished_case(case_id: String, name: String, status: ExecutionStatus, error: Option<String>) -> TestCaseResult {
+    TestCaseResult { case_id, name, success: false, status, steps: vec![], duration_ms: 0, error }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn case(serial: bool, locks: &[&str]) -> TestCase {
+        TestCase { serial, locks: locks.iter().map(|l| l.to_string()).collect(), ..Default::default() }
+    }
+
+    fn suite_locks(cases: &[TestCase], max_workers: usize) -> Arc<SuiteLocks> {
+        let config = TestConfig {
+            test_cases: cases.iter().enumerate().map(|(i, c)| (format!("case_{}", i), c.clone())).collect(),
+            ..Default::default()
+        };
+        Arc::new(SuiteLocks::new(&config, max_workers))
+    }
+
+    /// 在后台获取锁，返回的任务在拿到锁后结束
+    fn spawn_acquire(locks: &Arc<SuiteLocks>, case: TestCase) -> tokio::task::JoinHandle<CaseGuard> {
+        let locks = locks.clone();
+        tokio::spawn(async move { locks.acquire(&case).await })
+    }
+
+    async fn is_waiting(handle: &tokio::task::JoinHandle<CaseGuard>) -> bool {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        !handle.is_finished()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn crossed_resource_locks_do_not_deadlock() {
+        let first = case(false, &["b", "a"]);
+        let second = case(false, &["a", "b"]);
+        let locks = suite_locks(&[first.clone(), second.clone()], 4);
+
+        let tasks: Vec<_> = (0..200)
+            .map(|i| {
+                let locks = locks.clone();
+                let case = if i % 2 == 0 { first.clone() } else { second.clone() };
+                tokio::spawn(async move {
+                    let _guard = locks.acquire(&case).await;
+                    tokio::task::yield_now().await;
+                })
+            })
+            .collect();
+
+        let all = async {
+            for task in tasks {
+                task.await.unwrap();
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), all).await.expect("资源锁交叉等待导致死锁");
+    }
+
+    #[tokio::test]
+    async fn cases_sharing_a_resource_run_one_at_a_time() {
+        let locks = suite_locks(&[case(false, &["db"]), case(false, &["cache"]), case(false, &["queue"])], 4);
+
+        let held = locks.acquire(&case(false, &["db"])).await;
+        let waiting = spawn_acquire(&locks, case(false, &["db", "cache"]));
+        assert!(is_waiting(&waiting).await);
+        // 不相关的资源不受影响
+        let other = tokio::time::timeout(Duration::from_secs(1), locks.acquire(&case(false, &["queue"]))).await;
+        assert!(other.is_ok());
+
+        drop(held);
+        tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn serial_case_runs_alone() {
+        let locks = suite_locks(&[], 4);
+
+        let running = locks.acquire(&case(false, &[])).await;
+        let serial = spawn_acquire(&locks, case(true, &[]));
+        assert!(is_waiting(&serial).await, "串行用例应等待正在执行的用例结束");
+
+        drop(running);
+        let serial_guard = tokio::time::timeout(Duration::from_secs(1), serial).await.unwrap().unwrap();
+        let next = spawn_acquire(&locks, case(false, &[]));
+        assert!(is_waiting(&next).await, "串行用例执行期间不启动新用例");
+
+        drop(serial_guard);
+        tokio::time::timeout(Duration::from_secs(1), next).await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn waiting_for_a_resource_does_not_take_a_worker() {
+        let locks = suite_locks(&[case(false, &["db"])], 2);
+
+        let _held = locks.acquire(&case(false, &["db"])).await;
+        let waiting = spawn_acquire(&locks, case(false, &["db"]));
+        assert!(is_waiting(&waiting).await);
+
+        // 两个名额中一个被占用，等待资源的用例不占名额，无关的用例可以执行
+        let free = tokio::time::timeout(Duration::from_secs(1), locks.acquire(&case(false, &[]))).await;
+        assert!(free.is_ok());
+    }
+
+    #[tokio::test]
+    async fn workers_limit_concurrency() {
+        let locks = suite_locks(&[], 1);
+
+        let running = locks.acquire(&case(false, &[])).await;
+        let next = spawn_acquire(&locks, case(false, &[]));
+        assert!(is_waiting(&next).await);
+
+        drop(running);
+        tokio::time::timeout(Duration::from_secs(1), next).await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,618 @@
+//! 密钥库
+//!
+//! 配置文件中用 `{secret:名称}` 引用密钥，密钥值保存在应用数据目录下的加密文件 `secrets.vault` 中，
+//! 不写入测试配置文件：
+//! - 用主密码或密钥文件解锁，密钥由 Argon2id 派生，内容以 AES-256-GCM 加密
+//! - 引擎发送请求前替换引用（含认证配置中的 `username` / `password` / `client_id`），
+//!   执行结果、执行事件和调试输出中出现的密钥值一律替换为 `******`
+//! - 未解锁或密钥不存在时引用原样保留
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::config::{AuthConfig, ExecutionResult};
+use crate::error::{Error, Result};
+use crate::mask::MASK;
+use crate::random;
+use crate::storage::AppData;
+
+/// 密钥库文件名
+const VAULT_FILE: &str = "secrets.vault";
+
+/// 文件格式版本
+const VAULT_VERSION: u32 = 1;
+
+/// 密钥值的最短长度，过短的值在文本中随处可见，无法可靠地脱敏
+const MIN_SECRET_LEN: usize = 4;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 解锁方式
+#[derive(Debug, Clone)]
+pub enum VaultKey {
+    /// 主密码
+    Password(String),
+    /// 密钥文件，以文件的全部内容作为口令
+    KeyFile(PathBuf),
+}
+
+impl VaultKey {
+    fn material(&self) -> Result<Vec<u8>> {
+        let material = match self {
+            VaultKey::Password(password) => password.as_bytes().to_vec(),
+            VaultKey::KeyFile(path) => fs::read(path).map_err(|e| Error::io("读取密钥文件失败", path, e))?,
+        };
+        if material.is_empty() {
+            return Err(Error::Validation("主密码或密钥文件内容不能为空".to_string()));
+        }
+        Ok(material)
+    }
+}
+
+/// 已解锁的密钥，用于替换引用和脱敏
+#[derive(Default)]
+pub struct Secrets {
+    values: BTreeMap<String, String>,
+    /// 匹配密钥值及其编码形式，没有可脱敏的值时为 `None`
+    pattern: Option<Regex>,
+}
+
+// 不输出密钥值
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Secrets").field("names", &self.values.keys().collect::<Vec<_>>()).finish()
+    }
+}
+
+impl Secrets {
+    pub fn new(values: BTreeMap<String, String>) -> Self {
+        let pattern = mask_pattern(values.values());
+        Self { values, pattern }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// 替换文本中的 `{secret:名称}`，不存在的密钥原样保留
+    pub fn resolve(&self, text: &str) -> String {
+        if !text.contains("{secret:") {
+            return text.to_string();
+        }
+        reference_regex()
+            .replace_all(text, |caps: &regex::Captures| {
+                self.values.get(&caps[1]).cloned().unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    }
+
+    /// 替换认证配置中的密钥引用
+    pub fn resolve_auth(&self, auth: &AuthConfig) -> AuthConfig {
+        AuthConfig {
+            client_id: self.resolve(&auth.client_id),
+            username: self.resolve(&auth.username),
+            password: self.resolve(&auth.password),
+            ..auth.clone()
+        }
+    }
+
+    /// 把文本中出现的密钥值替换为 `******`
+    ///
+    /// 同时识别 URL 编码（如 `p%40ss`、空格写作 `+`）和 JSON 转义后的形式；
+    /// 短于 [`MIN_SECRET_LEN`] 的值不脱敏。
+    pub fn mask(&self, text: &str) -> String {
+        match &self.pattern {
+            Some(pattern) => pattern.replace_all(text, MASK).into_owned(),
+            None => text.to_string(),
+        }
+    }
+
+    /// 脱敏 JSON 中的字符串（含对象的键）
+    pub fn mask_json(&self, value: &JsonValue) -> JsonValue {
+        match value {
+            JsonValue::String(s) => JsonValue::String(self.mask(s)),
+            JsonValue::Array(items) => JsonValue::Array(items.iter().map(|v| self.mask_json(v)).collect()),
+            JsonValue::Object(obj) => JsonValue::Object(
+                obj.iter().map(|(k, v)| (self.mask(k), self.mask_json(v))).collect(),
+            ),
+            _ => value.clone(),
+        }
+    }
+
+    /// 脱敏执行结果中的请求、响应、验证结果和错误信息
+    pub fn mask_result(&self, result: &mut ExecutionResult) {
+        if self.is_empty() {
+            return;
+        }
+        result.request_url = self.mask(&result.request_url);
+        for value in result.request_headers.values_mut().chain(result.response_headers.values_mut()) {
+            *value = self.mask(value);
+        }
+        for body in result.request_body.iter_mut().chain(result.response_body.iter_mut()) {
+            *body = self.mask_json(body);
+        }
+        for validation in &mut result.validations {
+            validation.expected = self.mask(&validation.expected);
+            validation.actual = self.mask(&validation.actual);
+        }
+        let errors = result.attempts.iter_mut().map(|a| &mut a.error).chain([&mut result.error]);
+        for error in errors.flatten() {
+            *error = self.mask(error);
+        }
+    }
+}
+
+/// `{secret:名称}` 引用
+fn reference_regex() -> Regex {
+    Regex::new(r"\{secret:([A-Za-z0-9_.\-]+)\}").unwrap()
+}
+
+/// 匹配任一密钥值的正则，较长的值在前，避免某个值是另一个值的一部分时只遮住一半
+fn mask_pattern<'a>(values: impl Iterator<Item = &'a String>) -> Option<Regex> {
+    let mut values: Vec<&String> = values.filter(|v| v.chars().count() >= MIN_SECRET_LEN).collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+    values.dedup();
+
+    let mut literals = Vec::new();
+    let mut alternatives = Vec::new();
+    for value in values {
+        // JSON 转义后的形式（如错误信息中序列化的请求体），与原值不同时才需要单独匹配
+        let escaped = serde_json::to_string(value).unwrap_or_default();
+        let escaped = &escaped[1..escaped.len() - 1];
+        if escaped != value {
+            literals.push(regex::escape(escaped));
+            alternatives.push(regex::escape(escaped));
+        }
+        literals.push(regex::escape(value));
+        alternatives.push(encoded_pattern(value));
+    }
+    // 值很长时编码形式的正则可能超出大小限制，退回只匹配原值和 JSON 转义形式
+    Regex::new(&alternatives.join("|")).or_else(|_| Regex::new(&literals.join("|"))).ok()
+}
+
+/// 每个字符可以是原样或 URL 编码（`%XX`，不区分大小写）的形式，空格还可以写作 `+`
+fn encoded_pattern(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            let mut buf = [0u8; 4];
+            let raw = c.encode_utf8(&mut buf);
+            let encoded: String = raw.bytes().map(|b| format!("%{:02x}", b)).collect();
+            let plus = if c == ' ' { r"|\+" } else { "" };
+            format!("(?:{}|(?i:{}){})", regex::escape(raw), encoded, plus)
+        })
+        .collect()
+}
+
+/// 密钥名称只允许字母、数字和 `_` `.` `-`
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')) {
+        return Err(Error::Validation(format!("无效的密钥名称 '{}'，只能包含字母、数字和 _ . -", name)));
+    }
+    Ok(())
+}
+
+/// 密钥库文件内容，二进制字段为 base64
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// 已解锁的密钥库
+pub struct Vault {
+    path: PathBuf,
+    salt: Vec<u8>,
+    key: [u8; 32],
+    secrets: BTreeMap<String, String>,
+}
+
+impl fmt::Debug for Vault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vault")
+            .field("path", &self.path)
+            .field("names", &self.secrets.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Vault {
+    /// 应用数据目录下的密钥库文件
+    pub fn default_path() -> Result<PathBuf> {
+        AppData::get_data_dir()
+            .map(|dir| dir.join(VAULT_FILE))
+            .ok_or_else(|| Error::Internal("无法获取应用数据目录".to_string()))
+    }
+
+    /// 解锁密钥库，文件不存在时创建空的密钥库（首次添加密钥时写入文件）
+    pub fn unlock(path: &Path, key: &VaultKey) -> Result<Self> {
+        let material = key.material()?;
+
+        if !path.exists() {
+            let mut salt = vec![0u8; SALT_LEN];
+            random::fill(&mut salt)?;
+            let key = derive_key(&material, &salt)?;
+            return Ok(Self { path: path.to_path_buf(), salt, key, secrets: BTreeMap::new() });
+        }
+
+        let content = fs::read_to_string(path).map_err(|e| Error::io("读取密钥库失败", path, e))?;
+        let file: VaultFile = serde_json::from_str(&content)
+            .map_err(|e| Error::Vault(format!("密钥库文件已损坏: {}", e)))?;
+        if file.version != VAULT_VERSION {
+            return Err(Error::Vault(format!("不支持的密钥库版本: {}", file.version)));
+        }
+
+        let decode = |field: &str| {
+            BASE64.decode(field).map_err(|e| Error::Vault(format!("密钥库文件已损坏: {}", e)))
+        };
+        let salt = decode(&file.salt)?;
+        let nonce = decode(&file.nonce)?;
+        let ciphertext = decode(&file.ciphertext)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(Error::Vault("密钥库文件已损坏: nonce 长度不正确".to_string()));
+        }
+
+        let key = derive_key(&material, &salt)?;
+        let plaintext = cipher(&key)
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| Error::Vault("主密码或密钥文件不正确".to_string()))?;
+        let secrets = serde_json::from_slice(&plaintext)
+            .map_err(|e| Error::Vault(format!("密钥库文件已损坏: {}", e)))?;
+
+        Ok(Self { path: path.to_path_buf(), salt, key, secrets })
+    }
+
+    /// 密钥名称，按名称排序
+    pub fn names(&self) -> Vec<String> {
+        self.secrets.keys().cloned().collect()
+    }
+
+    /// 添加或覆盖密钥并写入文件
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        validate_name(name)?;
+        if value.chars().count() < MIN_SECRET_LEN {
+            return Err(Error::Validation(format!("密钥值至少需要 {} 个字符", MIN_SECRET_LEN)));
+        }
+        let previous = self.secrets.insert(name.to_string(), value.to_string());
+        if let Err(e) = self.save() {
+            // 写入失败时恢复内存中的内容，与文件保持一致
+            match previous {
+                Some(previous) => self.secrets.insert(name.to_string(), previous),
+                None => self.secrets.remove(name),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// 删除密钥并写入文件，不存在时返回 false
+    pub fn remove(&mut self, name: &str) -> Result<bool> {
+        let Some(previous) = self.secrets.remove(name) else {
+            return Ok(false);
+        };
+        if let Err(e) = self.save() {
+            self.secrets.insert(name.to_string(), previous);
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// 当前密钥的快照
+    pub fn secrets(&self) -> Arc<Secrets> {
+        Arc::new(Secrets::new(self.secrets.clone()))
+    }
+
+    /// 加密写入文件，每次写入使用新的 nonce
+    fn save(&self) -> Result<()> {
+        let plaintext = serde_json::to_vec(&self.secrets)
+            .map_err(|e| Error::Internal(format!("序列化密钥失败: {}", e)))?;
+        let mut nonce = [0u8; NONCE_LEN];
+        random::fill(&mut nonce)?;
+        let ciphertext = cipher(&self.key)
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| Error::Internal("加密密钥库失败".to_string()))?;
+
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            salt: BASE64.encode(&self.salt),
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        };
+        let content = serde_json::to_string_pretty(&file)
+            .map_err(|e| Error::Internal(format!("序列化密钥库失败: {}", e)))?;
+
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|e| Error::io("创建数据目录失败", dir, e))?;
+        }
+        // 先写临时文件再替换，避免写到一半时损坏密钥库
+        let temp = self.path.with_extension("tmp");
+        fs::write(&temp, content).map_err(|e| Error::io("写入密钥库失败", &temp, e))?;
+        fs::rename(&temp, &self.path).map_err(|e| Error::io("写入密钥库失败", &self.path, e))
+    }
+}
+
+fn derive_key(material: &[u8], salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(material, salt, &mut key)
+        .map_err(|e| Error::Vault(format!("派生密钥失败: {}", e)))?;
+    Ok(key)
+}
+
+fn cipher(key: &[u8; 32]) -> Aes256Gcm {
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+}
+
+/// 应用内的密钥库状态，解锁后保存在内存中直到锁定或退出
+#[derive(Default)]
+pub struct VaultState {
+    vault: Mutex<Option<Vault>>,
+}
+
+impl VaultState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 已解锁的密钥，未解锁时为空
+    pub fn secrets(&self) -> Arc<Secrets> {
+        self.vault
+            .lock()
+            .ok()
+            .and_then(|vault| vault.as_ref().map(Vault::secrets))
+            .unwrap_or_default()
+    }
+
+    fn with_vault<T>(&self, f: impl FnOnce(&mut Vault) -> Result<T>) -> Result<T> {
+        let mut vault = self.vault.lock().map_err(|_| Error::Internal("密钥库状态异常".to_string()))?;
+        match vault.as_mut() {
+            Some(vault) => f(vault),
+            None => Err(Error::Vault("密钥库未解锁".to_string())),
+        }
+    }
+}
+
+/// 密钥库状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultStatus {
+    /// 密钥库文件是否已存在
+    pub exists: bool,
+    pub unlocked: bool,
+}
+
+// ============ Tauri 命令 ============
+
+/// 查询密钥库是否已创建、是否已解锁
+#[tauri::command]
+pub fn get_vault_status(vault: State<'_, VaultState>) -> Result<VaultStatus> {
+    Ok(VaultStatus {
+        exists: Vault::default_path()?.exists(),
+        unlocked: vault.vault.lock().map(|v| v.is_some()).unwrap_or(false),
+    })
+}
+
+/// 用主密码或密钥文件解锁密钥库（二选一），密钥库不存在时以此创建；返回密钥名称
+#[tauri::command]
+pub fn unlock_vault(
+    master_password: Option<String>,
+    key_file: Option<String>,
+    vault: State<'_, VaultState>,
+) -> Result<Vec<String>> {
+    let key = match (master_password, key_file) {
+        (Some(password), None) => VaultKey::Password(password),
+        (None, Some(path)) => VaultKey::KeyFile(PathBuf::from(path)),
+        _ => return Err(Error::Validation("需要提供主密码或密钥文件其中之一".to_string())),
+    };
+    let unlocked = Vault::unlock(&Vault::default_path()?, &key)?;
+    let names = unlocked.names();
+    *vault.vault.lock().map_err(|_| Error::Internal("密钥库状态异常".to_string()))? = Some(unlocked);
+    Ok(names)
+}
+
+/// 锁定密钥库，清除内存中的密钥
+#[tauri::command]
+pub fn lock_vault(vault: State<'_, VaultState>) -> Result<()> {
+    *vault.vault.lock().map_err(|_| Error::Internal("密钥库状态异常".to_string()))? = None;
+    Ok(())
+}
+
+/// 添加或覆盖密钥
+#[tauri::command]
+pub fn add_secret(name: String, value: String, vault: State<'_, VaultState>) -> Result<()> {
+    vault.with_vault(|v| v.set(&name, &value))
+}
+
+/// 列出密钥名称，不返回密钥值
+#[tauri::command]
+pub fn list_secrets(vault: State<'_, VaultState>) -> Result<Vec<String>> {
+    vault.with_vault(|v| Ok(v.names()))
+}
+
+/// 删除密钥，不存在时返回 false
+#[tauri::command]
+pub fn remove_secret(name: String, vault: State<'_, VaultState>) -> Result<bool> {
+    vault.with_vault(|v| v.remove(&name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secrets(values: &[(&str, &str)]) -> Secrets {
+        Secrets::new(values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+    }
+
+    #[test]
+    fn mask_replaces_plain_value() {
+        let secrets = secrets(&[("token", "s3cr3t-value")]);
+        assert_eq!(secrets.mask("Bearer s3cr3t-value"), "Bearer ******");
+    }
+
+    #[test]
+    fn mask_replaces_longer_value_first() {
+        let secrets = secrets(&[("short", "abcd"), ("long", "abcdefgh")]);
+        assert_eq!(secrets.mask("x=abcdefgh&y=abcd"), "x=******&y=******");
+    }
+
+    #[test]
+    fn mask_replaces_url_encoded_value() {
+        let secrets = secrets(&[("password", "p@ss w/rd")]);
+        assert_eq!(secrets.mask("password=p%40ss+w%2Frd"), "password=******");
+        assert_eq!(secrets.mask("password=p%40ss%20w%2frd"), "password=******");
+        assert_eq!(secrets.mask("/login/p@ss%20w/rd"), "/login/******");
+    }
+
+    #[test]
+    fn mask_replaces_url_encoded_non_ascii_value() {
+        let secrets = secrets(&[("password", "密码1234")]);
+        assert_eq!(secrets.mask("q=%E5%AF%86%E7%A0%811234"), "q=******");
+    }
+
+    #[test]
+    fn mask_replaces_json_escaped_value() {
+        let secrets = secrets(&[("password", r#"a"b\c"#)]);
+        let body = serde_json::json!({ "password": r#"a"b\c"# }).to_string();
+        assert_eq!(secrets.mask(&body), r#"{"password":"******"}"#);
+    }
+
+    #[test]
+    fn mask_skips_short_values() {
+        let secrets = secrets(&[("pin", "12"), ("token", "abcd")]);
+        assert_eq!(secrets.mask("id=12&token=abcd"), "id=12&token=******");
+    }
+
+    #[test]
+    fn mask_without_secrets_keeps_text() {
+        assert_eq!(Secrets::default().mask("p@ss"), "p@ss");
+    }
+
+    #[test]
+    fn mask_result_covers_every_field() {
+        use crate::config::{AttemptRecord, ExecutionStatus, ValidationResult};
+
+        let secrets = secrets(&[("token", "s3cr3t-value")]);
+        let headers: indexmap::IndexMap<String, String> =
+            [("Authorization".to_string(), "Bearer s3cr3t-value".to_string())].into_iter().collect();
+        let body = serde_json::json!({ "token": "s3cr3t-value", "s3cr3t-value": ["s3cr3t-value"] });
+        let mut result = ExecutionResult {
+            success: false,
+            status: ExecutionStatus::Failed,
+            step_name: "login".to_string(),
+            request_url: "http://localhost/login?token=s3cr3t-value".to_string(),
+            request_method: "POST".to_string(),
+            request_headers: headers.clone(),
+            request_body: Some(body.clone()),
+            response_status: 500,
+            response_headers: headers,
+            response_body: Some(body),
+            validations: vec![ValidationResult {
+                field: "token".to_string(),
+                operator: "equals".to_string(),
+                expected: "s3cr3t-value".to_string(),
+                actual: "\"s3cr3t-value\"".to_string(),
+                passed: false,
+            }],
+            duration_ms: 10,
+            error: Some("请求失败: http://localhost/login?token=s3cr3t%2Dvalue".to_string()),
+            error_kind: None,
+            attempts: vec![AttemptRecord {
+                attempt: 1,
+                status: 0,
+                duration_ms: 5,
+                error: Some("连接失败: s3cr3t-value".to_string()),
+                error_kind: None,
+            }],
+        };
+
+        secrets.mask_result(&mut result);
+        let serialized = serde_json::to_string(&result).unwrap();
+        assert!(!serialized.contains("s3cr3t"), "{}", serialized);
+        assert_eq!(result.request_url, "http://localhost/login?token=******");
+        assert_eq!(result.request_headers["Authorization"], "Bearer ******");
+    }
+
+    /// 测试用的密钥库路径，文件不存在
+    fn temp_vault_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("yuml-ddt-{}-{:016x}.vault", name, random::next_u64()))
+    }
+
+    #[test]
+    fn vault_round_trip() {
+        let path = temp_vault_path("round-trip");
+        let key = VaultKey::Password("master password".to_string());
+
+        let mut vault = Vault::unlock(&path, &key).unwrap();
+        assert!(vault.names().is_empty());
+        assert!(!path.exists(), "解锁不应创建文件");
+
+        vault.set("api_key", "key-1234").unwrap();
+        vault.set("db.password", "p@ss w0rd").unwrap();
+        vault.set("api_key", "key-5678").unwrap();
+        assert!(vault.remove("db.password").unwrap());
+        assert!(!vault.remove("missing").unwrap());
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("key-5678"), "密钥库文件中不应出现明文");
+
+        let reopened = Vault::unlock(&path, &key).unwrap();
+        assert_eq!(reopened.names(), vec!["api_key".to_string()]);
+        assert_eq!(reopened.secrets().resolve("X-Api-Key: {secret:api_key}"), "X-Api-Key: key-5678");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn vault_rejects_wrong_password() {
+        let path = temp_vault_path("wrong-password");
+        let mut vault = Vault::unlock(&path, &VaultKey::Password("right".to_string())).unwrap();
+        vault.set("token", "s3cr3t-value").unwrap();
+
+        let error = Vault::unlock(&path, &VaultKey::Password("wrong".to_string())).unwrap_err();
+        assert!(matches!(&error, Error::Vault(message) if message == "主密码或密钥文件不正确"), "{:?}", error);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn vault_unlocks_with_key_file() {
+        let path = temp_vault_path("key-file");
+        let key_file = path.with_extension("key");
+        fs::write(&key_file, b"key file content").unwrap();
+        let key = VaultKey::KeyFile(key_file.clone());
+
+        Vault::unlock(&path, &key).unwrap().set("token", "s3cr3t-value").unwrap();
+        assert_eq!(Vault::unlock(&path, &key).unwrap().names(), vec!["token".to_string()]);
+        assert!(Vault::unlock(&path, &VaultKey::Password("key file content!".to_string())).is_err());
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&key_file).unwrap();
+    }
+
+    #[test]
+    fn vault_set_validates_name_and_value() {
+        let path = temp_vault_path("validate");
+        let mut vault = Vault::unlock(&path, &VaultKey::Password("master".to_string())).unwrap();
+
+        assert!(matches!(vault.set("bad name", "s3cr3t-value"), Err(Error::Validation(_))));
+        assert!(matches!(vault.set("pin", "123"), Err(Error::Validation(_))));
+        assert!(vault.names().is_empty());
+        assert!(!path.exists());
+    }
+}
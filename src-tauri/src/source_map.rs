@@ -0,0 +1,241 @@
+//! YAML 源码位置查找
+//!
+//! serde_yaml 反序列化后不保留位置信息，这里按缩进扫描原文，
+//! 根据键路径（如 `["steps", "login", "validate", "0"]`）定位对应行列。
+//! 只处理块风格的映射和序列，流式写法（`{ a: 1 }`）内部的键无法定位。
+
+use serde::{Deserialize, Serialize};
+
+/// 源码位置（行列均从 1 开始）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// 一行有效内容：序列项 `- key: v` 会拆成 `-` 和 `key: v` 两条
+struct Entry<'a> {
+    line: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+fn entries(content: &str) -> Vec<Entry<'_>> {
+    let mut result = Vec::new();
+
+    for (line, raw) in content.lines().enumerate() {
+        let trimmed = raw.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("---") {
+            continue;
+        }
+
+        let mut indent = raw.len() - trimmed.len();
+        let mut text = trimmed;
+
+        // 展开 "- - key: v" 这类序列项
+        while text == "-" || text.starts_with("- ") {
+            result.push(Entry { line, indent, text: "-" });
+            let rest = text[1..].trim_start();
+            if rest.is_empty() {
+                text = rest;
+                break;
+            }
+            indent += text.len() - rest.len();
+            text = rest;
+        }
+
+        if !text.is_empty() {
+            result.push(Entry { line, indent, text });
+        }
+    }
+
+    result
+}
+
+/// 提取映射行的键名，非映射行返回 None
+fn entry_key(text: &str) -> Option<&str> {
+    if let Some(quote) = text.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let end = text[1..].find(quote)? + 1;
+        let rest = text[end + 1..].trim_start();
+        return rest.starts_with(':').then(|| &text[1..end]);
+    }
+
+    let colon = text.find(": ").or_else(|| text.strip_suffix(':').map(|s| s.len()))?;
+    Some(text[..colon].trim_end())
+}
+
+/// 定位键路径对应的位置，序列下标用数字字符串表示
+pub fn locate(content: &str, path: &[&str]) -> Option<Position> {
+    let (position, depth) = locate_prefix(content, path)?;
+    (depth == path.len()).then_some(position)
+}
+
+/// 定位键路径中能找到的最深一级，返回位置及匹配到的层数
+pub fn locate_nearest(content: &str, path: &[&str]) -> Option<Position> {
+    locate_prefix(content, path).map(|(position, _)| position)
+}
+
+fn locate_prefix(content: &str, path: &[&str]) -> Option<(Position, usize)> {
+    let entries = entries(content);
+    let mut start = 0;
+    // (缩进, 是否为映射键)：映射键下的序列允许与键同级缩进
+    let mut parent: Option<(usize, bool)> = None;
+    let mut found = None;
+
+    for (depth, segment) in path.iter().enumerate() {
+        let mut child_indent: Option<usize> = None;
+        let mut seq_index = 0;
+        let mut matched = None;
+
+        for (i, entry) in entries.iter().enumerate().skip(start) {
+            if let Some((indent, is_key)) = parent {
+                let same_level_seq = is_key && entry.indent == indent && entry.text == "-";
+                if entry.indent < indent || (entry.indent == indent && !same_level_seq) {
+                    break;
+                }
+            }
+            let level = *child_indent.get_or_insert(entry.indent);
+            if entry.indent != level {
+                continue;
+            }
+
+            let is_match = if entry.text == "-" {
+                let hit = segment.parse::<usize>().ok() == Some(seq_index);
+                seq_index += 1;
+                hit
+            } else {
+                entry_key(entry.text) == Some(*segment)
+            };
+
+            if is_match {
+                matched = Some(i);
+                break;
+            }
+        }
+
+        let Some(i) = matched else { break };
+        let entry = &entries[i];
+        found = Some((
+            Position { line: entry.line + 1, column: entry.indent + 1 },
+            depth + 1,
+        ));
+        parent = Some((entry.indent, entry.text != "-"));
+        start = i + 1;
+    }
+
+    found
+}
+
+/// 查找某一行所属的键路径（[`locate`] 的逆操作），序列项用下标表示
+///
+/// 空行、注释行归属于其上方最近的一行
+pub fn path_at(content: &str, line: usize) -> Vec<String> {
+    // (缩进, 路径段, 是否为序列项)
+    let mut stack: Vec<(usize, String, bool)> = Vec::new();
+
+    for entry in entries(content).iter().take_while(|e| e.line < line) {
+        let is_item = entry.text == "-";
+        let mut index = 0;
+
+        while let Some((indent, _, top_is_item)) = stack.last() {
+            // 映射键下的序列项允许与键同级缩进
+            let same_level_seq = is_item && !top_is_item && *indent == entry.indent;
+            if *indent < entry.indent || same_level_seq {
+                break;
+            }
+            let (indent, segment, top_is_item) = stack.pop().unwrap_or_default();
+            if is_item && top_is_item && indent == entry.indent {
+                index = segment.parse::<usize>().map(|i| i + 1).unwrap_or(0);
+            }
+        }
+
+        if is_item {
+            stack.push((entry.indent, index.to_string(), true));
+        } else if let Some(key) = entry_key(entry.text) {
+            stack.push((entry.indent, key.to_string(), false));
+        }
+    }
+
+    stack.into_iter().map(|(_, segment, _)| segment).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+# 注释
+global:
+  name: demo
+steps:
+  login:
+    method: POST
+    # 内部注释
+    validate:
+      - field: status
+        operator: equals
+      - field: \"data.token\"
+        operator: exists
+    \"quoted key\": 1
+  'other':
+    path: /x
+test_cases:
+  smoke:
+    steps:
+    - login
+    - other
+";
+
+    fn at(line: usize, column: usize) -> Option<Position> {
+        Some(Position { line, column })
+    }
+
+    #[test]
+    fn locates_nested_keys() {
+        assert_eq!(locate(CONFIG, &["global", "name"]), at(3, 3));
+        assert_eq!(locate(CONFIG, &["steps", "login", "method"]), at(6, 5));
+        assert_eq!(locate(CONFIG, &["steps", "login", "validate"]), at(8, 5));
+    }
+
+    #[test]
+    fn locates_sequence_items() {
+        assert_eq!(locate(CONFIG, &["steps", "login", "validate", "0"]), at(9, 7));
+        assert_eq!(locate(CONFIG, &["steps", "login", "validate", "1", "operator"]), at(12, 9));
+        assert_eq!(locate(CONFIG, &["steps", "login", "validate", "2"]), None);
+        // 与键同级缩进的序列
+        assert_eq!(locate(CONFIG, &["test_cases", "smoke", "steps", "1"]), at(20, 5));
+    }
+
+    #[test]
+    fn locates_quoted_keys() {
+        assert_eq!(locate(CONFIG, &["steps", "login", "quoted key"]), at(13, 5));
+        assert_eq!(locate(CONFIG, &["steps", "other", "path"]), at(15, 5));
+    }
+
+    #[test]
+    fn does_not_match_keys_outside_parent() {
+        assert_eq!(locate(CONFIG, &["steps", "login", "path"]), None);
+        assert_eq!(locate(CONFIG, &["global", "steps"]), None);
+        assert_eq!(locate_nearest(CONFIG, &["steps", "login", "missing"]), at(5, 3));
+        assert_eq!(locate_nearest(CONFIG, &["missing"]), None);
+    }
+
+    #[test]
+    fn finds_path_at_line() {
+        assert_eq!(path_at(CONFIG, 3), ["global", "name"]);
+        assert_eq!(path_at(CONFIG, 12), ["steps", "login", "validate", "1", "operator"]);
+        assert_eq!(path_at(CONFIG, 20), ["test_cases", "smoke", "steps", "1"]);
+        // 注释行归属于上方最近的一行
+        assert_eq!(path_at(CONFIG, 7), ["steps", "login", "method"]);
+        assert!(path_at(CONFIG, 1).is_empty());
+    }
+
+    #[test]
+    fn path_at_is_inverse_of_locate() {
+        for line in [2, 6, 10, 13, 15, 19] {
+            let path = path_at(CONFIG, line);
+            let path: Vec<&str> = path.iter().map(String::as_str).collect();
+            assert_eq!(locate(CONFIG, &path).map(|p| p.line), Some(line), "{:?}", path);
+        }
+    }
+}
@@ -7,6 +7,8 @@ use std::fs;
 use std::path::PathBuf;
 use chrono::{DateTime, Utc};
 
+use crate::error::{Error, Result};
+
 /// 应用数据目录名
 const APP_DIR: &str = "yuml-ddt";
 /// 应用数据文件名
@@ -53,7 +55,7 @@ pub struct AppData {
 
 impl AppData {
     /// 获取应用数据目录
-    fn get_data_dir() -> Option<PathBuf> {
+    pub(crate) fn get_data_dir() -> Option<PathBuf> {
         dirs::data_dir().map(|p| p.join(APP_DIR))
     }
 
@@ -77,22 +79,22 @@ impl AppData {
     }
 
     /// 保存应用数据
-    pub fn save(&self) -> Result<(), String> {
+    pub fn save(&self) -> Result<()> {
         let data_dir = Self::get_data_dir()
-            .ok_or_else(|| "无法获取应用数据目录".to_string())?;
+            .ok_or_else(|| Error::Internal("无法获取应用数据目录".to_string()))?;
         
         // 确保目录存在
         if !data_dir.exists() {
             fs::create_dir_all(&data_dir)
-                .map_err(|e| format!("创建数据目录失败: {}", e))?;
+                .map_err(|e| Error::io("创建数据目录失败", &data_dir, e))?;
         }
 
         let file_path = data_dir.join(APP_DATA_FILE);
         let content = serde_json::to_string_pretty(self)
-            .map_err(|e| format!("序列化数据失败: {}", e))?;
+            .map_err(|e| Error::io("序列化数据失败", &file_path, e.into()))?;
         
         fs::write(&file_path, content)
-            .map_err(|e| format!("写入数据文件失败: {}", e))?;
+            .map_err(|e| Error::io("写入数据文件失败", &file_path, e))?;
 
         Ok(())
     }
@@ -146,13 +148,13 @@ pub fn get_app_data() -> AppData {
 
 /// 保存应用数据
 #[tauri::command]
-pub fn save_app_data(data: AppData) -> Result<(), String> {
+pub fn save_app_data(data: AppData) -> Result<()> {
     data.save()
 }
 
 /// 添加最近工作区
 #[tauri::command]
-pub fn add_recent_workspace(path: String) -> Result<AppData, String> {
+pub fn add_recent_workspace(path: String) -> Result<AppData> {
     let mut data = AppData::load();
     data.add_recent_workspace(&path);
     data.save()?;
@@ -161,7 +163,7 @@ pub fn add_recent_workspace(path: String) -> Result<AppData, String> {
 
 /// 移除最近工作区
 #[tauri::command]
-pub fn remove_recent_workspace(path: String) -> Result<AppData, String> {
+pub fn remove_recent_workspace(path: String) -> Result<AppData> {
     let mut data = AppData::load();
     data.remove_recent_workspace(&path);
     data.save()?;
@@ -170,7 +172,7 @@ pub fn remove_recent_workspace(path: String) -> Result<AppData, String> {
 
 /// 清空最近工作区
 #[tauri::command]
-pub fn clear_recent_workspaces() -> Result<AppData, String> {
+pub fn clear_recent_workspaces() -> Result<AppData> {
     let mut data = AppData::load();
     data.clear_recent_workspaces();
     data.save()?;
@@ -185,7 +187,7 @@ pub fn get_recent_workspaces() -> Vec<RecentWorkspace> {
 
 /// 更新应用设置
 #[tauri::command]
-pub fn update_settings(settings: AppSettings) -> Result<(), String> {
+pub fn update_settings(settings: AppSettings) -> Result<()> {
     let mut data = AppData::load();
     data.settings = settings;
     data.save()
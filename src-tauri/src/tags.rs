@@ -0,0 +1,240 @@
+//! 标签过滤表达式
+//!
+//! 语法：`smoke and not slow`、`(order or payment) and not flaky`，
+//! 也可以用 `&&` / `||` / `!`。空表达式匹配全部。
+
+use crate::error::Error;
+
+/// 表达式最大长度
+const MAX_SOURCE_LEN: usize = 1024;
+/// 最大嵌套深度（括号和 `not`）
+const MAX_DEPTH: usize = 64;
+
+/// 标签过滤表达式
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagFilter {
+    All,
+    Tag(String),
+    Not(Box<TagFilter>),
+    And(Box<TagFilter>, Box<TagFilter>),
+    Or(Box<TagFilter>, Box<TagFilter>),
+}
+
+impl TagFilter {
+    /// 解析标签表达式
+    pub fn parse(source: &str) -> Result<Self, Error> {
+        Self::parse_tokens(source).map_err(Error::Validation)
+    }
+
+    fn parse_tokens(source: &str) -> Result<Self, String> {
+        if source.len() > MAX_SOURCE_LEN {
+            return Err(format!("标签表达式过长（最多 {} 个字符）", MAX_SOURCE_LEN));
+        }
+        let tokens = tokenize(source)?;
+        if tokens.is_empty() {
+            return Ok(TagFilter::All);
+        }
+
+        let mut parser = Parser { tokens, pos: 0, depth: 0 };
+        let filter = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(filter),
+            Some(token) => Err(format!("标签表达式错误: 多余的 '{}'", token)),
+        }
+    }
+
+    /// 判断标签集合是否满足表达式
+    pub fn matches<S: AsRef<str>>(&self, tags: &[S]) -> bool {
+        match self {
+            TagFilter::All => true,
+            TagFilter::Tag(tag) => tags.iter().any(|t| t.as_ref() == tag),
+            TagFilter::Not(inner) => !inner.matches(tags),
+            TagFilter::And(a, b) => a.matches(tags) && b.matches(tags),
+            TagFilter::Or(a, b) => a.matches(tags) || b.matches(tags),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' || c == '!' {
+            tokens.push(c.to_string());
+            chars.next();
+        } else if c == '&' || c == '|' {
+            chars.next();
+            if chars.next() != Some(c) {
+                return Err(format!("标签表达式错误: 应为 '{}{}'", c, c));
+            }
+            tokens.push(if c == '&' { "and" } else { "or" }.to_string());
+        } else if is_tag_char(c) {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek().filter(|c| is_tag_char(**c)) {
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(word);
+        } else {
+            return Err(format!("标签表达式错误: 无法识别的字符 '{}'", c));
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':' | '/')
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn eat(&mut self, token: &str) -> bool {
+        if self.tokens.get(self.pos).is_some_and(|t| t == token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<TagFilter, String> {
+        let mut left = self.parse_and()?;
+        while self.eat("or") {
+            left = TagFilter::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<TagFilter, String> {
+        let mut left = self.parse_not()?;
+        while self.eat("and") {
+            left = TagFilter::And(Box::new(left), Box::new(self.parse_not()?));
+        }
+        Ok(left)
+    }
+
+    /// `not` 和括号的嵌套都经过这里，在此限制深度
+    fn parse_not(&mut self) -> Result<TagFilter, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("标签表达式错误: 嵌套过深".to_string());
+        }
+        let result = if self.eat("not") || self.eat("!") {
+            self.parse_not().map(|inner| TagFilter::Not(Box::new(inner)))
+        } else {
+            self.parse_atom()
+        };
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_atom(&mut self) -> Result<TagFilter, String> {
+        if self.eat("(") {
+            let inner = self.parse_or()?;
+            if !self.eat(")") {
+                return Err("标签表达式错误: 缺少 ')'".to_string());
+            }
+            return Ok(inner);
+        }
+
+        match self.tokens.get(self.pos) {
+            Some(token) if !matches!(token.as_str(), "and" | "or" | ")") => {
+                self.pos += 1;
+                Ok(TagFilter::Tag(token.clone()))
+            }
+            Some(token) => Err(format!("标签表达式错误: 意外的 '{}'", token)),
+            None => Err("标签表达式错误: 表达式不完整".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str) -> Box<TagFilter> {
+        Box::new(TagFilter::Tag(name.to_string()))
+    }
+
+    fn parse_err(source: &str) -> String {
+        TagFilter::parse(source).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn empty_expression_matches_all() {
+        assert_eq!(TagFilter::parse("  ").unwrap(), TagFilter::All);
+        assert!(TagFilter::All.matches::<&str>(&[]));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let filter = TagFilter::parse("a or b and c").unwrap();
+        assert_eq!(filter, TagFilter::Or(tag("a"), Box::new(TagFilter::And(tag("b"), tag("c")))));
+        assert_eq!(TagFilter::parse("a || b && c").unwrap(), filter);
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and() {
+        let filter = TagFilter::parse("not a and b").unwrap();
+        assert_eq!(filter, TagFilter::And(Box::new(TagFilter::Not(tag("a"))), tag("b")));
+        assert_eq!(TagFilter::parse("!a && b").unwrap(), filter);
+        assert_eq!(TagFilter::parse("not not a").unwrap(), TagFilter::Not(Box::new(TagFilter::Not(tag("a")))));
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        let filter = TagFilter::parse("(a or b) and not (c)").unwrap();
+        assert_eq!(
+            filter,
+            TagFilter::And(Box::new(TagFilter::Or(tag("a"), tag("b"))), Box::new(TagFilter::Not(tag("c"))))
+        );
+        assert!(filter.matches(&["b"]));
+        assert!(!filter.matches(&["a", "c"]));
+        assert!(!filter.matches(&["d"]));
+    }
+
+    #[test]
+    fn matches_tag_sets() {
+        let filter = TagFilter::parse("smoke and not slow").unwrap();
+        assert!(filter.matches(&["smoke", "order"]));
+        assert!(!filter.matches(&["smoke", "slow"]));
+        assert!(!filter.matches::<String>(&[]));
+        assert!(TagFilter::parse("team:api/v2").unwrap().matches(&["team:api/v2"]));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert!(parse_err("a & b").contains("应为 '&&'"));
+        assert!(parse_err("a | b").contains("应为 '||'"));
+        assert!(parse_err("(a or b").contains("缺少 ')'"));
+        assert!(parse_err("a b").contains("多余的 'b'"));
+        assert!(parse_err("a and").contains("表达式不完整"));
+        assert!(parse_err("or a").contains("意外的 'or'"));
+        assert!(parse_err("a, b").contains("无法识别的字符 ','"));
+    }
+
+    #[test]
+    fn source_length_limit() {
+        assert!(TagFilter::parse(&"a".repeat(MAX_SOURCE_LEN)).is_ok());
+        assert!(parse_err(&"a".repeat(MAX_SOURCE_LEN + 1)).contains("过长"));
+    }
+
+    #[test]
+    fn nesting_depth_limit() {
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(TagFilter::parse(&nested(MAX_DEPTH - 1)).is_ok());
+        assert!(parse_err(&nested(MAX_DEPTH)).contains("嵌套过深"));
+        assert!(parse_err(&format!("{}a", "not ".repeat(MAX_DEPTH))).contains("嵌套过深"));
+        // 深度按嵌套计算，并列的括号不累加
+        assert!(TagFilter::parse(&vec!["(a)"; MAX_DEPTH * 2].join(" and ")).is_ok());
+    }
+}